@@ -0,0 +1,276 @@
+//! The regex-based tokenizer which the hand-written lexer replaced, kept so that the two can be
+//! benchmarked against each other. It compiles a regex for every candidate token at every position
+//! and recurses once per token.
+
+// the implementation is kept as it was, lints included
+#![allow(
+    clippy::iter_nth_zero,
+    clippy::len_zero,
+    clippy::needless_return,
+    clippy::useless_format
+)]
+
+use linger::{
+    error::TokenizerError::{self, *},
+    tokenizer::{AssignOp, Keyword, Operator, TokenValue, Trivia},
+};
+use regex::{Match, Regex};
+
+/// A token value along with its line and column.
+pub type LegacyToken = (TokenValue, usize, usize);
+
+const WHITESPACE_REGEX: &str = r"[[:space:]]+";
+const ASSIGN_REGEX: &str = r"=";
+const THIN_ARROW_REGEX: &str = r"->";
+const EQ_REGEX: &str = r"==";
+const NE_REGEX: &str = r"!=";
+const LT_REGEX: &str = r"<";
+const GT_REGEX: &str = r">";
+const LTE_REGEX: &str = r"<=";
+const GTE_REGEX: &str = r">=";
+const ID_REGEX: &str = r"([a-zA-Z][a-zA-Z0-9_]*)\b";
+const NUM_REGEX: &str = r"\d*\.?\d+";
+const PLUS_REGEX: &str = r"\+";
+const MINUS_REGEX: &str = r"\-";
+const STAR_REGEX: &str = r"\*";
+const SLASH_REGEX: &str = r"/";
+const DOUBLE_SLASH_REGEX: &str = r"//";
+const DOUBLE_PLUS_REGEX: &str = r"\+\+";
+const DOUBLE_MINUS_REGEX: &str = r"\-\-";
+const MOD_REGEX: &str = "%";
+const LPAREN_REGEX: &str = r"\(";
+const RPAREN_REGEX: &str = r"\)";
+const L_CURLY_BRACKET_REGEX: &str = r"\{";
+const R_CURLY_BRACKET_REGEX: &str = r"\}";
+const L_SQUARE_BRACKET_REGEX: &str = r"\[";
+const R_SQUARE_BRACKET_REGEX: &str = r"\]";
+const SEMICOLON_REGEX: &str = ";";
+const COMMA_REGEX: &str = ",";
+const QUOTE_REGEX: &str = "\"";
+const LOGIC_OR_REGEX: &str = r"\|\|";
+const LOGIC_AND_REGEX: &str = "&&";
+const LOGIC_NOT_REGEX: &str = "!";
+const ASSIGNMENT_PLUS_REGEX: &str = r"\+=";
+const ASSIGNMENT_MINUS_REGEX: &str = r"\-=";
+const DOT_REGEX: &str = r"\.";
+
+/// Returns the tokens which make up the program `s`.
+pub fn tokenize(s: &str) -> Result<Vec<LegacyToken>, TokenizerError> {
+    let enumerated_lines = s.split("\n").enumerate();
+    let mut tokens: Vec<LegacyToken> = vec![];
+    for (line_num, line) in enumerated_lines {
+        let mut tokenized_line = tokenize_helper(line, line_num + 1, 1)?;
+        tokenized_line.retain(|token| !matches!(token.0, TokenValue::TRIVIA(_)));
+        tokens.append(&mut tokenized_line)
+    }
+    Ok(tokens)
+}
+
+/// Returns the tokens which make up the program `s`. This is a helper function which is
+/// wrapped by [tokenize]. This function also takes a line and column number which are passed to
+/// created token structures.
+fn tokenize_helper(
+    s: &str,
+    line_num: usize,
+    col_num: usize,
+) -> Result<Vec<LegacyToken>, TokenizerError> {
+    if s.len() == 0 {
+        return Ok(vec![]);
+    }
+
+    let (token_value_option, token_length) = get_token_value(s)?;
+    let token_value = match token_value_option {
+        Some(token) => token,
+        None => return tokenize_helper(&s[token_length..], line_num, col_num + token_length),
+    };
+
+    match token_value {
+        TokenValue::QUOTE => {
+            let s = &s[token_length..];
+            let mut string_token_content = String::new();
+            let mut enumerated_character_iter = s.chars().enumerate();
+            while let Some((index, char)) = enumerated_character_iter.next() {
+                match char {
+                    '"' => {
+                        let mut tokens = vec![(
+                            TokenValue::STR(string_token_content.to_string()),
+                            line_num,
+                            col_num,
+                        )];
+                        let mut rest_tokens = tokenize_helper(
+                            &s[index + 1..],
+                            line_num,
+                            // the "plus 2" is to account for the opening and closing quotes for the string literal
+                            col_num + string_token_content.len() + 2,
+                        )?;
+                        tokens.append(&mut rest_tokens);
+                        return Ok(tokens);
+                    }
+                    '\\' => match enumerated_character_iter.nth(0) {
+                        Some((_, escaped_char)) => match escaped_char {
+                            'n' => string_token_content.push('\n'),
+                            'r' => string_token_content.push('\r'),
+                            't' => string_token_content.push('\t'),
+                            '\\' => string_token_content.push('\\'),
+                            '0' => string_token_content.push('\0'),
+                            '"' => string_token_content.push('"'),
+                            '\'' => string_token_content.push('\''),
+                            c => return Err(InvalidEscapeSequence(c)),
+                        },
+                        None => return Err(UnterminatedStringLiteral),
+                    },
+                    _ => string_token_content.push(char),
+                }
+            }
+            return Err(UnterminatedStringLiteral);
+        }
+        TokenValue::DOUBLE_SLASH => {
+            let comment = s[token_length..].trim_end().to_string();
+            return Ok(vec![(
+                TokenValue::TRIVIA(Trivia::Comment(comment)),
+                line_num,
+                col_num,
+            )]);
+        }
+        token_value => {
+            let mut tokens = vec![(token_value, line_num, col_num)];
+            let mut rest_tokens =
+                tokenize_helper(&s[token_length..], line_num, col_num + token_length)?;
+            tokens.append(&mut rest_tokens);
+            return Ok(tokens);
+        }
+    }
+}
+
+/// Tries to get a token beginning at the start of `s`. On success, this function returns an option
+/// of a [Token] that is None in the case of whitespace, or Some(Token) in all other cases. If the
+/// beginning of `s` is not a known token, this function returns a [TokenizerError].
+fn get_token_value(s: &str) -> Result<(Option<TokenValue>, usize), TokenizerError> {
+    // WHITESPACE TOKEN
+    if let Some(mat) = find(WHITESPACE_REGEX, s) {
+        Ok((None, mat.end()))
+
+    // KEYWORDS
+    } else if let Some(mat) = find("if", s) {
+        Ok((Some(TokenValue::KW(Keyword::If)), mat.end()))
+    } else if let Some(mat) = find("else", s) {
+        Ok((Some(TokenValue::KW(Keyword::Else)), mat.end()))
+    } else if let Some(mat) = find("proc", s) {
+        Ok((Some(TokenValue::KW(Keyword::Proc)), mat.end()))
+    } else if let Some(mat) = find("let", s) {
+        Ok((Some(TokenValue::KW(Keyword::Let)), mat.end()))
+    } else if let Some(mat) = find("true", s) {
+        Ok((Some(TokenValue::KW(Keyword::True)), mat.end()))
+    } else if let Some(mat) = find("false", s) {
+        Ok((Some(TokenValue::KW(Keyword::False)), mat.end()))
+    } else if let Some(mat) = find("return", s) {
+        Ok((Some(TokenValue::KW(Keyword::Return)), mat.end()))
+    } else if let Some(mat) = find("while", s) {
+        Ok((Some(TokenValue::KW(Keyword::While)), mat.end()))
+    } else if let Some(mat) = find("break", s) {
+        Ok((Some(TokenValue::KW(Keyword::Break)), mat.end()))
+    } else if let Some(mat) = find("continue", s) {
+        Ok((Some(TokenValue::KW(Keyword::Continue)), mat.end()))
+    } else if let Some(mat) = find("for", s) {
+        Ok((Some(TokenValue::KW(Keyword::For)), mat.end()))
+    } else if let Some(mat) = find("const", s) {
+        Ok((Some(TokenValue::KW(Keyword::Const)), mat.end()))
+    } else if let Some(mat) = find("nil", s) {
+        Ok((Some(TokenValue::KW(Keyword::Nil)), mat.end()))
+
+    // TWO-CHARACTER TOKENS
+    } else if let Some(mat) = find(NE_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::Ne)), mat.end()))
+    } else if let Some(mat) = find(EQ_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::Eq)), mat.end()))
+    } else if let Some(mat) = find(LTE_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::LTE)), mat.end()))
+    } else if let Some(mat) = find(GTE_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::GTE)), mat.end()))
+    } else if let Some(mat) = find(LOGIC_AND_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::LogicAnd)), mat.end()))
+    } else if let Some(mat) = find(LOGIC_OR_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::LogicOr)), mat.end()))
+    } else if let Some(mat) = find(DOUBLE_SLASH_REGEX, s) {
+        Ok((Some(TokenValue::DOUBLE_SLASH), mat.end()))
+    } else if let Some(mat) = find(THIN_ARROW_REGEX, s) {
+        Ok((Some(TokenValue::THIN_ARROW), mat.end()))
+    } else if let Some(mat) = find(DOUBLE_PLUS_REGEX, s) {
+        Ok((Some(TokenValue::DOUBLE_PLUS), mat.end()))
+    } else if let Some(mat) = find(DOUBLE_MINUS_REGEX, s) {
+        Ok((Some(TokenValue::DOUBLE_MINUS), mat.end()))
+    } else if let Some(mat) = find(ASSIGNMENT_PLUS_REGEX, s) {
+        Ok((Some(TokenValue::ASSIGN_OP(AssignOp::Plus)), mat.end()))
+    } else if let Some(mat) = find(ASSIGNMENT_MINUS_REGEX, s) {
+        Ok((Some(TokenValue::ASSIGN_OP(AssignOp::Minus)), mat.end()))
+
+    // ONE-CHARACTER TOKENS
+    } else if let Some(mat) = find(ASSIGN_REGEX, s) {
+        Ok((Some(TokenValue::ASSIGN), mat.end()))
+    } else if let Some(mat) = find(LT_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::LT)), mat.end()))
+    } else if let Some(mat) = find(GT_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::GT)), mat.end()))
+    } else if let Some(mat) = find(STAR_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::Times)), mat.end()))
+    } else if let Some(mat) = find(MOD_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::Mod)), mat.end()))
+    } else if let Some(mat) = find(SLASH_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::Div)), mat.end()))
+    } else if let Some(mat) = find(PLUS_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::Plus)), mat.end()))
+    } else if let Some(mat) = find(MINUS_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::Minus)), mat.end()))
+    } else if let Some(mat) = find(LPAREN_REGEX, s) {
+        Ok((Some(TokenValue::LPAREN), mat.end()))
+    } else if let Some(mat) = find(RPAREN_REGEX, s) {
+        Ok((Some(TokenValue::RPAREN), mat.end()))
+    } else if let Some(mat) = find(L_CURLY_BRACKET_REGEX, s) {
+        Ok((Some(TokenValue::L_CURLY_BRACKET), mat.end()))
+    } else if let Some(mat) = find(R_CURLY_BRACKET_REGEX, s) {
+        Ok((Some(TokenValue::R_CURLY_BRACKET), mat.end()))
+    } else if let Some(mat) = find(L_SQUARE_BRACKET_REGEX, s) {
+        Ok((Some(TokenValue::L_SQUARE_BRACKET), mat.end()))
+    } else if let Some(mat) = find(R_SQUARE_BRACKET_REGEX, s) {
+        Ok((Some(TokenValue::R_SQUARE_BRACKET), mat.end()))
+    } else if let Some(mat) = find(SEMICOLON_REGEX, s) {
+        Ok((Some(TokenValue::SEMICOLON), mat.end()))
+    } else if let Some(mat) = find(COMMA_REGEX, s) {
+        Ok((Some(TokenValue::COMMA), mat.end()))
+    } else if let Some(mat) = find(QUOTE_REGEX, s) {
+        Ok((Some(TokenValue::QUOTE), mat.end()))
+    } else if let Some(mat) = find(LOGIC_NOT_REGEX, s) {
+        Ok((Some(TokenValue::OP(Operator::LogicNot)), mat.end()))
+
+    // VARIABLE-LENGTH TOKENS
+    } else if let Some(mat) = find(ID_REGEX, s) {
+        Ok((Some(TokenValue::ID(mat.as_str().to_string())), mat.end()))
+    } else if let Some(mat) = find(NUM_REGEX, s) {
+        Ok((
+            Some(TokenValue::NUM(mat.as_str().parse::<f64>().expect("a match with the NUM_REGEX should imply that the string slice can be parsed into am i64"))),
+            mat.end(),
+        ))
+    } else if let Some(mat) = find(DOT_REGEX, s) {
+        Ok((Some(TokenValue::DOT), mat.end()))
+
+    // THE ERROR CASE
+    } else {
+        Err(UnknownToken({
+            let mut split =
+                s.split(|c: char| str_to_regex(WHITESPACE_REGEX).is_match(c.to_string().as_str()));
+            let unknown_token = split.nth(0).expect("some non-whitespace text since whitespace would have been matched on the first branch of the if statement");
+            format!("{}", unknown_token).to_string()
+        }))
+    }
+}
+
+/// Takes a string and returns the corresponding [Regex].
+fn str_to_regex(s: &str) -> Regex {
+    return Regex::new(format!("^({s})").as_str())
+        .expect("strings to be valid regular expressions");
+}
+
+/// Checks if `s` starts with the regular expression represented by `re`.
+fn find<'a>(re: &'a str, s: &'a str) -> Option<Match<'a>> {
+    return str_to_regex(re).find(s);
+}
@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+mod legacy;
+
+/// The example programs, which together exercise every kind of token.
+const EXAMPLES: [&str; 5] = [
+    include_str!("../../examples/fibonacci.ling"),
+    include_str!("../../examples/filter.ling"),
+    include_str!("../../examples/fizzbuzz.ling"),
+    include_str!("../../examples/fold_left.ling"),
+    include_str!("../../examples/map.ling"),
+];
+
+fn tokenizers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+    // the legacy tokenizer takes long enough that the default sample size makes runs drag on
+    group.sample_size(10);
+
+    for copies in [1, 10] {
+        let source = EXAMPLES.concat().repeat(copies);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::new("lexer", copies), &source, |b, source| {
+            b.iter(|| linger::tokenizer::tokenize(source).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("legacy", copies), &source, |b, source| {
+            b.iter(|| legacy::tokenize(source).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, tokenizers);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linger::formatter::format_source;
+
+// formatting is idempotent, so formatting a formatted program leaves it unchanged
+fuzz_target!(|source: &str| {
+    if let Ok(formatted) = format_source(source) {
+        assert_eq!(format_source(&formatted).ok(), Some(formatted));
+    }
+});
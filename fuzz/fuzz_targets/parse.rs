@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linger::{
+    parser::{parse_expression, parse_program_recovering, parse_source_file},
+    tokenizer::{tokenize, tokenize_with_trivia},
+};
+
+fuzz_target!(|source: &str| {
+    if let Ok(tokens) = tokenize(source) {
+        let _ = parse_program_recovering(tokens.as_slice());
+        let _ = parse_expression(tokens.as_slice());
+    }
+    if let Ok(tokens) = tokenize_with_trivia(source) {
+        let _ = parse_source_file(tokens.as_slice());
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linger::tokenizer::{tokenize, tokenize_with_trivia};
+
+fuzz_target!(|source: &str| {
+    let _ = tokenize(source);
+    let _ = tokenize_with_trivia(source);
+});
@@ -0,0 +1,685 @@
+use std::fmt;
+
+use crate::{
+    error::ParseError,
+    lint::{lint, Level, LintConfig, Rule},
+    parser::{parse_declarations_recovering, Builtin, Span},
+    tokenizer::{
+        error_position, tokenize, Keyword, Token,
+        TokenValue::{self, *},
+        Trivia,
+    },
+    typecheck::typecheck,
+};
+
+/// How serious a [Problem] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem in a [Document], found without running it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub span: Span,
+    pub severity: Severity,
+    pub message: String,
+    /// The lint rule which found the problem, or `None` if the document could not be tokenized or
+    /// parsed or the problem is a type error.
+    pub rule: Option<Rule>,
+}
+
+/// The kind of thing that a name is bound to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DefinitionKind {
+    /// A top-level procedure along with its parameters.
+    Proc(Vec<String>),
+    /// A parameter of a top-level procedure or a lambda.
+    Param,
+    Let,
+    Const,
+}
+
+/// The place where a name is bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    /// The position of the name in the binding.
+    pub span: Span,
+    /// The doc comments of a top-level procedure.
+    pub doc: Option<String>,
+}
+
+impl fmt::Display for Definition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DefinitionKind::Proc(params) => write!(f, "proc {}({})", self.name, params.join(", ")),
+            DefinitionKind::Param => write!(f, "(parameter) {}", self.name),
+            DefinitionKind::Let => write!(f, "let {}", self.name),
+            DefinitionKind::Const => write!(f, "const {}", self.name),
+        }
+    }
+}
+
+/// The kind of name offered by a [Completion].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionKind {
+    Builtin,
+    Proc,
+    Variable,
+}
+
+/// A name which can be used at a position in a [Document].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// A short description of the name, such as the signature of a procedure.
+    pub detail: String,
+}
+
+/// A top-level procedure in a [Document].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// The signature of the procedure.
+    pub detail: String,
+    /// The position of the `proc` keyword which begins the procedure.
+    pub start: Span,
+    /// The position just after the closing curly bracket which ends the procedure.
+    pub end: Span,
+    /// The position of the name of the procedure.
+    pub name_span: Span,
+}
+
+/// A Linger source file open in an editor. Every query is answered from the tokens of the file,
+/// so that go-to-definition, hover, completion and symbols keep working while the file has parse
+/// errors.
+#[derive(Clone, Debug)]
+pub struct Document {
+    tokens: Vec<Token>,
+    problems: Vec<Problem>,
+}
+
+impl Document {
+    pub fn new(source: &str) -> Self {
+        let tokens = match tokenize(source) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                return Self {
+                    tokens: vec![],
+                    problems: vec![Problem {
+                        span: tokenizer_error_span(source),
+                        severity: Severity::Error,
+                        message: e.to_string(),
+                        rule: None,
+                    }],
+                }
+            }
+        };
+
+        let problems = match parse_declarations_recovering(tokens.as_slice()) {
+            (_, errors) if !errors.is_empty() => errors
+                .into_iter()
+                .map(|e| parse_error_problem(e, &tokens))
+                .collect(),
+            (declarations, _) if !declarations.procedures.iter().any(|proc| proc.name == "main") => {
+                vec![parse_error_problem(ParseError::NoMain, &tokens)]
+            }
+            (declarations, _) => {
+                // calls with the wrong number of arguments are only reported as warnings, since
+                // the call may be in code that is never run
+                let config = LintConfig::new().with_level(Rule::ArgMismatch, Level::Warn);
+                let mut problems: Vec<Problem> = lint(&declarations.procedures, &config)
+                    .into_iter()
+                    .map(|diagnostic| Problem {
+                        span: diagnostic.span,
+                        severity: match diagnostic.level {
+                            Level::Deny => Severity::Error,
+                            _ => Severity::Warning,
+                        },
+                        message: diagnostic.message,
+                        rule: Some(diagnostic.rule),
+                    })
+                    .collect();
+                problems.extend(typecheck(&declarations).into_iter().map(|(span, e)| Problem {
+                    span,
+                    severity: Severity::Error,
+                    message: e.to_string(),
+                    rule: None,
+                }));
+                problems.sort_by_key(|problem| problem.span);
+                problems
+            }
+        };
+
+        Self { tokens, problems }
+    }
+
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// Returns where the name at `position` is bound, or `None` if there is no name at
+    /// `position` or if it is not bound in the document.
+    pub fn definition(&self, position: Span) -> Option<Definition> {
+        let index = self.name_at(position)?;
+        let name = match &self.tokens[index].0 {
+            ID(name) => name,
+            _ => return None,
+        };
+
+        let bindings = self.bindings();
+        if let Some((_, definition)) = bindings.iter().find(|(i, _)| *i == index) {
+            return Some(definition.clone());
+        }
+
+        self.locals_before(index)
+            .into_iter()
+            .rev()
+            .find(|definition| definition.name == *name)
+            .or_else(|| {
+                self.procedures()
+                    .into_iter()
+                    .find(|definition| definition.name == *name)
+            })
+    }
+
+    /// Returns a description of the name at `position`, or `None` if there is no name at
+    /// `position` or if it is not bound.
+    pub fn hover(&self, position: Span) -> Option<String> {
+        if let Some(definition) = self.definition(position) {
+            return Some(definition.to_string());
+        }
+        let index = self.name_at(position)?;
+        match &self.tokens[index].0 {
+            ID(name) => Builtin::ALL
+                .into_iter()
+                .find(|builtin| builtin.to_string() == *name)
+                .map(|builtin| format!("(builtin) {builtin}")),
+            _ => None,
+        }
+    }
+
+    /// Returns the builtins, top-level procedures and variables which can be used at `position`.
+    pub fn completions(&self, position: Span) -> Vec<Completion> {
+        let index = self
+            .tokens
+            .iter()
+            .position(|Token(_, line, col, ..)| (*line, *col) >= (position.line, position.col))
+            .unwrap_or(self.tokens.len());
+
+        let mut completions: Vec<Completion> = Builtin::ALL
+            .into_iter()
+            .map(|builtin| Completion {
+                label: builtin.to_string(),
+                kind: CompletionKind::Builtin,
+                detail: format!("(builtin) {builtin}"),
+            })
+            .collect();
+        completions.extend(self.procedures().into_iter().map(|definition| Completion {
+            label: definition.name.to_string(),
+            kind: CompletionKind::Proc,
+            detail: definition.to_string(),
+        }));
+
+        // inner bindings shadow outer bindings with the same name
+        let mut locals: Vec<Definition> = vec![];
+        for definition in self.locals_before(index).into_iter().rev() {
+            if !locals.iter().any(|local| local.name == definition.name) {
+                locals.push(definition);
+            }
+        }
+        for definition in locals.into_iter().rev() {
+            completions.retain(|completion| completion.label != definition.name);
+            completions.push(Completion {
+                label: definition.name.to_string(),
+                kind: CompletionKind::Variable,
+                detail: definition.to_string(),
+            });
+        }
+
+        completions
+    }
+
+    /// Returns the top-level procedures of the document.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols = vec![];
+        for (index, window) in self.tokens.windows(2).enumerate() {
+            let (start, name, name_span) = match window {
+                [Token(KW(Keyword::Proc), line, col, ..), Token(ID(name), name_line, name_col, ..)] => {
+                    (span(*line, *col), name, span(*name_line, *name_col))
+                }
+                _ => continue,
+            };
+            let params = params_after(&self.tokens, index + 2);
+            let end = self.tokens[index..]
+                .iter()
+                .position(|token| token.0 == L_CURLY_BRACKET)
+                .and_then(|offset| matching_bracket(&self.tokens, index + offset))
+                .map(|end| {
+                    let Token(_, line, col, ..) = &self.tokens[end];
+                    span(*line, *col + 1)
+                })
+                .unwrap_or(name_span);
+
+            symbols.push(Symbol {
+                name: name.to_string(),
+                detail: format!("proc {name}({})", params.join(", ")),
+                start,
+                end,
+                name_span,
+            });
+        }
+        symbols
+    }
+
+    /// Returns the index of the identifier token which contains `position`, including the
+    /// position just after its last character.
+    fn name_at(&self, position: Span) -> Option<usize> {
+        self.tokens.iter().position(|token| match token {
+            Token(ID(name), line, col, ..) => {
+                *line == position.line
+                    && *col <= position.col
+                    && position.col <= col + name.chars().count()
+            }
+            _ => false,
+        })
+    }
+
+    /// Returns every top-level procedure in the document.
+    fn procedures(&self) -> Vec<Definition> {
+        self.tokens
+            .windows(3)
+            .enumerate()
+            .filter_map(|(index, window)| match window {
+                [Token(KW(Keyword::Proc), ..), Token(ID(name), line, col, ..), Token(LPAREN, ..)] => {
+                    Some(Definition {
+                        name: name.to_string(),
+                        kind: DefinitionKind::Proc(params_after(&self.tokens, index + 2)),
+                        span: span(*line, *col),
+                        doc: doc_before(&self.tokens, index),
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the index of the name token of every variable binding in the document, along with
+    /// its [Definition].
+    fn bindings(&self) -> Vec<(usize, Definition)> {
+        let mut bindings = vec![];
+        for (index, token) in self.tokens.iter().enumerate() {
+            match (token, self.tokens.get(index + 1)) {
+                (Token(KW(Keyword::Let), ..), Some(Token(ID(name), line, col, ..))) => bindings
+                    .push((
+                        index + 1,
+                        definition(name, DefinitionKind::Let, *line, *col),
+                    )),
+                (Token(KW(Keyword::Const), ..), Some(Token(ID(name), line, col, ..))) => bindings
+                    .push((
+                        index + 1,
+                        definition(name, DefinitionKind::Const, *line, *col),
+                    )),
+                (Token(LPAREN, ..), _) if self.is_param_list(index) => {
+                    for param_index in param_indices(&self.tokens, index) {
+                        if let Token(ID(name), line, col, ..) = &self.tokens[param_index] {
+                            bindings.push((
+                                param_index,
+                                definition(name, DefinitionKind::Param, *line, *col),
+                            ));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        bindings
+    }
+
+    /// Returns whether the left parenthesis at `index` begins the parameters of a top-level
+    /// procedure or a lambda.
+    fn is_param_list(&self, index: usize) -> bool {
+        self.is_proc_header(index)
+            || !self.is_proc_type(index)
+                && matching_bracket(&self.tokens, index).is_some_and(|end| {
+                    matches!(self.tokens.get(end + 1), Some(Token(THIN_ARROW, ..)))
+                })
+    }
+
+    /// Returns whether the left parenthesis at `index` begins the parameters of a top-level
+    /// procedure.
+    fn is_proc_header(&self, index: usize) -> bool {
+        matches!(
+            index.checked_sub(2).map(|i| &self.tokens[i..index]),
+            Some([Token(KW(Keyword::Proc), ..), Token(ID(_), ..)])
+        )
+    }
+
+    /// Returns whether the left parenthesis at `index` begins the parameter types of a procedure
+    /// type such as `proc(num) -> num`.
+    fn is_proc_type(&self, index: usize) -> bool {
+        matches!(
+            index.checked_sub(1).map(|i| &self.tokens[i]),
+            Some(Token(KW(Keyword::Proc), ..))
+        )
+    }
+
+    /// Returns the variables which are in scope at the token at `index`, from the outermost to
+    /// the innermost. A `let` or `const` binding is in scope after the end of its statement, and
+    /// parameters are in scope in the body of their procedure or lambda.
+    fn locals_before(&self, index: usize) -> Vec<Definition> {
+        let bindings = self.bindings();
+        let mut scopes = vec![Scope::new(ScopeEnd::Bracket, vec![])];
+        let mut pending_params = vec![];
+        let mut pending_bindings: Vec<(Definition, usize, usize)> = vec![];
+        let mut in_for_header = false;
+        let mut paren_depth: usize = 0;
+
+        for (i, token) in self.tokens[..index.min(self.tokens.len())]
+            .iter()
+            .enumerate()
+        {
+            match &token.0 {
+                L_CURLY_BRACKET => {
+                    let end = if in_for_header {
+                        ScopeEnd::ForBody
+                    } else {
+                        ScopeEnd::Bracket
+                    };
+                    in_for_header = false;
+                    scopes.push(Scope::new(end, std::mem::take(&mut pending_params)));
+                }
+                R_CURLY_BRACKET => {
+                    while let Some(scope) = scopes.pop() {
+                        match scope.end {
+                            ScopeEnd::Bracket => break,
+                            ScopeEnd::ForBody => {
+                                scopes.pop(); // the scope of the loop variable
+                                break;
+                            }
+                            _ => (),
+                        }
+                    }
+                    if scopes.is_empty() {
+                        scopes.push(Scope::new(ScopeEnd::Bracket, vec![]));
+                    }
+                    pending_bindings.retain(|(_, depth, _)| *depth <= scopes.len());
+                }
+                LPAREN => paren_depth += 1,
+                RPAREN => {
+                    paren_depth = paren_depth.saturating_sub(1);
+                    close_expression_scopes(&mut scopes, |depth| depth > paren_depth);
+                }
+                COMMA => close_expression_scopes(&mut scopes, |depth| depth >= paren_depth),
+                SEMICOLON => {
+                    close_expression_scopes(&mut scopes, |depth| depth >= paren_depth);
+                    let depth = scopes.len();
+                    let (finished, unfinished) = pending_bindings.into_iter().partition(
+                        |(_, binding_depth, binding_paren_depth)| {
+                            *binding_depth == depth && *binding_paren_depth == paren_depth
+                        },
+                    );
+                    pending_bindings = unfinished;
+                    if let Some(scope) = scopes.last_mut() {
+                        scope.extend(finished.into_iter().map(|(definition, ..)| definition));
+                    }
+                }
+                KW(Keyword::For) => {
+                    scopes.push(Scope::new(ScopeEnd::ForHeader, vec![]));
+                    in_for_header = true;
+                }
+                KW(Keyword::Let | Keyword::Const) => {
+                    if let Some((_, definition)) = bindings.iter().find(|(j, _)| *j == i + 1) {
+                        pending_bindings.push((definition.clone(), scopes.len(), paren_depth));
+                    }
+                }
+                THIN_ARROW => {
+                    let start = i
+                        .checked_sub(1)
+                        .and_then(|end| matching_open_paren(&self.tokens, end));
+                    // the arrows in procedure headers and procedure types do not begin lambdas
+                    if start
+                        .is_some_and(|start| self.is_proc_header(start) || self.is_proc_type(start))
+                    {
+                        continue;
+                    }
+                    let params = start
+                        .map(|start| params_between(&bindings, start, i))
+                        .unwrap_or_default();
+                    match self.tokens.get(i + 1) {
+                        Some(Token(L_CURLY_BRACKET, ..)) => pending_params = params,
+                        _ => scopes.push(Scope::new(ScopeEnd::Expression(paren_depth), params)),
+                    }
+                }
+                KW(Keyword::Proc) => {
+                    if let Some(end) = matching_bracket(&self.tokens, i + 2) {
+                        pending_params = params_between(&bindings, i + 2, end);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        scopes
+            .into_iter()
+            .flat_map(|scope| scope.definitions)
+            .collect()
+    }
+}
+
+/// How a scope tracked by [Document::locals_before] ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScopeEnd {
+    /// A block, which ends at its closing curly bracket.
+    Bracket,
+    /// The header of a `for` loop, which ends along with the loop body.
+    ForHeader,
+    /// The body of a `for` loop, which also ends the scope of the loop header.
+    ForBody,
+    /// A lambda whose body is not a block, which ends at the first comma, semicolon or closing
+    /// parenthesis outside of the given depth of parentheses.
+    Expression(usize),
+}
+
+#[derive(Clone, Debug)]
+struct Scope {
+    end: ScopeEnd,
+    definitions: Vec<Definition>,
+}
+
+impl Scope {
+    fn new(end: ScopeEnd, definitions: Vec<Definition>) -> Self {
+        Self { end, definitions }
+    }
+
+    fn extend(&mut self, definitions: impl IntoIterator<Item = Definition>) {
+        self.definitions.extend(definitions);
+    }
+}
+
+fn close_expression_scopes(scopes: &mut Vec<Scope>, should_close: impl Fn(usize) -> bool) {
+    while let Some(Scope {
+        end: ScopeEnd::Expression(depth),
+        ..
+    }) = scopes.last()
+    {
+        if !should_close(*depth) {
+            break;
+        }
+        scopes.pop();
+    }
+}
+
+fn span(line: usize, col: usize) -> Span {
+    Span { line, col }
+}
+
+fn definition(name: &str, kind: DefinitionKind, line: usize, col: usize) -> Definition {
+    Definition {
+        name: name.to_string(),
+        kind,
+        span: span(line, col),
+        doc: None,
+    }
+}
+
+/// Returns the text of the doc comments just before the token at `index`.
+fn doc_before(tokens: &[Token], index: usize) -> Option<String> {
+    let lines: Vec<&str> = tokens[..index]
+        .iter()
+        .rev()
+        .map_while(|token| match &token.0 {
+            TRIVIA(Trivia::DocComment(line)) => Some(line.as_str()),
+            _ => None,
+        })
+        .collect();
+    match lines.is_empty() {
+        true => None,
+        false => Some(lines.into_iter().rev().collect::<Vec<_>>().join("\n")),
+    }
+}
+
+/// Returns the parameters in `bindings` whose names are between the tokens at `start` and `end`.
+fn params_between(bindings: &[(usize, Definition)], start: usize, end: usize) -> Vec<Definition> {
+    bindings
+        .iter()
+        .filter(|(index, _)| start < *index && *index < end)
+        .map(|(_, definition)| definition.clone())
+        .collect()
+}
+
+/// Returns the names of the parameters in the parameter list whose left parenthesis is at `index`,
+/// with the name of a rest parameter written after its `...`.
+fn params_after(tokens: &[Token], index: usize) -> Vec<String> {
+    param_indices(tokens, index)
+        .into_iter()
+        .filter_map(|index| match (&tokens[index - 1].0, &tokens[index].0) {
+            (ELLIPSIS, ID(name)) => Some(format!("...{name}")),
+            (_, ID(name)) => Some(name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the indices of the names in the parameter list whose left parenthesis is at `index`,
+/// skipping the type annotations and default values after them.
+fn param_indices(tokens: &[Token], index: usize) -> Vec<usize> {
+    let mut indices = vec![];
+    let mut depth = 0;
+    for (offset, token) in tokens.get(index..).unwrap_or_default().iter().enumerate() {
+        match token.0 {
+            LPAREN => depth += 1,
+            RPAREN => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            ID(_)
+                if depth == 1
+                    && matches!(tokens[index + offset - 1].0, LPAREN | COMMA | ELLIPSIS) =>
+            {
+                indices.push(index + offset)
+            }
+            _ => (),
+        }
+    }
+    indices
+}
+
+/// Returns the index of the bracket which closes the bracket at `index`.
+fn matching_bracket(tokens: &[Token], index: usize) -> Option<usize> {
+    let close = match tokens.get(index)?.0 {
+        LPAREN => RPAREN,
+        L_CURLY_BRACKET => R_CURLY_BRACKET,
+        L_SQUARE_BRACKET => R_SQUARE_BRACKET,
+        _ => return None,
+    };
+    let open = &tokens[index].0;
+    let mut depth = 0;
+    for (offset, token) in tokens[index..].iter().enumerate() {
+        if token.0 == *open {
+            depth += 1;
+        } else if token.0 == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(index + offset);
+            }
+        }
+    }
+    None
+}
+
+/// Returns the index of the left parenthesis which is closed by the right parenthesis at `index`.
+fn matching_open_paren(tokens: &[Token], index: usize) -> Option<usize> {
+    if tokens.get(index)?.0 != RPAREN {
+        return None;
+    }
+    let mut depth = 0;
+    for i in (0..=index).rev() {
+        match tokens[i].0 {
+            RPAREN => depth += 1,
+            LPAREN => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Returns the position of the token at which tokenizing `source` fails. [error_position] runs
+/// the lexer again up to the error and reports where the failing token begins, so an unterminated
+/// string or block comment is reported at its opening delimiter even if it spans several lines.
+fn tokenizer_error_span(source: &str) -> Span {
+    let (line, col) = error_position(source).unwrap_or((1, 1));
+    span(line, col)
+}
+
+fn parse_error_problem(e: ParseError, tokens: &[Token]) -> Problem {
+    let position = match &e {
+        ParseError::UnexpectedToken(token)
+        | ParseError::Expected(_, token)
+        | ParseError::NestedTooDeeply(token) => span(token.1, token.2),
+        ParseError::UnexpectedEOF => match tokens.last() {
+            Some(Token(value, line, col, ..)) => span(*line, col + token_len(value)),
+            None => span(1, 1),
+        },
+        ParseError::MultipleSameNamedProcs(name) => tokens
+            .windows(2)
+            .filter_map(|window| match window {
+                [Token(KW(Keyword::Proc), ..), Token(ID(proc_name), line, col, ..)]
+                    if proc_name == name =>
+                {
+                    Some(span(*line, *col))
+                }
+                _ => None,
+            })
+            .nth(1)
+            .unwrap_or(span(1, 1)),
+        _ => span(1, 1),
+    };
+    Problem {
+        span: position,
+        severity: Severity::Error,
+        message: e.to_string(),
+        rule: None,
+    }
+}
+
+/// Returns the number of characters in the source text of a token with the value `value`, for the
+/// tokens whose source text is known.
+fn token_len(value: &TokenValue) -> usize {
+    match value {
+        ID(name) => name.chars().count(),
+        THIN_ARROW | DOUBLE_PLUS | DOUBLE_MINUS => 2,
+        _ => 1,
+    }
+}
@@ -0,0 +1,464 @@
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, BufRead, Stdin, Stdout, Write},
+    process::ExitCode,
+    rc::Rc,
+};
+
+use linger::{
+    error::RuntimeError,
+    interpreter::{Capabilities, DebugFrontend, Debugger, Interpreter, Pause, PauseReason, Resume},
+    parser::{parse_program_recovering, Program},
+    tokenizer::tokenize,
+    Writer,
+};
+use serde_json::{json, Value as Json};
+
+/// The id of the only thread of a Linger program.
+const THREAD_ID: u64 = 1;
+
+/// The reference of the variables of the innermost frame, which are the only ones that can be
+/// inspected.
+const LOCALS: u64 = 1;
+
+/// A Linger debug adapter which speaks the Debug Adapter Protocol over stdin and stdout. The
+/// program is run once the client has sent both `launch` and `configurationDone`, and messages
+/// are only read while the program is paused or after it has ended.
+fn main() -> ExitCode {
+    let connection = Rc::new(RefCell::new(Connection {
+        input: io::stdin(),
+        output: io::stdout(),
+        seq: 0,
+        closed: false,
+    }));
+    let mut launch: Option<Launch> = None;
+    let mut breakpoints: Vec<usize> = vec![];
+    let mut configured = false;
+
+    loop {
+        let request = match connection.borrow_mut().read() {
+            Ok(Some(request)) => request,
+            Ok(None) => return ExitCode::FAILURE, // the client went away without `disconnect`
+            Err(e) => {
+                eprintln!("error reading message: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+        let arguments = &request["arguments"];
+
+        let result = match request["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsEvaluateForHovers": true,
+                    "supportsTerminateRequest": true,
+                });
+                let mut connection = connection.borrow_mut();
+                connection
+                    .respond(&request, capabilities)
+                    .and_then(|_| connection.event("initialized", json!({})))
+            }
+            "launch" => match Launch::new(arguments) {
+                Ok(new_launch) => {
+                    launch = Some(new_launch);
+                    connection.borrow_mut().respond(&request, json!({}))
+                }
+                Err(message) => connection.borrow_mut().fail(&request, &message),
+            },
+            "setBreakpoints" => {
+                breakpoints = breakpoint_lines(arguments);
+                let body = breakpoints_body(&breakpoints);
+                connection.borrow_mut().respond(&request, body)
+            }
+            "configurationDone" => {
+                configured = true;
+                connection.borrow_mut().respond(&request, json!({}))
+            }
+            "threads" => connection.borrow_mut().respond(&request, threads_body()),
+            "disconnect" | "terminate" => {
+                return match connection.borrow_mut().respond(&request, json!({})) {
+                    Ok(()) => ExitCode::SUCCESS,
+                    Err(_) => ExitCode::FAILURE,
+                };
+            }
+            command => {
+                let message = format!("unsupported request \"{command}\"");
+                connection.borrow_mut().fail(&request, &message)
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("error writing message: {e}");
+            return ExitCode::FAILURE;
+        }
+
+        if configured {
+            if let Some(launch) = launch.take() {
+                if let Err(e) = run(launch, &breakpoints, &connection) {
+                    eprintln!("error writing message: {e}");
+                    return ExitCode::FAILURE;
+                }
+                if connection.borrow().closed {
+                    return ExitCode::SUCCESS;
+                }
+            }
+        }
+    }
+}
+
+/// The program to debug and how to run it, from the arguments of a `launch` request.
+struct Launch {
+    path: String,
+    program: Program,
+    args: Vec<String>,
+    stop_on_entry: bool,
+}
+
+impl Launch {
+    /// Reads and parses the program named in `arguments`, returning the message of the error
+    /// response if it cannot be run.
+    fn new(arguments: &Json) -> Result<Self, String> {
+        let path = match arguments["program"].as_str() {
+            Some(path) => path.to_string(),
+            None => return Err("missing \"program\" argument".to_string()),
+        };
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("error opening {path}: {e}"))?;
+        let tokens = tokenize(content.as_str()).map_err(|e| e.to_string())?;
+        let program = parse_program_recovering(tokens.as_slice()).map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            errors.join("\n")
+        })?;
+
+        let args = match arguments["args"].as_array() {
+            Some(args) => args
+                .iter()
+                .filter_map(|arg| arg.as_str().map(str::to_string))
+                .collect(),
+            None => vec![],
+        };
+        Ok(Self {
+            path,
+            program,
+            args,
+            stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+        })
+    }
+}
+
+/// Runs the launched program to the end, then tells the client that it has exited.
+fn run(launch: Launch, breakpoints: &[usize], connection: &Shared) -> io::Result<()> {
+    let adapter = Adapter {
+        connection: connection.clone(),
+        path: launch.path,
+    };
+    let mut debugger = Debugger::new(adapter).with_breakpoints(breakpoints.iter().copied());
+    if launch.stop_on_entry {
+        debugger = debugger.with_stop_on_entry();
+    }
+
+    // the client speaks to the adapter over standard input, so the program reads from an empty
+    // input stream
+    let mut writer = Writer::new(Box::new(Output {
+        connection: connection.clone(),
+        category: "stdout",
+    }))
+    .with_stderr(Box::new(Output {
+        connection: connection.clone(),
+        category: "stderr",
+    }))
+    .with_stdin(Box::new(io::empty()));
+    let mut interpreter = Interpreter::new(&mut writer)
+        .with_capabilities(Capabilities::all())
+        .with_args(launch.args)
+        .with_debugger(debugger);
+    let exit_code = match interpreter.run(launch.program) {
+        Ok(_) => 0,
+        Err(RuntimeError::Exit(code)) => code,
+        Err(RuntimeError::Cancelled) if connection.borrow().closed => return Ok(()),
+        Err(e) => {
+            let output = json!({ "category": "stderr", "output": format!("{e}\n") });
+            connection.borrow_mut().event("output", output)?;
+            1
+        }
+    };
+
+    let mut connection = connection.borrow_mut();
+    connection.event("exited", json!({ "exitCode": exit_code }))?;
+    connection.event("terminated", json!({}))
+}
+
+/// A [DebugFrontend] which tells the client when the program pauses, and answers its requests
+/// until it resumes the program.
+struct Adapter {
+    connection: Shared,
+    path: String,
+}
+
+impl DebugFrontend for Adapter {
+    fn paused(&mut self, pause: &mut Pause) -> Resume {
+        let reason = match pause.reason() {
+            PauseReason::Entry => "entry",
+            PauseReason::Breakpoint => "breakpoint",
+            PauseReason::Step => "step",
+        };
+        let stopped = json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true });
+        if self
+            .connection
+            .borrow_mut()
+            .event("stopped", stopped)
+            .is_err()
+        {
+            return self.close();
+        }
+
+        loop {
+            // the connection is not borrowed while an expression is evaluated, since the
+            // expression may print
+            let read = self.connection.borrow_mut().read();
+            let request = match read {
+                Ok(Some(request)) => request,
+                Ok(None) | Err(_) => return self.close(),
+            };
+            let arguments = &request["arguments"];
+
+            let (body, resume) = match request["command"].as_str().unwrap_or_default() {
+                "threads" => (threads_body(), None),
+                "stackTrace" => (self.stack_trace_body(pause), None),
+                "scopes" => {
+                    let scopes = match arguments["frameId"].as_u64() {
+                        Some(0) => json!([
+                            { "name": "Locals", "variablesReference": LOCALS, "expensive": false },
+                        ]),
+                        _ => json!([]),
+                    };
+                    (json!({ "scopes": scopes }), None)
+                }
+                "variables" => {
+                    let variables: Vec<Json> = match arguments["variablesReference"].as_u64() {
+                        Some(LOCALS) => pause
+                            .bindings()
+                            .into_iter()
+                            .map(|(name, value)| {
+                                json!({
+                                    "name": name,
+                                    "value": value.to_quoted_string(),
+                                    "variablesReference": 0,
+                                })
+                            })
+                            .collect(),
+                        _ => vec![],
+                    };
+                    (json!({ "variables": variables }), None)
+                }
+                "evaluate" => {
+                    let expression = arguments["expression"].as_str().unwrap_or_default();
+                    match pause.evaluate(expression) {
+                        Ok(value) => (
+                            json!({ "result": value.to_quoted_string(), "variablesReference": 0 }),
+                            None,
+                        ),
+                        Err(message) => {
+                            let sent = self.connection.borrow_mut().fail(&request, &message);
+                            match sent {
+                                Ok(()) => continue,
+                                Err(_) => return self.close(),
+                            }
+                        }
+                    }
+                }
+                "setBreakpoints" => {
+                    let lines = breakpoint_lines(arguments);
+                    let body = breakpoints_body(&lines);
+                    *pause.breakpoints() = lines.into_iter().collect();
+                    (body, None)
+                }
+                "continue" => (
+                    json!({ "allThreadsContinued": true }),
+                    Some(Resume::Continue),
+                ),
+                "next" => (json!({}), Some(Resume::StepOver)),
+                "stepIn" => (json!({}), Some(Resume::StepIn)),
+                "stepOut" => (json!({}), Some(Resume::StepOut)),
+                "disconnect" | "terminate" => {
+                    let _ = self.connection.borrow_mut().respond(&request, json!({}));
+                    return self.close();
+                }
+                command => {
+                    let message = format!("unsupported request \"{command}\"");
+                    let sent = self.connection.borrow_mut().fail(&request, &message);
+                    match sent {
+                        Ok(()) => continue,
+                        Err(_) => return self.close(),
+                    }
+                }
+            };
+
+            if self
+                .connection
+                .borrow_mut()
+                .respond(&request, body)
+                .is_err()
+            {
+                return self.close();
+            }
+            if let Some(resume) = resume {
+                return resume;
+            }
+        }
+    }
+}
+
+impl Adapter {
+    /// Returns the frames of the paused program, starting with the innermost one.
+    fn stack_trace_body(&self, pause: &Pause) -> Json {
+        let frames: Vec<Json> = pause
+            .stack()
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(id, frame)| {
+                json!({
+                    "id": id,
+                    "name": frame.name,
+                    "source": { "path": self.path },
+                    "line": frame.line,
+                    "column": 1,
+                })
+            })
+            .collect();
+        json!({ "totalFrames": frames.len(), "stackFrames": frames })
+    }
+
+    /// Stops the program after the client has gone away or asked to disconnect.
+    fn close(&mut self) -> Resume {
+        self.connection.borrow_mut().closed = true;
+        Resume::Stop
+    }
+}
+
+/// Sends the output of the program to the client as `output` events.
+struct Output {
+    connection: Shared,
+    category: &'static str,
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let output = String::from_utf8_lossy(buf);
+        let body = json!({ "category": self.category, "output": output });
+        self.connection.borrow_mut().event("output", body)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+type Shared = Rc<RefCell<Connection>>;
+
+/// The connection to the client, which is shared by the adapter and the output of the program.
+struct Connection {
+    /// The standard input stream, which is only locked while a message is read from it, since a
+    /// [Writer] locks it while it is created.
+    input: Stdin,
+    output: Stdout,
+    /// The sequence number of the last message sent to the client.
+    seq: u64,
+    /// Whether the client has gone away or asked to disconnect.
+    closed: bool,
+}
+
+impl Connection {
+    fn read(&mut self) -> io::Result<Option<Json>> {
+        read_message(&mut self.input.lock())
+    }
+
+    fn send(&mut self, mut message: Json) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        write_message(&mut self.output, &message)
+    }
+
+    fn respond(&mut self, request: &Json, body: Json) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }))
+    }
+
+    fn fail(&mut self, request: &Json, message: &str) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }))
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+}
+
+fn threads_body() -> Json {
+    json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })
+}
+
+/// Returns the lines of the breakpoints in the arguments of a `setBreakpoints` request.
+fn breakpoint_lines(arguments: &Json) -> Vec<usize> {
+    match arguments["breakpoints"].as_array() {
+        Some(breakpoints) => breakpoints
+            .iter()
+            .filter_map(|breakpoint| breakpoint["line"].as_u64())
+            .map(|line| line as usize)
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn breakpoints_body(lines: &[usize]) -> Json {
+    let breakpoints: Vec<Json> = lines
+        .iter()
+        .map(|line| json!({ "verified": true, "line": line }))
+        .collect();
+    json!({ "breakpoints": breakpoints })
+}
+
+/// Reads a message with a `Content-Length` header, returning `None` at the end of the input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Json>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(output: &mut impl Write, message: &Json) -> io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    output.flush()
+}
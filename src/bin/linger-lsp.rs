@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    process::ExitCode,
+};
+
+use linger::{
+    analysis::{CompletionKind, Document, Severity},
+    parser::Span,
+};
+use serde_json::{json, Value};
+
+/// The JSON-RPC error code for a request whose method the server does not support.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A Linger language server which speaks the Language Server Protocol over stdin and stdout.
+fn main() -> ExitCode {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let mut server = Server::default();
+
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) => return ExitCode::FAILURE, // the client went away without `exit`
+            Err(e) => {
+                eprintln!("error reading message: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+
+        if message["method"] == "exit" {
+            return match server.shut_down {
+                true => ExitCode::SUCCESS,
+                false => ExitCode::FAILURE,
+            };
+        }
+
+        for response in server.handle(&message) {
+            if let Err(e) = write_message(&mut output, &response) {
+                eprintln!("error writing message: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+}
+
+/// The state of the server, which holds the text and analysis of the open documents keyed by URI.
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, (String, Document)>,
+    shut_down: bool,
+}
+
+impl Server {
+    /// Returns the messages to send to the client in response to `message`.
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1, // the full text is sent on every change
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": {},
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "linger-lsp" },
+            }),
+            "shutdown" => {
+                self.shut_down = true;
+                Value::Null
+            }
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                return vec![self.open(uri, text)];
+            }
+            "textDocument/didChange" => {
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                    .unwrap_or_default();
+                return vec![self.open(uri, text)];
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return vec![publish_diagnostics(uri, vec![])];
+            }
+            "textDocument/definition" => match self.document(uri) {
+                Some((text, document)) => match document.definition(position(params, text)) {
+                    Some(definition) => json!({
+                        "uri": uri,
+                        "range": range(text, definition.span, definition.name.chars().count()),
+                    }),
+                    None => Value::Null,
+                },
+                None => Value::Null,
+            },
+            "textDocument/hover" => match self.document(uri) {
+                Some((text, document)) => match document.hover(position(params, text)) {
+                    Some(description) => {
+                        let mut value = format!("```linger\n{description}\n```");
+                        let definition = document.definition(position(params, text));
+                        if let Some(doc) = definition.and_then(|definition| definition.doc) {
+                            value += format!("\n\n{doc}").as_str();
+                        }
+                        json!({ "contents": { "kind": "markdown", "value": value } })
+                    }
+                    None => Value::Null,
+                },
+                None => Value::Null,
+            },
+            "textDocument/completion" => match self.document(uri) {
+                Some((text, document)) => document
+                    .completions(position(params, text))
+                    .into_iter()
+                    .map(|completion| {
+                        json!({
+                            "label": completion.label,
+                            "kind": match completion.kind {
+                                CompletionKind::Builtin | CompletionKind::Proc => 3, // Function
+                                CompletionKind::Variable => 6, // Variable
+                            },
+                            "detail": completion.detail,
+                        })
+                    })
+                    .collect(),
+                None => json!([]),
+            },
+            "textDocument/documentSymbol" => match self.document(uri) {
+                Some((text, document)) => document
+                    .symbols()
+                    .into_iter()
+                    .map(|symbol| {
+                        json!({
+                            "name": symbol.name,
+                            "detail": symbol.detail,
+                            "kind": 12, // Function
+                            "range": {
+                                "start": lsp_position(text, symbol.start),
+                                "end": lsp_position(text, symbol.end),
+                            },
+                            "selectionRange": range(
+                                text,
+                                symbol.name_span,
+                                symbol.name.chars().count(),
+                            ),
+                        })
+                    })
+                    .collect(),
+                None => json!([]),
+            },
+            _ => {
+                // notifications which the server does not support are ignored
+                return match message.get("id") {
+                    Some(id) => vec![json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": METHOD_NOT_FOUND,
+                            "message": format!("unsupported method \"{method}\""),
+                        },
+                    })],
+                    None => vec![],
+                };
+            }
+        };
+
+        match message.get("id") {
+            Some(id) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+            None => vec![],
+        }
+    }
+
+    fn document(&self, uri: &str) -> Option<&(String, Document)> {
+        self.documents.get(uri)
+    }
+
+    /// Analyzes the document at `uri`, returning a notification with its diagnostics.
+    fn open(&mut self, uri: &str, text: &str) -> Value {
+        let document = Document::new(text);
+        let diagnostics = document
+            .problems()
+            .iter()
+            .map(|problem| {
+                let mut diagnostic = json!({
+                    "range": range(text, problem.span, word_len(text, problem.span)),
+                    "severity": match problem.severity {
+                        Severity::Error => 1,
+                        Severity::Warning => 2,
+                    },
+                    "source": "linger",
+                    "message": problem.message,
+                });
+                if let Some(rule) = problem.rule {
+                    diagnostic["code"] = json!(rule.to_string());
+                }
+                diagnostic
+            })
+            .collect();
+        self.documents
+            .insert(uri.to_string(), (text.to_string(), document));
+        publish_diagnostics(uri, diagnostics)
+    }
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Returns the position in the parameters of a request on `text`. LSP positions start at 0 and
+/// count UTF-16 code units, while [Span] lines and columns start at 1 and columns count
+/// characters.
+fn position(params: &Value, text: &str) -> Span {
+    let line = params["position"]["line"].as_u64().unwrap_or_default() as usize;
+    let character = params["position"]["character"].as_u64().unwrap_or_default() as usize;
+    let mut units = 0;
+    let col = line_text(text, line + 1)
+        .chars()
+        .take_while(|c| {
+            units += c.len_utf16();
+            units <= character
+        })
+        .count();
+    Span {
+        line: line + 1,
+        col: col + 1,
+    }
+}
+
+fn lsp_position(text: &str, span: Span) -> Value {
+    let character: usize = line_text(text, span.line)
+        .chars()
+        .take(span.col.saturating_sub(1))
+        .map(char::len_utf16)
+        .sum();
+    json!({ "line": span.line.saturating_sub(1), "character": character })
+}
+
+/// Returns the range of `len` characters which starts at `span`.
+fn range(text: &str, span: Span, len: usize) -> Value {
+    let end = Span {
+        line: span.line,
+        col: span.col + len,
+    };
+    json!({ "start": lsp_position(text, span), "end": lsp_position(text, end) })
+}
+
+/// Returns the line of `text` with the number `line`, starting at 1, or an empty string if there
+/// is no such line.
+fn line_text(text: &str, line: usize) -> &str {
+    text.split('\n')
+        .nth(line.saturating_sub(1))
+        .unwrap_or_default()
+}
+
+/// Returns the length in characters of the word which starts at `span` in `text`, or 1 if there
+/// is no word there, so that every diagnostic covers at least one character.
+fn word_len(text: &str, span: Span) -> usize {
+    let word_len = line_text(text, span.line)
+        .chars()
+        .skip(span.col.saturating_sub(1))
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .count();
+    word_len.max(1)
+}
+
+/// Reads a message with a `Content-Length` header, returning `None` at the end of the input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    output.flush()
+}
@@ -1,31 +1,69 @@
+use std::fmt;
+
+use serde::Serialize;
+
 use crate::tokenizer::AssignOp;
 use crate::{
-    parser::{Builtin, SugaredExpr, SugaredStatement},
+    formatter::{format_enum, format_proc, format_statement, format_struct},
+    parser::{
+        Builtin, Param, Pattern, Span, SugaredEnum, SugaredExpr, SugaredMatchArm, SugaredParam,
+        SugaredProcedure, SugaredStatement, SugaredStruct, SugaredVariant,
+    },
     tokenizer::Operator,
 };
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct Procedure {
     pub name: String,
-    pub params: Vec<String>,
+    pub params: Vec<Param<Expr>>,
     pub body: Statement,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Struct {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Variant {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Statement,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum Statement {
     Expr(Expr),
     Let(String, Expr),
     Const(String, Expr),
     Assign(String, Expr),
+    FieldAssign(String, Vec<String>, Expr),
     If(Expr, Box<Statement>, Option<Box<Statement>>),
     While(Expr, Box<Statement>),
     Block(Vec<Statement>),
     Return(Option<Expr>),
     Break,
     Continue,
+    /// A statement along with the position in the source at which it begins, which is kept from
+    /// the [Spanned](SugaredStatement::Spanned) statement it was desugared from so that a debugger
+    /// can stop at it.
+    Spanned(Span, Box<Statement>),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum Expr {
     Nil,
     Num(f64),
@@ -36,29 +74,63 @@ pub enum Expr {
     Unary(Operator, Box<Expr>),
     PrimitiveCall(Builtin, Vec<Expr>),
     Call(Box<Expr>, Vec<Expr>),
-    Lambda(Vec<String>, Box<Statement>),
+    Lambda(Span, Vec<Param<Expr>>, Box<Statement>),
     Index(Box<Expr>, Box<Expr>),
+    StructLiteral(String, Vec<(String, Expr)>),
+    Field(Box<Expr>, String),
+    /// A `match` expression. When it is used as a statement, a `return`, `break` or `continue` in
+    /// the body of an arm applies to the enclosing procedure or loop.
+    Match(Box<Expr>, Vec<MatchArm>),
+    /// Creates an instance of the variant with the given name from the values of its fields. This
+    /// expression cannot be written in the source, and is only used as the body of the constructor
+    /// of each variant.
+    Variant(String, Vec<Expr>),
+    /// A named argument of a call, which is only evaluated as part of the call.
+    NamedArg(String, Box<Expr>),
 }
 
 fn desugar_statements(sugared_statements: Vec<SugaredStatement>) -> Vec<Statement> {
     sugared_statements
-        .iter()
-        .map(|s| desugar_statement(s.clone()))
+        .into_iter()
+        .filter(|s| !matches!(s, SugaredStatement::Trivia(_)))
+        .map(desugar_statement)
+        .collect()
+}
+
+fn desugar_args(sugared_args: Vec<SugaredExpr>) -> Vec<Expr> {
+    sugared_args
+        .into_iter()
+        .filter(|arg| !matches!(arg, SugaredExpr::Trivia(_)))
+        .map(desugar_expression)
+        .collect()
+}
+
+pub fn desugar_params(sugared_params: Vec<SugaredParam>) -> Vec<Param<Expr>> {
+    sugared_params
+        .into_iter()
+        .map(|param| Param {
+            name: param.name,
+            default: param.default.map(desugar_expression),
+            rest: param.rest,
+        })
         .collect()
 }
 
 pub fn desugar_statement(sugared_statement: SugaredStatement) -> Statement {
     match sugared_statement {
         SugaredStatement::Expr(sugared_expr) => Statement::Expr(desugar_expression(sugared_expr)),
-        SugaredStatement::Let(name, sugared_expr) => {
+        SugaredStatement::Let(name, _, sugared_expr) => {
             Statement::Let(name, desugar_expression(sugared_expr))
         }
-        SugaredStatement::Const(name, sugared_expr) => {
+        SugaredStatement::Const(name, _, sugared_expr) => {
             Statement::Const(name, desugar_expression(sugared_expr))
         }
         SugaredStatement::Assign(name, sugared_expr) => {
             Statement::Assign(name, desugar_expression(sugared_expr))
         }
+        SugaredStatement::FieldAssign(name, fields, sugared_expr) => {
+            Statement::FieldAssign(name, fields, desugar_expression(sugared_expr))
+        }
         SugaredStatement::If(if_cond, then_block, else_ifs, else_option) => {
             let desugared_else_option = match else_option {
                 Some(else_block) => Some(desugar_statement(*else_block)),
@@ -126,6 +198,12 @@ pub fn desugar_statement(sugared_statement: SugaredStatement) -> Statement {
         SugaredStatement::Block(sugared_statements) => {
             Statement::Block(desugar_statements(sugared_statements))
         }
+        // trivia is removed from blocks by `desugar_statements`, so this is never executed
+        // programs with parse errors are never desugared
+        SugaredStatement::Trivia(_) | SugaredStatement::Error(_) => Statement::Block(vec![]),
+        SugaredStatement::Spanned(span, sugared_statement) => {
+            Statement::Spanned(span, Box::new(desugar_statement(*sugared_statement)))
+        }
         SugaredStatement::OperatorAssignment(assign_op, id, expr) => match assign_op {
             AssignOp::Plus => Statement::Assign(
                 id.to_string(),
@@ -147,7 +225,7 @@ pub fn desugar_statement(sugared_statement: SugaredStatement) -> Statement {
     }
 }
 
-fn desugar_expression(sugared_expr: SugaredExpr) -> Expr {
+pub fn desugar_expression(sugared_expr: SugaredExpr) -> Expr {
     match sugared_expr {
         SugaredExpr::Nil => Expr::Nil,
         SugaredExpr::Num(n) => Expr::Num(n),
@@ -160,26 +238,248 @@ fn desugar_expression(sugared_expr: SugaredExpr) -> Expr {
             Box::new(desugar_expression(*right_sugared_expr)),
         ),
         SugaredExpr::Unary(op, expr) => Expr::Unary(op, Box::new(desugar_expression(*expr))),
-        SugaredExpr::PrimitiveCall(name, sugared_args) => Expr::PrimitiveCall(
+        SugaredExpr::PrimitiveCall(name, sugared_args) => {
+            Expr::PrimitiveCall(name, desugar_args(sugared_args))
+        }
+        SugaredExpr::Call(sugared_proc_expr, sugared_args) => Expr::Call(
+            Box::new(desugar_expression(*sugared_proc_expr)),
+            desugar_args(sugared_args),
+        ),
+        SugaredExpr::Lambda(span, sugared_params, sugared_body) => Expr::Lambda(
+            span,
+            desugar_params(sugared_params),
+            Box::new(desugar_statement(*sugared_body)),
+        ),
+        SugaredExpr::Index(sugared_indexable_expr, sugared_index_expr) => Expr::Index(
+            Box::new(desugar_expression(*sugared_indexable_expr)),
+            Box::new(desugar_expression(*sugared_index_expr)),
+        ),
+        SugaredExpr::StructLiteral(name, sugared_fields) => Expr::StructLiteral(
             name,
-            sugared_args
-                .iter()
-                .map(|sugared_arg_expr| desugar_expression(sugared_arg_expr.clone()))
+            sugared_fields
+                .into_iter()
+                .map(|(field, sugared_expr)| (field, desugar_expression(sugared_expr)))
                 .collect(),
         ),
-        SugaredExpr::Call(sugared_proc_expr, sugared_args) => Expr::Call(
-            Box::new(desugar_expression(*sugared_proc_expr)),
-            sugared_args
+        SugaredExpr::Field(sugared_struct_expr, field) => {
+            Expr::Field(Box::new(desugar_expression(*sugared_struct_expr)), field)
+        }
+        SugaredExpr::MethodCall(sugared_receiver, name, sugared_args) => {
+            let mut args = vec![desugar_expression(*sugared_receiver)];
+            args.append(&mut desugar_args(sugared_args));
+            match Builtin::from_name(&name) {
+                Some(builtin) => Expr::PrimitiveCall(builtin, args),
+                None => Expr::Call(Box::new(Expr::Var(name)), args),
+            }
+        }
+        SugaredExpr::Match(sugared_scrutinee, sugared_arms) => Expr::Match(
+            Box::new(desugar_expression(*sugared_scrutinee)),
+            sugared_arms.into_iter().map(desugar_match_arm).collect(),
+        ),
+        SugaredExpr::NamedArg(name, sugared_expr) => {
+            Expr::NamedArg(name, Box::new(desugar_expression(*sugared_expr)))
+        }
+        // trivia is removed from argument lists by `desugar_args`, so this is never evaluated
+        SugaredExpr::Trivia(_) => Expr::Nil,
+    }
+}
+
+fn desugar_match_arm(sugared_arm: SugaredMatchArm) -> MatchArm {
+    MatchArm {
+        pattern: sugared_arm.pattern,
+        guard: sugared_arm.guard.map(desugar_expression),
+        body: desugar_statement(sugared_arm.body),
+    }
+}
+
+/// Converts `statement` back into a syntax tree which it could have been desugared from, so that
+/// it can be printed as Linger source by the [formatter](crate::formatter). Nothing is sugared
+/// again, so a lowered `for` loop is printed as the block and `while` loop it became. The one
+/// exception is an `if` statement which is the `else` branch of another, which can only be written
+/// as an `else if`, since there is no block around it.
+fn resugar_statement(statement: &Statement) -> SugaredStatement {
+    match statement {
+        Statement::Expr(expr) => SugaredStatement::Expr(resugar_expression(expr)),
+        Statement::Let(name, expr) => {
+            SugaredStatement::Let(name.to_string(), None, resugar_expression(expr))
+        }
+        Statement::Const(name, expr) => {
+            SugaredStatement::Const(name.to_string(), None, resugar_expression(expr))
+        }
+        Statement::Assign(name, expr) => {
+            SugaredStatement::Assign(name.to_string(), resugar_expression(expr))
+        }
+        Statement::FieldAssign(name, fields, expr) => SugaredStatement::FieldAssign(
+            name.to_string(),
+            fields.clone(),
+            resugar_expression(expr),
+        ),
+        Statement::If(cond, then_statement, else_statement_option) => {
+            let mut else_ifs = vec![];
+            let mut else_statement_option = else_statement_option.as_deref();
+            while let Some(Statement::If(cond, then_statement, else_statement)) =
+                else_statement_option
+            {
+                else_ifs.push((resugar_expression(cond), resugar_statement(then_statement)));
+                else_statement_option = else_statement.as_deref();
+            }
+            SugaredStatement::If(
+                resugar_expression(cond),
+                Box::new(resugar_statement(then_statement)),
+                else_ifs,
+                else_statement_option
+                    .map(|else_statement| Box::new(resugar_statement(else_statement))),
+            )
+        }
+        Statement::While(cond, body) => {
+            SugaredStatement::While(resugar_expression(cond), Box::new(resugar_statement(body)))
+        }
+        Statement::Block(statements) => {
+            SugaredStatement::Block(statements.iter().map(resugar_statement).collect())
+        }
+        Statement::Return(expr_option) => {
+            SugaredStatement::Return(expr_option.as_ref().map(resugar_expression))
+        }
+        Statement::Break => SugaredStatement::Break,
+        Statement::Continue => SugaredStatement::Continue,
+        Statement::Spanned(span, statement) => {
+            SugaredStatement::Spanned(*span, Box::new(resugar_statement(statement)))
+        }
+    }
+}
+
+fn resugar_expression(expr: &Expr) -> SugaredExpr {
+    let resugar_args = |args: &[Expr]| args.iter().map(resugar_expression).collect();
+    match expr {
+        Expr::Nil => SugaredExpr::Nil,
+        Expr::Num(n) => SugaredExpr::Num(*n),
+        Expr::Bool(b) => SugaredExpr::Bool(*b),
+        Expr::Str(s) => SugaredExpr::Str(s.to_string()),
+        Expr::Var(id) => SugaredExpr::Var(id.to_string()),
+        Expr::Binary(op, left, right) => SugaredExpr::Binary(
+            *op,
+            Box::new(resugar_expression(left)),
+            Box::new(resugar_expression(right)),
+        ),
+        Expr::Unary(op, expr) => SugaredExpr::Unary(*op, Box::new(resugar_expression(expr))),
+        Expr::PrimitiveCall(builtin, args) => {
+            SugaredExpr::PrimitiveCall(*builtin, resugar_args(args))
+        }
+        Expr::Call(proc_expr, args) => {
+            SugaredExpr::Call(Box::new(resugar_expression(proc_expr)), resugar_args(args))
+        }
+        Expr::Lambda(span, params, body) => SugaredExpr::Lambda(
+            *span,
+            resugar_params(params),
+            Box::new(resugar_statement(body)),
+        ),
+        Expr::Index(indexable, index) => SugaredExpr::Index(
+            Box::new(resugar_expression(indexable)),
+            Box::new(resugar_expression(index)),
+        ),
+        Expr::StructLiteral(name, fields) => SugaredExpr::StructLiteral(
+            name.to_string(),
+            fields
                 .iter()
-                .map(|sugared_arg_expr| desugar_expression(sugared_arg_expr.clone()))
+                .map(|(field, expr)| (field.to_string(), resugar_expression(expr)))
                 .collect(),
         ),
-        SugaredExpr::Lambda(params, sugared_body) => {
-            Expr::Lambda(params, Box::new(desugar_statement(*sugared_body)))
+        Expr::Field(strukt, field) => {
+            SugaredExpr::Field(Box::new(resugar_expression(strukt)), field.to_string())
         }
-        SugaredExpr::Index(sugared_indexable_expr, sugared_index_expr) => Expr::Index(
-            Box::new(desugar_expression(*sugared_indexable_expr)),
-            Box::new(desugar_expression(*sugared_index_expr)),
+        Expr::Match(scrutinee, arms) => SugaredExpr::Match(
+            Box::new(resugar_expression(scrutinee)),
+            arms.iter()
+                .map(|arm| SugaredMatchArm {
+                    pattern: arm.pattern.clone(),
+                    guard: arm.guard.as_ref().map(resugar_expression),
+                    body: resugar_statement(&arm.body),
+                })
+                .collect(),
         ),
+        // a variant is created by calling its constructor
+        Expr::Variant(name, args) => SugaredExpr::Call(
+            Box::new(SugaredExpr::Var(name.to_string())),
+            resugar_args(args),
+        ),
+        Expr::NamedArg(name, expr) => {
+            SugaredExpr::NamedArg(name.to_string(), Box::new(resugar_expression(expr)))
+        }
+    }
+}
+
+fn resugar_params(params: &[Param<Expr>]) -> Vec<SugaredParam> {
+    params
+        .iter()
+        .map(|param| Param {
+            name: param.name.to_string(),
+            default: param.default.as_ref().map(resugar_expression),
+            rest: param.rest,
+        })
+        .collect()
+}
+
+/// The position given to the declarations which are sugared again to be printed, which is never
+/// shown.
+const NO_SPAN: Span = Span { line: 0, col: 0 };
+
+/// Prints the statement as Linger source in the canonical style, without a trailing newline.
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = format_statement(&resugar_statement(self), 0);
+        write!(f, "{}", source.trim_end_matches('\n'))
+    }
+}
+
+/// Prints the expression as Linger source with as few parentheses as possible.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", resugar_expression(self))
+    }
+}
+
+impl fmt::Display for Procedure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proc = SugaredProcedure {
+            span: NO_SPAN,
+            doc: None,
+            name: self.name.to_string(),
+            params: resugar_params(&self.params),
+            param_types: vec![None; self.params.len()],
+            return_type: None,
+            body: resugar_statement(&self.body),
+        };
+        write!(f, "{}", format_proc(&proc).trim_end_matches('\n'))
+    }
+}
+
+impl fmt::Display for Struct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let strukt = SugaredStruct {
+            span: NO_SPAN,
+            name: self.name.to_string(),
+            fields: self.fields.clone(),
+            field_types: vec![None; self.fields.len()],
+        };
+        write!(f, "{}", format_struct(&strukt).trim_end_matches('\n'))
+    }
+}
+
+impl fmt::Display for Enum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let enumeration = SugaredEnum {
+            span: NO_SPAN,
+            name: self.name.to_string(),
+            variants: self
+                .variants
+                .iter()
+                .map(|variant| SugaredVariant {
+                    name: variant.name.to_string(),
+                    fields: variant.fields.clone(),
+                    field_types: vec![None; variant.fields.len()],
+                })
+                .collect(),
+        };
+        write!(f, "{}", format_enum(&enumeration).trim_end_matches('\n'))
     }
 }
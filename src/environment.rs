@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
 use crate::{
-    desugar::{Procedure, Statement},
+    desugar::{Expr, Procedure, Statement},
     error::RuntimeError::{self, *},
     interpreter::Value,
+    parser::Param,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -20,7 +21,7 @@ pub enum Mutability {
 
 #[derive(Debug, Clone)]
 struct TopLevelProcedure {
-    params: Vec<String>,
+    params: Vec<Param<Expr>>,
     body: Statement,
 }
 
@@ -50,8 +51,9 @@ impl Environment {
             Some((value, ..)) => Ok(value.clone()),
             None => match self.top_level_procedures.get(&key) {
                 Some(proc) => Ok(Value::Proc(
+                    key,
                     proc.params.clone(),
-                    proc.body.clone(),
+                    Box::new(proc.body.clone()),
                     self.clone(),
                 )),
                 None => Err(UnknownVariable(key)),
@@ -1,8 +1,12 @@
-use std::fmt::{self, Display};
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
 
 use crate::{
-    interpreter::Value,
+    interpreter::{Capability, Value},
     tokenizer::{Operator, Token, TokenValue},
+    typecheck::Type,
 };
 
 /// A Tokenizer Error
@@ -16,10 +20,16 @@ pub enum TokenizerError {
     UnterminatedStringLiteral,
     /// This error occurs when the tokenizer reaches an invalid escape sequence.
     InvalidEscapeSequence(char),
+    /// This error occurs when the tokenizer tokenizes a block comment but never reaches its
+    /// closing `*/`.
+    UnterminatedBlockComment,
+    /// This error occurs when a `\x` escape is not two hexadecimal digits for an ASCII character,
+    /// or a `\u{...}` escape is not up to six hexadecimal digits for a Unicode scalar value.
+    InvalidCharacterEscape(String),
 }
 
 /// A Parse Error
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParseError {
     /// This error occurs when there is no `main` procedure.
     NoMain,
@@ -47,6 +57,74 @@ pub enum ParseError {
     /// This error occurs when the parser expects to parse an assignment statement or an
     /// initialization statement but was unsuccessful.
     ExpectedAssignmentOrInitialization,
+    /// This error occurs when a type annotation names a type which does not exist.
+    UnknownType(String),
+    /// This error occurs when there are multiple struct declarations with the same name.
+    MultipleSameNamedStructs(String),
+    /// This error occurs when a keyword is used as the name of a struct.
+    KeywordAsStruct(String),
+    /// This error occurs when a keyword is used as the name of a struct field.
+    KeywordAsField(String),
+    /// This error occurs when there are multiple enum declarations with the same name.
+    MultipleSameNamedEnums(String),
+    /// This error occurs when variants of the same or different enums have the same name.
+    MultipleSameNamedVariants(String),
+    /// This error occurs when a keyword is used as the name of an enum.
+    KeywordAsEnum(String),
+    /// This error occurs when a keyword is used as the name of an enum variant.
+    KeywordAsVariant(String),
+    /// This error occurs when a rest parameter is followed by another parameter.
+    RestParamNotLast(String),
+    /// This error occurs when a parameter without a default value follows one with a default
+    /// value.
+    RequiredParamAfterDefault(String),
+    /// This error occurs when a positional argument follows a named argument in a call.
+    PositionalArgAfterNamed,
+    /// This error occurs when a builtin procedure is called with a named argument.
+    NamedArgToBuiltin(String),
+    /// This error occurs when expressions, statements, patterns or types are nested more deeply
+    /// than [MAX_NESTING](crate::parser::MAX_NESTING), along with the token at which the limit
+    /// was reached.
+    NestedTooDeeply(Token),
+    /// This error occurs when the procedure which is run first, such as `main`, has more than one
+    /// parameter, since it is only passed the command-line arguments.
+    TooManyMainParams(String),
+}
+
+/// A Type Error
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeError {
+    /// This error occurs when a value is used where a value of another type is expected. The
+    /// expected type comes first.
+    Mismatch(Type, Type),
+    /// This error occurs when a binary operator is applied to values of types it does not support.
+    BadOperands(Operator, Type, Type),
+    /// This error occurs when a unary operator is applied to a value of a type it does not support.
+    BadOperand(Operator, Type),
+    /// This error occurs when a value which is not a procedure is called.
+    NotCallable(Type),
+    /// This error occurs when a value which is not a list or a string is indexed.
+    NotIndexable(Type),
+    /// This error occurs when a field is accessed, assigned or initialized on a value which does
+    /// not have it.
+    NoField(Type, String),
+    /// This error occurs when a struct constructor does not initialize one of the struct's fields.
+    MissingField(Type, String),
+    /// This error occurs when the patterns of a `match` expression do not cover every value of
+    /// the matched type. The type comes first, followed by an example of a value which is not
+    /// covered.
+    NonExhaustiveMatch(Type, String),
+}
+
+/// A Format Error
+#[derive(Debug, Clone)]
+pub enum FormatError {
+    /// This error occurs when the source being formatted cannot be tokenized.
+    Tokenizer(TokenizerError),
+    /// This error occurs when the source being formatted cannot be parsed. Comments are only
+    /// allowed between statements, top-level procedures and arguments, so a comment anywhere else
+    /// results in an [UnexpectedToken](ParseError::UnexpectedToken) error.
+    Parse(ParseError),
 }
 
 /// A Runtime Error
@@ -55,18 +133,31 @@ pub enum RuntimeError {
     /// This error occurs when the interpreter encounters an variable unbound in the environment.
     UnknownVariable(String),
     /// This error occurs when a single argument to a procedure is incorrect.
-    BadArg(Value),
+    BadArg(Box<Value>),
     /// This error occurs when multiple arguments to a procedure are incorrect.
     BadArgs(Vec<Value>),
     /// This error occurs when the number of arguments passed to a procedure is different from the
     /// number of parameters defined for that procedure.
     ArgMismatch(String, usize, usize),
+    /// This error occurs when a call does not pass an argument for a parameter without a default
+    /// value. The name of the procedure comes first.
+    MissingArg(String, String),
+    /// This error occurs when a call passes a named argument which does not match any parameter.
+    /// The name of the procedure comes first.
+    UnknownParam(String, String),
+    /// This error occurs when a call passes more than one argument for the same parameter. The
+    /// name of the procedure comes first.
+    DuplicateArg(String, String),
     /// This error occurs when a value is expected to be a boolean but is not.
-    ExpectedBool(Value),
+    ExpectedBool(Box<Value>),
     /// This error occurs when a value is expected to be a integer but is not.
-    ExpectedInteger(Value),
+    ExpectedInteger(Box<Value>),
     /// This error occurs when a value is expected to be a list but is not
-    ExpectedList(Value),
+    ExpectedList(Box<Value>),
+    /// This error occurs when an exit code is an integer which is not between 0 and 255.
+    ExitCodeOutOfRange(Box<Value>),
+    /// This error occurs when an exit code is reserved for the errors of the interpreter.
+    ReservedExitCode(i32),
     /// This error occurs when a binary operator is used as a unary operator.
     BinaryAsUnary(Operator),
     /// This error occurs when a unary operator is used as a binary operator.
@@ -82,10 +173,55 @@ pub enum RuntimeError {
     /// This error occurs when attempting to reassign a top-level procedure
     ReassignTopLevelProc(String),
     /// This error occurs when attempting to index a non-indexable value
-    NotIndexable(Value),
+    NotIndexable(Box<Value>),
+    /// This error occurs when constructing a struct which has not been declared.
+    UnknownStruct(String),
+    /// This error occurs when a struct constructor does not initialize one of the struct's fields.
+    /// The name of the struct comes first.
+    MissingField(String, String),
+    /// This error occurs when accessing or initializing a field which the struct does not have.
+    /// The name of the struct comes first.
+    UnknownField(String, String),
+    /// This error occurs when accessing or assigning a field of a value which is not a struct.
+    NotAStruct(Box<Value>),
+    /// This error occurs when no arm of a `match` expression matches the value.
+    NoMatch(Box<Value>),
+    /// This error occurs when a pattern names a variant which has not been declared.
+    UnknownVariant(String),
+    /// This error occurs when a variant pattern has a different number of fields than the
+    /// variant. The expected number of fields comes first.
+    VariantArity(String, usize, usize),
     /// This error occurs when trying to index a value and the index is out
     /// of bounds
     IndexOutOfBounds(i64),
+    /// This error occurs when a program evaluates more steps than the interpreter's step limit
+    /// allows.
+    StepLimitExceeded(u64),
+    /// This error occurs when nested procedure calls exceed the interpreter's call depth limit.
+    CallDepthExceeded(usize),
+    /// This error occurs when a program allocates more list elements and string characters than
+    /// the interpreter's allocation limit allows.
+    AllocationLimitExceeded(usize),
+    /// This error occurs when a program runs for longer than the interpreter's deadline.
+    DeadlineExceeded(Duration),
+    /// This error occurs when the host cancels a running program.
+    Cancelled,
+    /// This error occurs when a program calls an I/O builtin without the capability it requires.
+    CapabilityDenied(String, Capability),
+    /// This error occurs when an I/O operation fails.
+    Io(String),
+    /// This error occurs when the condition of an `assert` is false, along with the message passed
+    /// to it, if any.
+    AssertionFailed(Option<String>),
+    /// This error occurs when the values passed to `assert_eq` are not equal. The first value
+    /// comes first.
+    AssertEqFailed(Box<Value>, Box<Value>),
+    /// This error occurs when the procedure passed to `assert_error` returns a value instead of
+    /// failing.
+    ExpectedError(Box<Value>),
+    /// This is not an error, but is raised by the `exit` builtin to unwind the program with an
+    /// exit code, which is between 0 and 255.
+    Exit(i32),
 }
 
 impl Display for ParseError {
@@ -121,6 +257,86 @@ impl Display for ParseError {
             ParseError::ExpectedAssignmentOrInitialization => {
                 write!(f, "expected an assignment or initialization statement")
             }
+            ParseError::UnknownType(name) => write!(f, "unknown type \"{name}\""),
+            ParseError::MultipleSameNamedStructs(name) => {
+                write!(f, "multiple structs with name \"{name}\"")
+            }
+            ParseError::KeywordAsStruct(keyword) => {
+                write!(f, "keyword \"{keyword}\" used as struct name")
+            }
+            ParseError::KeywordAsField(keyword) => {
+                write!(f, "keyword \"{keyword}\" used as field name")
+            }
+            ParseError::MultipleSameNamedEnums(name) => {
+                write!(f, "multiple enums with name \"{name}\"")
+            }
+            ParseError::MultipleSameNamedVariants(name) => {
+                write!(f, "multiple variants with name \"{name}\"")
+            }
+            ParseError::KeywordAsEnum(keyword) => {
+                write!(f, "keyword \"{keyword}\" used as enum name")
+            }
+            ParseError::KeywordAsVariant(keyword) => {
+                write!(f, "keyword \"{keyword}\" used as variant name")
+            }
+            ParseError::RestParamNotLast(name) => {
+                write!(f, "rest parameter \"{name}\" must be the last parameter")
+            }
+            ParseError::RequiredParamAfterDefault(name) => write!(
+                f,
+                "parameter \"{name}\" needs a default value since it follows a parameter with one"
+            ),
+            ParseError::PositionalArgAfterNamed => {
+                write!(f, "positional argument found after a named argument")
+            }
+            ParseError::NamedArgToBuiltin(name) => {
+                write!(f, "builtin \"{name}\" does not take named arguments")
+            }
+            ParseError::NestedTooDeeply(token) => {
+                write!(f, "nested too deeply @ ({}, {})", token.1, token.2)
+            }
+            ParseError::TooManyMainParams(name) => {
+                write!(f, "procedure \"{name}\" is run first, so it takes at most one parameter")
+            }
+        }
+    }
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeError::Mismatch(expected, actual) => {
+                write!(f, "expected type \"{expected}\" but found \"{actual}\"")
+            }
+            TypeError::BadOperands(op, lhs, rhs) => write!(
+                f,
+                "operator \"{op}\" cannot be applied to \"{lhs}\" and \"{rhs}\""
+            ),
+            TypeError::BadOperand(op, ty) => {
+                write!(f, "operator \"{op}\" cannot be applied to \"{ty}\"")
+            }
+            TypeError::NotCallable(ty) => write!(f, "a value of type \"{ty}\" cannot be called"),
+            TypeError::NotIndexable(ty) => {
+                write!(f, "a value of type \"{ty}\" cannot be indexed")
+            }
+            TypeError::NoField(ty, field) => {
+                write!(f, "a value of type \"{ty}\" has no field \"{field}\"")
+            }
+            TypeError::MissingField(ty, field) => {
+                write!(f, "field \"{field}\" of \"{ty}\" is not initialized")
+            }
+            TypeError::NonExhaustiveMatch(ty, missing) => {
+                write!(f, "match on \"{ty}\" does not cover \"{missing}\"")
+            }
+        }
+    }
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Tokenizer(e) => write!(f, "{e}"),
+            FormatError::Parse(e) => write!(f, "{e}"),
         }
     }
 }
@@ -135,6 +351,10 @@ impl Display for TokenizerError {
             TokenizerError::InvalidEscapeSequence(char) => {
                 write!(f, "invalid escape sequence \"\\{char}\"")
             }
+            TokenizerError::UnterminatedBlockComment => write!(f, "unterminated block comment"),
+            TokenizerError::InvalidCharacterEscape(escape) => {
+                write!(f, "invalid character escape \"{escape}\"")
+            }
         }
     }
 }
@@ -149,6 +369,17 @@ impl Display for RuntimeError {
                 "procedure \"{}\" expected {} args, instead got {}",
                 proc_name, expected, actual
             ),
+            RuntimeError::MissingArg(proc_name, param) => write!(
+                f,
+                "procedure \"{proc_name}\" is missing an argument for parameter \"{param}\""
+            ),
+            RuntimeError::UnknownParam(proc_name, param) => {
+                write!(f, "procedure \"{proc_name}\" has no parameter named \"{param}\"")
+            }
+            RuntimeError::DuplicateArg(proc_name, param) => write!(
+                f,
+                "procedure \"{proc_name}\" got more than one argument for parameter \"{param}\""
+            ),
             RuntimeError::ExpectedBool(v) => {
                 write!(f, "expected boolean value, instead got {}", v)
             }
@@ -175,15 +406,70 @@ impl Display for RuntimeError {
                 write!(f, "cannot assign to top-level procedure \"{proc_name}\"")
             }
             RuntimeError::NotIndexable(value) => write!(f, "\"{value}\" is not indexable"),
+            RuntimeError::UnknownStruct(name) => write!(f, "unknown struct \"{name}\""),
+            RuntimeError::MissingField(name, field) => {
+                write!(f, "missing field \"{field}\" in constructor of \"{name}\"")
+            }
+            RuntimeError::UnknownField(name, field) => {
+                write!(f, "struct \"{name}\" has no field \"{field}\"")
+            }
+            RuntimeError::NotAStruct(value) => write!(f, "\"{value}\" is not a struct"),
+            RuntimeError::NoMatch(value) => write!(f, "no pattern matches \"{value}\""),
+            RuntimeError::UnknownVariant(name) => write!(f, "unknown variant \"{name}\""),
+            RuntimeError::VariantArity(name, expected, actual) => write!(
+                f,
+                "variant \"{name}\" has {expected} fields but the pattern has {actual}"
+            ),
             RuntimeError::ExpectedInteger(value) => write!(
                 f,
                 "expected an integer but got \"{value}\", which is not an integer"
             ),
+            RuntimeError::ExitCodeOutOfRange(value) => {
+                write!(f, "exit code {value} is not between 0 and 255")
+            }
+            RuntimeError::ReservedExitCode(code) => write!(
+                f,
+                "exit code {code} is reserved for errors in tokenizing, parsing or running a program"
+            ),
             RuntimeError::IndexOutOfBounds(index) => write!(f, "index {index} is out of bounds"),
             RuntimeError::ExpectedList(value) => write!(
                 f,
                 "expected a list, instead got {value}, which is not a list"
             ),
+            RuntimeError::StepLimitExceeded(max_steps) => {
+                write!(f, "step limit of {max_steps} exceeded")
+            }
+            RuntimeError::CallDepthExceeded(max_call_depth) => {
+                write!(f, "call depth limit of {max_call_depth} exceeded")
+            }
+            RuntimeError::AllocationLimitExceeded(max_allocation) => {
+                write!(f, "allocation limit of {max_allocation} elements exceeded")
+            }
+            RuntimeError::DeadlineExceeded(deadline) => {
+                write!(f, "deadline of {}ms exceeded", deadline.as_millis())
+            }
+            RuntimeError::Cancelled => write!(f, "execution cancelled"),
+            RuntimeError::CapabilityDenied(builtin, capability) => write!(
+                f,
+                "\"{builtin}\" requires the \"{capability}\" capability, which was not granted"
+            ),
+            RuntimeError::Io(message) => write!(f, "I/O error: {message}"),
+            RuntimeError::AssertionFailed(Some(message)) => {
+                write!(f, "assertion failed: {message}")
+            }
+            RuntimeError::AssertionFailed(None) => write!(f, "assertion failed"),
+            RuntimeError::AssertEqFailed(left, right) => write!(
+                f,
+                "assertion failed: {} is not equal to {}",
+                left.to_quoted_string(),
+                right.to_quoted_string()
+            ),
+            RuntimeError::ExpectedError(value) => write!(
+                f,
+                "expected an error, but the procedure returned {}",
+                value.to_quoted_string()
+            ),
+            RuntimeError::Exit(code) => write!(f, "exited with code {code}"),
         }
     }
 }
@@ -0,0 +1,611 @@
+use std::fmt;
+
+use crate::{
+    error::FormatError,
+    parser::{
+        parse_source_file, Pattern, SugaredEnum, SugaredExpr, SugaredItem, SugaredMatchArm,
+        SugaredParam, SugaredProcedure, SugaredStatement, SugaredStruct,
+    },
+    tokenizer::{
+        tokenize_with_trivia, Keyword, Operator, Token,
+        TokenValue::{self, *},
+        Trivia,
+    },
+    typecheck::Type,
+};
+
+const INDENT: &str = "  ";
+
+/// Formats the Linger source `s` in the canonical Linger style. Comments are preserved, and runs
+/// of blank lines between statements are collapsed into a single blank line. A comment within a
+/// statement or declaration is moved onto its own line before it, or to the end of its line if
+/// the comment trailed a line, except that a trailing comment before the opening curly bracket of
+/// a block follows that bracket, and a comment before an `else` ends the block before it.
+pub fn format_source(s: &str) -> Result<String, FormatError> {
+    let tokens = tokenize_with_trivia(s).map_err(FormatError::Tokenizer)?;
+    let tokens = remove_misplaced_blank_lines(tokens);
+    let items = parse_source_file(tokens.as_slice()).map_err(FormatError::Parse)?;
+    Ok(format_items(&items))
+}
+
+/// Returns the formatted source of the top-level `items` of a source file.
+pub fn format_items(items: &[SugaredItem]) -> String {
+    let mut out = String::new();
+    let mut previous_item: Option<&SugaredItem> = None;
+    let mut pending_blank_line = false;
+    for item in items {
+        match item {
+            SugaredItem::Trivia(Trivia::BlankLine) => {
+                pending_blank_line = previous_item.is_some();
+            }
+            SugaredItem::Trivia(Trivia::TrailingComment(comment)) => {
+                append_trailing_comment(&mut out, comment);
+            }
+            item => {
+                if pending_blank_line
+                    || matches!(
+                        previous_item,
+                        Some(SugaredItem::Proc(_) | SugaredItem::Struct(_) | SugaredItem::Enum(_))
+                    )
+                {
+                    out.push('\n');
+                }
+                match item {
+                    SugaredItem::Proc(proc) => out += format_proc(proc).as_str(),
+                    SugaredItem::Struct(strukt) => out += format_struct(strukt).as_str(),
+                    SugaredItem::Enum(enumeration) => out += format_enum(enumeration).as_str(),
+                    SugaredItem::Trivia(trivia) => out += format!("{trivia}\n").as_str(),
+                }
+                previous_item = Some(item);
+                pending_blank_line = false;
+            }
+        }
+    }
+    out
+}
+
+/// Prints the statement as Linger source in the canonical style, without a trailing newline.
+impl fmt::Display for SugaredStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_statement(self, 0).trim_end_matches('\n'))
+    }
+}
+
+/// Prints the expression as Linger source with as few parentheses as possible.
+impl fmt::Display for SugaredExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_expr(self, 0))
+    }
+}
+
+/// Returns the formatted source of the procedure `proc`, including its doc comments.
+pub fn format_proc(proc: &SugaredProcedure) -> String {
+    let doc: String = proc
+        .doc
+        .iter()
+        .flat_map(|doc| doc.split('\n'))
+        .map(|line| format!("{}\n", Trivia::DocComment(line.to_string())))
+        .collect();
+    let params: Vec<String> = proc
+        .params
+        .iter()
+        .zip(&proc.param_types)
+        .map(|(param, ty)| format_param(param, ty, 0))
+        .collect();
+    let return_type = match &proc.return_type {
+        Some(ty) => format!(" -> {ty}"),
+        None => String::new(),
+    };
+    format!(
+        "{doc}proc {}({}){return_type} {}\n",
+        proc.name,
+        params.join(", "),
+        format_block_statement(&proc.body, 0)
+    )
+}
+
+/// Returns the formatted source of the struct declaration `strukt`.
+pub fn format_struct(strukt: &SugaredStruct) -> String {
+    let fields: Vec<String> = strukt
+        .fields
+        .iter()
+        .zip(&strukt.field_types)
+        .map(|(name, ty)| format!("{name}{}", format_annotation(ty)))
+        .collect();
+    match fields.is_empty() {
+        true => format!("struct {} {{}}\n", strukt.name),
+        false => format!("struct {} {{ {} }}\n", strukt.name, fields.join(", ")),
+    }
+}
+
+/// Returns the formatted source of the enum declaration `enumeration`.
+pub fn format_enum(enumeration: &SugaredEnum) -> String {
+    let variants: Vec<String> = enumeration
+        .variants
+        .iter()
+        .map(|variant| {
+            let fields: Vec<String> = variant
+                .fields
+                .iter()
+                .zip(&variant.field_types)
+                .map(|(name, ty)| format!("{name}{}", format_annotation(ty)))
+                .collect();
+            match fields.is_empty() {
+                true => variant.name.to_string(),
+                false => format!("{}({})", variant.name, fields.join(", ")),
+            }
+        })
+        .collect();
+    match variants.is_empty() {
+        true => format!("enum {} {{}}\n", enumeration.name),
+        false => format!("enum {} {{ {} }}\n", enumeration.name, variants.join(", ")),
+    }
+}
+
+/// Returns the formatted type annotation `ty`, including its leading colon.
+fn format_annotation(ty: &Option<Type>) -> String {
+    match ty {
+        Some(ty) => format!(": {ty}"),
+        None => String::new(),
+    }
+}
+
+/// Returns the formatted source of the parameter `param` along with its type annotation `ty`.
+fn format_param(param: &SugaredParam, ty: &Option<Type>, indent: usize) -> String {
+    let ellipsis = if param.rest { "..." } else { "" };
+    let default = match &param.default {
+        Some(default) => format!(" = {}", format_expr(default, indent)),
+        None => String::new(),
+    };
+    format!("{ellipsis}{}{}{default}", param.name, format_annotation(ty))
+}
+
+/// Returns the formatted source of `statement` indented by `indent` levels, including its
+/// trailing newline.
+pub fn format_statement(statement: &SugaredStatement, indent: usize) -> String {
+    let ind = INDENT.repeat(indent);
+    match statement {
+        SugaredStatement::Spanned(_, statement) => format_statement(statement, indent),
+        SugaredStatement::Block(statements) => {
+            format!("{ind}{}\n", format_block(statements, indent))
+        }
+        SugaredStatement::If(cond, then_block, else_ifs, else_block) => {
+            let mut out = format!(
+                "{ind}if ({}) {}",
+                format_expr(cond, indent),
+                format_block_statement(then_block, indent)
+            );
+            for (else_if_cond, else_if_block) in else_ifs {
+                out += format!(
+                    " else if ({}) {}",
+                    format_expr(else_if_cond, indent),
+                    format_block_statement(else_if_block, indent)
+                )
+                .as_str();
+            }
+            if let Some(else_block) = else_block {
+                out += format!(" else {}", format_block_statement(else_block, indent)).as_str();
+            }
+            out + "\n"
+        }
+        SugaredStatement::While(cond, body) => format!(
+            "{ind}while ({}) {}\n",
+            format_expr(cond, indent),
+            format_block_statement(body, indent)
+        ),
+        SugaredStatement::For(init, cond, update, body) => format!(
+            "{ind}for ({}; {}; {}) {}\n",
+            format_inline_statement(init, indent),
+            format_expr(cond, indent),
+            format_inline_statement(update, indent),
+            format_block(body, indent)
+        ),
+        // like an `if` statement, a `match` used as a statement is not followed by a semicolon
+        SugaredStatement::Expr(expr @ SugaredExpr::Match(..)) => {
+            format!("{ind}{}\n", format_expr(expr, indent))
+        }
+        SugaredStatement::Trivia(trivia) => format!("{ind}{trivia}\n"),
+        // the formatter rejects programs with errors, but a statement with one can still be
+        // printed while debugging
+        SugaredStatement::Error(e) => format!("{ind}/* {e} */\n"),
+        statement => format!("{ind}{};\n", format_inline_statement(statement, indent)),
+    }
+}
+
+/// Returns the formatted source of a simple statement without its indentation, semicolon or
+/// trailing newline, as it appears in the header of a `for` loop or the body of a lambda.
+fn format_inline_statement(statement: &SugaredStatement, indent: usize) -> String {
+    match statement {
+        // a statement which starts with `match` is a `match` statement, so an expression which
+        // only starts with one, such as `match (x) { ... }.y`, is parenthesized
+        SugaredStatement::Expr(expr) => match format_expr(expr, indent) {
+            formatted
+                if formatted.starts_with("match ") && !matches!(expr, SugaredExpr::Match(..)) =>
+            {
+                format!("({formatted})")
+            }
+            formatted => formatted,
+        },
+        SugaredStatement::Let(id, ty, expr) => {
+            format!(
+                "let {id}{} = {}",
+                format_annotation(ty),
+                format_expr(expr, indent)
+            )
+        }
+        SugaredStatement::Const(id, ty, expr) => {
+            format!(
+                "const {id}{} = {}",
+                format_annotation(ty),
+                format_expr(expr, indent)
+            )
+        }
+        SugaredStatement::Assign(id, expr) => format!("{id} = {}", format_expr(expr, indent)),
+        SugaredStatement::FieldAssign(id, fields, expr) => {
+            format!("{id}.{} = {}", fields.join("."), format_expr(expr, indent))
+        }
+        SugaredStatement::OperatorAssignment(op, id, expr) => {
+            format!("{id} {op} {}", format_expr(expr, indent))
+        }
+        SugaredStatement::Return(Some(expr)) => format!("return {}", format_expr(expr, indent)),
+        SugaredStatement::Return(None) => "return".to_string(),
+        SugaredStatement::Break => "break".to_string(),
+        SugaredStatement::Continue => "continue".to_string(),
+        SugaredStatement::Block(statements) => format_block(statements, indent),
+        statement => format_statement(statement, indent)
+            .trim_start()
+            .trim_end_matches('\n')
+            .to_string(),
+    }
+}
+
+/// Returns the formatted source of a statement which is expected to be a block, such as the body
+/// of an `if` statement, starting at its opening curly bracket.
+fn format_block_statement(statement: &SugaredStatement, indent: usize) -> String {
+    match statement {
+        SugaredStatement::Block(statements) => format_block(statements, indent),
+        statement => format_block(std::slice::from_ref(statement), indent),
+    }
+}
+
+/// Returns the formatted source of a block containing `statements`, starting at its opening curly
+/// bracket and ending at its closing curly bracket.
+fn format_block(statements: &[SugaredStatement], indent: usize) -> String {
+    let mut header_comment = None;
+    let mut inner = String::new();
+    let mut pending_blank_line = false;
+    for statement in statements {
+        match statement {
+            SugaredStatement::Trivia(Trivia::BlankLine) => pending_blank_line = !inner.is_empty(),
+            SugaredStatement::Trivia(Trivia::TrailingComment(comment)) if inner.is_empty() => {
+                header_comment = Some(comment)
+            }
+            SugaredStatement::Trivia(Trivia::TrailingComment(comment)) => {
+                append_trailing_comment(&mut inner, comment)
+            }
+            statement => {
+                if pending_blank_line {
+                    inner.push('\n');
+                }
+                inner += format_statement(statement, indent + 1).as_str();
+                pending_blank_line = false;
+            }
+        }
+    }
+
+    let header = match header_comment {
+        Some(comment) => format!("{{ //{comment}\n"),
+        None if inner.is_empty() => return "{}".to_string(),
+        None => "{\n".to_string(),
+    };
+    format!("{header}{inner}{}}}", INDENT.repeat(indent))
+}
+
+/// Returns the formatted source of `expr`, where `indent` is the indentation of the line on which
+/// the expression starts.
+pub fn format_expr(expr: &SugaredExpr, indent: usize) -> String {
+    match expr {
+        SugaredExpr::Nil => Keyword::Nil.to_string(),
+        SugaredExpr::Num(n) => n.to_string(),
+        SugaredExpr::Bool(true) => Keyword::True.to_string(),
+        SugaredExpr::Bool(false) => Keyword::False.to_string(),
+        SugaredExpr::Str(s) => format_string_literal(s),
+        SugaredExpr::Var(id) => id.to_string(),
+        SugaredExpr::Binary(op, left, right) => {
+            let precedence = binary_precedence(*op);
+            let left = format_operand(left, precedence, indent);
+            let right = format_operand(right, precedence + 1, indent);
+            format!("{left} {op} {right}")
+        }
+        SugaredExpr::Unary(op @ (Operator::PostIncrement | Operator::PostDecrement), operand) => {
+            format!(
+                "{}{op}",
+                format_operand(operand, POSTFIX_PRECEDENCE, indent)
+            )
+        }
+        SugaredExpr::Unary(op @ (Operator::PreIncrement | Operator::PreDecrement), operand) => {
+            format!(
+                "{op}{}",
+                format_operand(operand, POSTFIX_PRECEDENCE, indent)
+            )
+        }
+        SugaredExpr::Unary(op, operand) => match operand.as_ref() {
+            // `- -x` and `- --x` would be read back as decrements without the space, which is
+            // used rather than parentheses so that formatting does not nest the operand any deeper
+            SugaredExpr::Unary(Operator::Minus | Operator::PreDecrement, _) => {
+                format!("{op} {}", format_operand(operand, UNARY_PRECEDENCE, indent))
+            }
+            _ => format!("{op}{}", format_operand(operand, UNARY_PRECEDENCE, indent)),
+        },
+        SugaredExpr::PrimitiveCall(builtin, args) => {
+            format!("{builtin}{}", format_args(args, indent))
+        }
+        SugaredExpr::Call(proc_expr, args) => format!(
+            "{}{}",
+            format_operand(proc_expr, POSTFIX_PRECEDENCE, indent),
+            format_args(args, indent)
+        ),
+        SugaredExpr::Lambda(_, params, body) => {
+            let body = match body.as_ref() {
+                SugaredStatement::Block(statements) => format_block(statements, indent),
+                statement @ (SugaredStatement::Return(_)
+                | SugaredStatement::Break
+                | SugaredStatement::Continue) => {
+                    format!("{};", format_inline_statement(statement, indent))
+                }
+                statement => format_inline_statement(statement, indent),
+            };
+            let params: Vec<String> = params
+                .iter()
+                .map(|param| format_param(param, &None, indent))
+                .collect();
+            format!("({}) -> {body}", params.join(", "))
+        }
+        SugaredExpr::NamedArg(name, value) => format!("{name}: {}", format_expr(value, indent)),
+        SugaredExpr::MethodCall(receiver, name, args) => format!(
+            "{}.{name}{}",
+            format_operand(receiver, POSTFIX_PRECEDENCE, indent),
+            format_args(args, indent)
+        ),
+        SugaredExpr::Index(indexable, index) => format!(
+            "{}[{}]",
+            format_operand(indexable, POSTFIX_PRECEDENCE, indent),
+            format_expr(index, indent)
+        ),
+        SugaredExpr::StructLiteral(name, fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(field, value)| format!("{field}: {}", format_expr(value, indent)))
+                .collect();
+            match fields.is_empty() {
+                true => format!("{name} {{}}"),
+                false => format!("{name} {{ {} }}", fields.join(", ")),
+            }
+        }
+        SugaredExpr::Field(strukt, field) => format!(
+            "{}.{field}",
+            format_operand(strukt, POSTFIX_PRECEDENCE, indent)
+        ),
+        SugaredExpr::Match(scrutinee, arms) => {
+            let header = format!("match ({})", format_expr(scrutinee, indent));
+            if arms.is_empty() {
+                return format!("{header} {{}}");
+            }
+            let inner_ind = INDENT.repeat(indent + 1);
+            let mut out = format!("{header} {{\n");
+            for (index, arm) in arms.iter().enumerate() {
+                let (body, delimited) = format_match_arm_body(arm, indent + 1);
+                let separator = if index + 1 < arms.len() && !delimited {
+                    ","
+                } else {
+                    ""
+                };
+                let guard = match &arm.guard {
+                    Some(guard) => format!(" if ({})", format_expr(guard, indent + 1)),
+                    None => String::new(),
+                };
+                out += format!(
+                    "{inner_ind}{}{guard} -> {body}{separator}\n",
+                    format_pattern(&arm.pattern)
+                )
+                .as_str();
+            }
+            out + INDENT.repeat(indent).as_str() + "}"
+        }
+        SugaredExpr::Trivia(trivia) => trivia.to_string(),
+    }
+}
+
+/// Returns the formatted body of a `match` arm which starts on a line indented by `indent`
+/// levels, and whether it ends in `;` or `}` so that no comma is needed after it.
+fn format_match_arm_body(arm: &SugaredMatchArm, indent: usize) -> (String, bool) {
+    match &arm.body {
+        SugaredStatement::Block(statements) => (format_block(statements, indent), true),
+        statement @ (SugaredStatement::Return(_)
+        | SugaredStatement::Break
+        | SugaredStatement::Continue) => (
+            format!("{};", format_inline_statement(statement, indent)),
+            true,
+        ),
+        statement => {
+            // an expression which ends with `}`, such as a struct literal, still needs a comma,
+            // since the pattern of the next arm could otherwise continue it, as in `P {}[x]`
+            let delimited = matches!(
+                statement,
+                SugaredStatement::If(..) | SugaredStatement::While(..) | SugaredStatement::For(..)
+            );
+            (format_inline_statement(statement, indent), delimited)
+        }
+    }
+}
+
+/// Returns the formatted source of `pattern`.
+fn format_pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Binding(name) => name.to_string(),
+        Pattern::Nil => Keyword::Nil.to_string(),
+        Pattern::Num(n) => n.to_string(),
+        Pattern::Bool(true) => Keyword::True.to_string(),
+        Pattern::Bool(false) => Keyword::False.to_string(),
+        Pattern::Str(s) => format_string_literal(s),
+        Pattern::List(elements, rest) => {
+            let mut elements: Vec<String> = elements.iter().map(format_pattern).collect();
+            match rest.as_deref() {
+                Some(Pattern::Wildcard) => elements.push("..".to_string()),
+                Some(rest) => elements.push(format!("..{}", format_pattern(rest))),
+                None => (),
+            }
+            format!("[{}]", elements.join(", "))
+        }
+        Pattern::Variant(name, fields) => {
+            let fields: Vec<String> = fields.iter().map(format_pattern).collect();
+            format!("{name}({})", fields.join(", "))
+        }
+    }
+}
+
+const LAMBDA_PRECEDENCE: u8 = 0;
+const UNARY_PRECEDENCE: u8 = 7;
+const POSTFIX_PRECEDENCE: u8 = 8;
+const TERMINAL_PRECEDENCE: u8 = 9;
+
+/// Returns the precedence of a binary operator, where operators with a higher precedence bind
+/// more tightly.
+fn binary_precedence(op: Operator) -> u8 {
+    match op {
+        Operator::LogicOr => 1,
+        Operator::LogicAnd => 2,
+        Operator::Eq | Operator::Ne => 3,
+        Operator::LT | Operator::GT | Operator::LTE | Operator::GTE => 4,
+        Operator::Plus | Operator::Minus => 5,
+        Operator::Times | Operator::Mod | Operator::Div => 6,
+        _ => UNARY_PRECEDENCE,
+    }
+}
+
+fn expr_precedence(expr: &SugaredExpr) -> u8 {
+    match expr {
+        SugaredExpr::Binary(op, ..) => binary_precedence(*op),
+        SugaredExpr::Unary(..) => UNARY_PRECEDENCE,
+        SugaredExpr::Call(..)
+        | SugaredExpr::PrimitiveCall(..)
+        | SugaredExpr::Index(..)
+        | SugaredExpr::Field(..)
+        | SugaredExpr::MethodCall(..) => POSTFIX_PRECEDENCE,
+        SugaredExpr::Lambda(..) => LAMBDA_PRECEDENCE,
+        _ => TERMINAL_PRECEDENCE,
+    }
+}
+
+/// Formats `expr`, surrounding it with parentheses if its precedence is lower than
+/// `min_precedence`.
+fn format_operand(expr: &SugaredExpr, min_precedence: u8, indent: usize) -> String {
+    if expr_precedence(expr) < min_precedence {
+        format!("({})", format_expr(expr, indent))
+    } else {
+        format_expr(expr, indent)
+    }
+}
+
+/// Formats the argument list of a call. Arguments are placed on a single line unless there are
+/// comments between them, in which case each argument is placed on its own line.
+fn format_args(args: &[SugaredExpr], indent: usize) -> String {
+    let has_trivia = args.iter().any(|arg| matches!(arg, SugaredExpr::Trivia(_)));
+    if !has_trivia {
+        let args: Vec<String> = args.iter().map(|arg| format_expr(arg, indent)).collect();
+        return format!("({})", args.join(", "));
+    }
+
+    let inner_ind = INDENT.repeat(indent + 1);
+    let arg_count = args
+        .iter()
+        .filter(|arg| !matches!(arg, SugaredExpr::Trivia(_)))
+        .count();
+    let mut out = "(\n".to_string();
+    let mut arg_index = 0;
+    for arg in args {
+        match arg {
+            SugaredExpr::Trivia(Trivia::BlankLine) => (),
+            SugaredExpr::Trivia(Trivia::TrailingComment(comment)) => {
+                append_trailing_comment(&mut out, comment)
+            }
+            SugaredExpr::Trivia(trivia) => out += format!("{inner_ind}{trivia}\n").as_str(),
+            arg => {
+                arg_index += 1;
+                let separator = if arg_index < arg_count { "," } else { "" };
+                out += format!("{inner_ind}{}{separator}\n", format_expr(arg, indent + 1)).as_str();
+            }
+        }
+    }
+    out + INDENT.repeat(indent).as_str() + ")"
+}
+
+fn format_string_literal(s: &str) -> String {
+    let mut out = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\\' => out.push_str("\\\\"),
+            '\0' => out.push_str("\\0"),
+            '"' => out.push_str("\\\""),
+            c if c.is_control() => out.push_str(format!("\\u{{{:x}}}", c as u32).as_str()),
+            c => out.push(c),
+        }
+    }
+    out + "\""
+}
+
+/// Appends a trailing comment to the last line of `out`, which must end in a newline.
+fn append_trailing_comment(out: &mut String, comment: &str) {
+    let had_newline = out.ends_with('\n');
+    if had_newline {
+        out.pop();
+    }
+    out.push_str(format!(" //{comment}").as_str());
+    if had_newline {
+        out.push('\n');
+    }
+}
+
+/// Removes the blank lines which cannot be preserved by the formatter because they are not
+/// between two statements or two top-level items, such as blank lines between the arguments of a
+/// call.
+fn remove_misplaced_blank_lines(tokens: Vec<Token>) -> Vec<Token> {
+    let mut brackets: Vec<TokenValue> = vec![];
+    let mut previous: Option<TokenValue> = None;
+    let mut result = vec![];
+    for (index, token) in tokens.iter().enumerate() {
+        match &token.0 {
+            TRIVIA(Trivia::BlankLine) => {
+                let in_block = matches!(brackets.last(), None | Some(L_CURLY_BRACKET));
+                let after_statement = matches!(
+                    previous,
+                    None | Some(SEMICOLON | L_CURLY_BRACKET | R_CURLY_BRACKET)
+                );
+                let before_else = tokens[index..]
+                    .iter()
+                    .find(|token| !matches!(token.0, TRIVIA(_)))
+                    .is_some_and(|token| token.0 == KW(Keyword::Else));
+                if in_block && after_statement && !before_else {
+                    result.push(token.clone());
+                }
+                continue;
+            }
+            TRIVIA(_) => {
+                result.push(token.clone());
+                continue;
+            }
+            LPAREN | L_CURLY_BRACKET | L_SQUARE_BRACKET => brackets.push(token.0.clone()),
+            RPAREN | R_CURLY_BRACKET | R_SQUARE_BRACKET => {
+                brackets.pop();
+            }
+            _ => (),
+        }
+        previous = Some(token.0.clone());
+        result.push(token.clone());
+    }
+    result
+}
@@ -1,20 +1,56 @@
-use std::fmt;
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
 
-use crate::{desugar::Statement, environment::Environment, error::RuntimeError, parser::Program, Writer};
+use crate::{
+    desugar::{Expr, Statement},
+    environment::{AssignmentType, Binding, Environment, Mutability},
+    error::RuntimeError::{self, *},
+    parser::{Param, Program, Span},
+    Writer,
+};
 
-use self::statements::interp_statement;
+use self::statements::{interp_statement, ControlFlow};
+
+pub use self::coverage::Coverage;
+pub use self::debugger::{DebugFrontend, Debugger, Frame, Pause, PauseReason, Resume};
+pub use self::io::{exit_code, Capabilities, Capability, FIRST_RESERVED_EXIT_CODE};
+pub use self::limits::{CancellationHandle, ExecutionLimits};
+pub use self::observer::Observer;
+pub use self::profiler::{ProcedureProfile, Profiler};
 
 #[derive(Clone, Debug)]
 pub enum Value {
     Num(f64),
     Bool(bool),
     Str(String),
-    Proc(Vec<String>, Statement, Environment),
+    /// A procedure, holding its name, parameters, body and the environment it was defined in. A
+    /// lambda is named after the position at which it is defined, such as `<lambda@3:11>`.
+    Proc(String, Vec<Param<Expr>>, Box<Statement>, Environment),
     List(Vec<Value>),
+    /// An instance of the struct with the given name, holding the value of each field in the
+    /// order in which the fields are declared.
+    Struct(String, Vec<(String, Value)>),
+    /// An instance of a variant, holding the name of its enum, the name of the variant and the
+    /// values of its fields.
+    Variant(String, String, Vec<Value>),
     // ! consider if Nil should be an explicit value or just return an Option<Value> instead where None represents Nil
     Nil,
 }
 
+impl Value {
+    /// Returns the value as it is shown by tools such as the debugger, in which a string is quoted
+    /// so that it can be told apart from other values.
+    pub fn to_quoted_string(&self) -> String {
+        match self {
+            Value::Str(s) => format!("{s:?}"),
+            value => value.to_string(),
+        }
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -28,16 +64,375 @@ impl fmt::Display for Value {
                 let list_string = values_as_strings.join(", ");
                 write!(f, "[{list_string}]")
             }
+            Value::Struct(name, fields) if fields.is_empty() => write!(f, "{name} {{}}"),
+            Value::Struct(name, fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| match value {
+                        Value::Str(s) => format!("{field}: {s:?}"),
+                        value => format!("{field}: {value}"),
+                    })
+                    .collect();
+                write!(f, "{name} {{ {} }}", fields.join(", "))
+            }
+            Value::Variant(_, variant, fields) if fields.is_empty() => write!(f, "{variant}"),
+            Value::Variant(_, variant, fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|value| match value {
+                        Value::Str(s) => format!("{s:?}"),
+                        value => value.to_string(),
+                    })
+                    .collect();
+                write!(f, "{variant}({})", fields.join(", "))
+            }
         }
     }
 }
 
+mod assertions;
+mod coverage;
+mod debugger;
 mod expressions;
+mod io;
+mod limits;
+mod observer;
+mod patterns;
+mod profiler;
 mod statements;
 mod utils;
 
-pub fn interp_program<'a>(p: Program, writer: &mut Writer) -> Result<Value, RuntimeError> {
-    return match interp_statement(&mut Environment::new(p.procedures), p.main, false, writer)? {
-        (value, _) => Ok(value),
+/// The state of a single run of a Linger program. The interpreter owns the configuration set by
+/// the host (such as [ExecutionLimits]) along with the bookkeeping needed to enforce it, and is
+/// passed through every step of evaluation.
+pub struct Interpreter<'w, 'a> {
+    pub(crate) writer: &'w mut Writer<'a>,
+    limits: ExecutionLimits,
+    cancellation: CancellationHandle,
+    pub(crate) capabilities: Capabilities,
+    pub(crate) args: Vec<String>,
+    pub(crate) started: Instant,
+    /// The names of the fields of each struct declared by the running program.
+    pub(crate) structs: HashMap<String, Vec<String>>,
+    /// The name of the enum and the names of the fields of each variant declared by the running
+    /// program.
+    pub(crate) variants: HashMap<String, (String, Vec<String>)>,
+    pub(crate) debugger: Option<Debugger>,
+    profiler: Option<Profiler>,
+    observers: Vec<Box<dyn Observer>>,
+    /// The position of the statement which started most recently, which is only kept while there
+    /// are observers.
+    statement_span: Span,
+    /// Whether to write the arguments and return value of every procedure call to the error
+    /// stream.
+    trace: bool,
+    steps: u64,
+    call_depth: usize,
+    allocated: usize,
+    deadline: Option<Instant>,
+}
+
+impl<'w, 'a> Interpreter<'w, 'a> {
+    /// Creates an interpreter with no execution limits and no [Capabilities] which prints to
+    /// `writer`.
+    pub fn new(writer: &'w mut Writer<'a>) -> Self {
+        Self {
+            writer,
+            limits: ExecutionLimits::none(),
+            cancellation: CancellationHandle::new(),
+            capabilities: Capabilities::none(),
+            args: vec![],
+            started: Instant::now(),
+            structs: HashMap::new(),
+            variants: HashMap::new(),
+            debugger: None,
+            profiler: None,
+            observers: vec![],
+            statement_span: Span { line: 0, col: 0 },
+            trace: false,
+            steps: 0,
+            call_depth: 0,
+            allocated: 0,
+            deadline: None,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Sets the command-line arguments returned by the `args` builtin.
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Attaches `debugger`, which pauses the program at breakpoints and steps through it.
+    pub fn with_debugger(mut self, debugger: Debugger) -> Self {
+        self.debugger = Some(debugger);
+        self
+    }
+
+    /// Attaches `profiler`, which measures the time spent in each procedure.
+    pub fn with_profiler(mut self, profiler: Profiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    /// Attaches `observer`, which is told about every statement, procedure call and print of each
+    /// run. Observers are told about each event in the order in which they were attached.
+    pub fn with_observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Writes a line to the error stream when each procedure is called, holding the values of its
+    /// arguments, and another when it returns, holding its return value.
+    pub fn with_tracing(mut self) -> Self {
+        self.trace = true;
+        self
+    }
+
+    /// Returns the attached profiler, which holds the profile of the last run.
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    /// Returns a handle which can be used to cancel a run of this interpreter from another thread.
+    pub fn cancellation_handle(&self) -> CancellationHandle {
+        self.cancellation.clone()
+    }
+
+    /// Runs the program `p`, returning the return value of its main procedure.
+    pub fn run(&mut self, p: Program) -> Result<Value, RuntimeError> {
+        self.steps = 0;
+        self.call_depth = 0;
+        self.allocated = 0;
+        self.started = Instant::now();
+        self.deadline = self.limits.deadline.map(|deadline| Instant::now() + deadline);
+        // the parameter of `main` is bound to the arguments which the host passed to it, so unlike
+        // the `args` builtin it needs no capability
+        let main_args = match p.args_param {
+            Some(param) => {
+                self.allocate(self.args.len())?;
+                let args = self.args.iter().map(|arg| Value::Str(arg.to_string()));
+                vec![(param, Value::List(args.collect()))]
+            }
+            None => vec![],
+        };
+        if let Some(debugger) = &mut self.debugger {
+            debugger.start();
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.start();
+        }
+        self.structs = p
+            .structs
+            .into_iter()
+            .map(|strukt| (strukt.name, strukt.fields))
+            .collect();
+        self.variants = p
+            .enums
+            .into_iter()
+            .flat_map(|enumeration| {
+                let name = enumeration.name;
+                enumeration
+                    .variants
+                    .into_iter()
+                    .map(move |variant| (variant.name, (name.clone(), variant.fields)))
+            })
+            .collect();
+
+        let constructors = self.variants.iter().map(constructor).collect();
+        let mut env = Environment::new(p.procedures).extend(constructors);
+        for (param, args) in &main_args {
+            env.insert_new_mutable_value(param.clone(), args.clone());
+        }
+        for observer in &mut self.observers {
+            observer.on_call("main", &main_args);
+        }
+        let result = interp_statement(&mut env, p.main, false, self);
+        if let Some(profiler) = &mut self.profiler {
+            profiler.exit();
+        }
+        self.observe_result(&result);
+
+        let (value, _) = result?;
+        Ok(value)
+    }
+
+    /// Records a single step of evaluation, failing if the step limit has been reached, the
+    /// deadline has passed, or the run has been cancelled.
+    pub(crate) fn step(&mut self) -> Result<(), RuntimeError> {
+        self.steps += 1;
+        if let Some(max_steps) = self.limits.max_steps {
+            if self.steps > max_steps {
+                return Err(StepLimitExceeded(max_steps));
+            }
+        }
+        if self.cancellation.is_cancelled() {
+            return Err(Cancelled);
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(DeadlineExceeded(
+                    self.limits.deadline.unwrap_or(Duration::ZERO),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records entry into a call to the procedure `name` with its parameters bound to `args`,
+    /// failing if the maximum call depth has been reached. Every successful call to this function
+    /// must be paired with a call to [Self::exit_call].
+    pub(crate) fn enter_call(&mut self, name: &str, args: &[Binding]) -> Result<(), RuntimeError> {
+        if let Some(max_call_depth) = self.limits.max_call_depth {
+            if self.call_depth >= max_call_depth {
+                return Err(CallDepthExceeded(max_call_depth));
+            }
+        }
+        if self.trace {
+            let args: Vec<String> = args
+                .iter()
+                .map(|(param, (value, ..))| format!("{param}: {}", value.to_quoted_string()))
+                .collect();
+            self.write_trace(format!("-> {name}({})", args.join(", ")));
+        }
+        if !self.observers.is_empty() {
+            let args: Vec<(String, Value)> = args
+                .iter()
+                .map(|(param, (value, ..))| (param.clone(), value.clone()))
+                .collect();
+            for observer in &mut self.observers {
+                observer.on_call(name, &args);
+            }
+        }
+        self.call_depth += 1;
+        if let Some(debugger) = &mut self.debugger {
+            debugger.enter_frame(name);
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.enter(name);
+        }
+        Ok(())
+    }
+
+    /// Records the end of a call to the procedure `name` which had the given result.
+    pub(crate) fn exit_call(
+        &mut self,
+        name: &str,
+        result: &Result<(Value, ControlFlow), RuntimeError>,
+    ) {
+        self.call_depth -= 1;
+        if let Some(debugger) = &mut self.debugger {
+            debugger.exit_frame();
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.exit();
+        }
+        self.observe_result(result);
+        if self.trace {
+            match result {
+                Ok((value, _)) => {
+                    self.write_trace(format!("<- {name} returned {}", value.to_quoted_string()))
+                }
+                Err(e) => self.write_trace(format!("<- {name} failed: {e}")),
+            }
+        }
+    }
+
+    /// Tells the observers that the statement at `span` is about to run.
+    pub(crate) fn observe_statement(&mut self, span: Span) {
+        if self.observers.is_empty() {
+            return;
+        }
+        self.statement_span = span;
+        for observer in &mut self.observers {
+            observer.on_statement(span);
+        }
+    }
+
+    /// Tells the observers that the `if` statement or loop at `span` took the branch numbered
+    /// `branch`.
+    pub(crate) fn observe_branch(&mut self, span: Span, branch: usize) {
+        for observer in &mut self.observers {
+            observer.on_branch(span, branch);
+        }
+    }
+
+    /// Tells the observers that the program printed `text` to its output stream.
+    pub(crate) fn observe_print(&mut self, text: &str) {
+        for observer in &mut self.observers {
+            observer.on_print(text);
+        }
+    }
+
+    /// Tells the observers that the innermost procedure call has ended with `result`.
+    fn observe_result(&mut self, result: &Result<(Value, ControlFlow), RuntimeError>) {
+        for observer in &mut self.observers {
+            match result {
+                Ok((value, _)) => observer.on_return(value),
+                Err(e) => observer.on_error(e),
+            }
+        }
+    }
+
+    /// Writes a line of the trace to the error stream, indented by the current call depth. The
+    /// trace is only a diagnostic, so a failure to write it does not stop the run.
+    fn write_trace(&mut self, line: String) {
+        let indent = "  ".repeat(self.call_depth);
+        let _ = self.writer.write_err(format!("{indent}{line}\n").as_str());
+    }
+
+    /// Records the allocation of `n` list elements or string characters, failing if the total
+    /// allocation limit has been exceeded.
+    pub(crate) fn allocate(&mut self, n: usize) -> Result<(), RuntimeError> {
+        self.allocated += n;
+        if let Some(max_allocation) = self.limits.max_allocation {
+            if self.allocated > max_allocation {
+                return Err(AllocationLimitExceeded(max_allocation));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the binding of the constructor of the variant named `variant`. A variant without fields
+/// is bound to its only instance, and any other variant to a procedure which takes the values of
+/// its fields.
+fn constructor((variant, (enumeration, fields)): (&String, &(String, Vec<String>))) -> Binding {
+    let value = match fields.is_empty() {
+        true => Value::Variant(enumeration.clone(), variant.clone(), vec![]),
+        false => {
+            let field_exprs = fields.iter().map(|field| Expr::Var(field.clone())).collect();
+            let body = Expr::Variant(variant.clone(), field_exprs);
+            let params = fields
+                .iter()
+                .map(|field| Param {
+                    name: field.clone(),
+                    default: None,
+                    rest: false,
+                })
+                .collect();
+            let body = Box::new(Statement::Expr(body));
+            Value::Proc(variant.clone(), params, body, Environment::new(vec![]))
+        }
     };
+    (
+        variant.clone(),
+        (value, AssignmentType::Initialized, Mutability::Constant),
+    )
+}
+
+/// Runs the program `p` with no execution limits and no capabilities, returning the return value of its main
+/// procedure.
+pub fn interp_program(p: Program, writer: &mut Writer) -> Result<Value, RuntimeError> {
+    Interpreter::new(writer).run(p)
 }
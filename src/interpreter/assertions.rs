@@ -0,0 +1,83 @@
+use crate::{
+    desugar::Expr,
+    environment::Environment,
+    error::RuntimeError::{self, *},
+    parser::Builtin,
+};
+
+use super::{
+    expressions::{interp_args, interp_call, interp_expression},
+    utils::structurally_equal,
+    Interpreter, Value,
+};
+
+/// Calls the assertion builtin `builtin` with the unevaluated `args`.
+pub fn interp_assertion(
+    env: &mut Environment,
+    builtin: Builtin,
+    args: Vec<Expr>,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    let expected = match builtin {
+        Builtin::Assert if args.len() == 2 => 2,
+        Builtin::Assert | Builtin::AssertError => 1,
+        _ => 2,
+    };
+    if args.len() != expected {
+        return Err(ArgMismatch(builtin.to_string(), expected, args.len()));
+    }
+    let mut values = vec![];
+    for expr in args {
+        values.push(interp_expression(env, expr, ctx)?);
+    }
+
+    match (builtin, values.as_slice()) {
+        (Builtin::Assert, [Value::Bool(true), ..]) => Ok(Value::Nil),
+        (Builtin::Assert, [Value::Bool(false)]) => Err(AssertionFailed(None)),
+        (Builtin::Assert, [Value::Bool(false), Value::Str(message)]) => {
+            Err(AssertionFailed(Some(message.to_string())))
+        }
+        (Builtin::Assert, [Value::Bool(_), bad_value]) => Err(BadArg(Box::new(bad_value.clone()))),
+        (Builtin::Assert, [bad_value, ..]) => Err(ExpectedBool(Box::new(bad_value.clone()))),
+        (Builtin::AssertEq, [left, right]) => match structurally_equal(left, right) {
+            true => Ok(Value::Nil),
+            false => Err(AssertEqFailed(
+                Box::new(left.clone()),
+                Box::new(right.clone()),
+            )),
+        },
+        (Builtin::AssertError, _) => {
+            let f = values.into_iter().next().unwrap_or(Value::Nil);
+            assert_error(env, f, ctx)
+        }
+        _ => unreachable!("\"{builtin}\" is not an assertion builtin"),
+    }
+}
+
+/// Calls the procedure `f` without arguments, returning the message of the error which it fails
+/// with. Errors which stop the whole run, such as exceeding a limit of the interpreter, are not
+/// caught.
+fn assert_error(
+    env: &mut Environment,
+    f: Value,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    let (name, params, body, f_env) = match f {
+        Value::Proc(name, params, body, f_env) => (name, params, body, f_env),
+        v => return Err(BadArg(Box::new(v))),
+    };
+
+    let param_bindings = interp_args(env, &name, params, vec![], &f_env, ctx)?;
+    match interp_call(&name, *body, f_env, param_bindings, ctx) {
+        Ok(value) => Err(ExpectedError(Box::new(value))),
+        Err(
+            e @ (StepLimitExceeded(_)
+            | CallDepthExceeded(_)
+            | AllocationLimitExceeded(_)
+            | DeadlineExceeded(_)
+            | Cancelled
+            | Exit(_)),
+        ) => Err(e),
+        Err(e) => Ok(Value::Str(e.to_string())),
+    }
+}
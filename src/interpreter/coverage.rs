@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    desugar::{Expr, Statement},
+    parser::{Param, Program, Span},
+};
+
+use super::Observer;
+
+/// The statements and branches of a program, along with how many times each of them ran. It is an
+/// [Observer], which can be shared with the interpreter through an `Rc<RefCell<Coverage>>` so that
+/// it can be read after the run.
+///
+/// A branch belongs to the `if` statement or loop which contains it, and the branches are numbered
+/// as in [Observer::on_branch].
+pub struct Coverage {
+    /// The number of times each statement started, keyed by its position.
+    statements: BTreeMap<Span, u64>,
+    /// The number of times each branch was taken, keyed by the position of the statement which
+    /// contains the branches.
+    branches: BTreeMap<Span, Vec<u64>>,
+}
+
+impl Coverage {
+    /// Creates the coverage of `program` before it has run, in which every statement and branch
+    /// has a count of 0.
+    pub fn new(program: &Program) -> Self {
+        let mut coverage = Self {
+            statements: BTreeMap::new(),
+            branches: BTreeMap::new(),
+        };
+        // the body of a procedure is a block, in which every statement has a position
+        let start = Span { line: 0, col: 0 };
+        coverage.visit_statement(&program.main, start);
+        for procedure in &program.procedures {
+            coverage.visit_params(&procedure.params, start);
+            coverage.visit_statement(&procedure.body, start);
+        }
+        coverage
+    }
+
+    /// Returns the coverage in the LCOV trace file format, as a single record for the source file
+    /// at `path`. A line is counted as many times as the statement on it which ran the most.
+    pub fn lcov(&self, path: &str) -> String {
+        let mut lcov = format!("TN:\nSF:{path}\n");
+
+        let lines = self.lines();
+        for (line, count) in &lines {
+            lcov += &format!("DA:{line},{count}\n");
+        }
+
+        let mut branches_found = 0;
+        let mut branches_hit = 0;
+        let mut block = 0;
+        let mut previous_line = 0;
+        for (span, counts) in &self.branches {
+            block = if span.line == previous_line { block + 1 } else { 0 };
+            previous_line = span.line;
+            let reached = counts.iter().any(|count| *count > 0);
+            for (branch, count) in counts.iter().enumerate() {
+                // LCOV writes a branch of a statement which never ran as `-`
+                let taken = if reached { count.to_string() } else { "-".to_string() };
+                lcov += &format!("BRDA:{},{block},{branch},{taken}\n", span.line);
+                branches_found += 1;
+                if *count > 0 {
+                    branches_hit += 1;
+                }
+            }
+        }
+        lcov += &format!("BRF:{branches_found}\nBRH:{branches_hit}\n");
+
+        let lines_hit = lines.values().filter(|count| **count > 0).count();
+        lcov += &format!("LF:{}\nLH:{lines_hit}\nend_of_record\n", lines.len());
+        lcov
+    }
+
+    /// Returns the proportion of the lines and branches of the program which ran, such as
+    /// `lines: 9/10 (90.0%)`.
+    pub fn summary(&self) -> String {
+        let lines = self.lines();
+        let lines_hit = lines.values().filter(|count| **count > 0).count();
+        let branches: Vec<u64> = self.branches.values().flatten().copied().collect();
+        let branches_hit = branches.iter().filter(|count| **count > 0).count();
+        format!(
+            "lines: {}\nbranches: {}\n",
+            proportion(lines_hit, lines.len()),
+            proportion(branches_hit, branches.len())
+        )
+    }
+
+    /// Returns the number of times each line with a statement ran.
+    fn lines(&self) -> BTreeMap<usize, u64> {
+        let mut lines = BTreeMap::new();
+        for (span, count) in &self.statements {
+            let line_count = lines.entry(span.line).or_insert(0);
+            *line_count = (*line_count).max(*count);
+        }
+        lines
+    }
+
+    /// Records the statements and branches in `statement`, which is contained in the statement at
+    /// `span`.
+    fn visit_statement(&mut self, statement: &Statement, span: Span) {
+        match statement {
+            Statement::Spanned(span, statement) => {
+                self.statements.entry(*span).or_insert(0);
+                self.visit_statement(statement, *span);
+            }
+            Statement::If(..) => {
+                // an `else if` is desugared into an `if` statement nested directly in the else
+                // branch, and its branches belong to the outermost `if` statement
+                let mut branches = 1;
+                let mut next = Some(statement);
+                while let Some(Statement::If(cond, then_statement, else_statement_option)) = next {
+                    self.visit_expression(cond, span);
+                    self.visit_statement(then_statement, span);
+                    branches += 1;
+                    next = else_statement_option.as_deref();
+                }
+                if let Some(else_statement) = next {
+                    self.visit_statement(else_statement, span);
+                }
+                self.branches.entry(span).or_insert(vec![0; branches]);
+            }
+            Statement::While(cond, body) => {
+                self.visit_expression(cond, span);
+                self.visit_statement(body, span);
+                self.branches.entry(span).or_insert(vec![0; 2]);
+            }
+            Statement::Block(statements) => {
+                for statement in statements {
+                    self.visit_statement(statement, span);
+                }
+            }
+            Statement::Expr(expr)
+            | Statement::Let(_, expr)
+            | Statement::Const(_, expr)
+            | Statement::Assign(_, expr)
+            | Statement::FieldAssign(_, _, expr)
+            | Statement::Return(Some(expr)) => self.visit_expression(expr, span),
+            Statement::Return(None) | Statement::Break | Statement::Continue => (),
+        }
+    }
+
+    /// Records the statements and branches in the lambdas and `match` expressions in `expr`.
+    fn visit_expression(&mut self, expr: &Expr, span: Span) {
+        match expr {
+            Expr::Lambda(_, params, body) => {
+                self.visit_params(params, span);
+                self.visit_statement(body, span);
+            }
+            Expr::Match(scrutinee, arms) => {
+                self.visit_expression(scrutinee, span);
+                for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.visit_expression(guard, span);
+                    }
+                    self.visit_statement(&arm.body, span);
+                }
+            }
+            Expr::Binary(_, left, right) | Expr::Index(left, right) => {
+                self.visit_expression(left, span);
+                self.visit_expression(right, span);
+            }
+            Expr::Unary(_, expr) | Expr::Field(expr, _) | Expr::NamedArg(_, expr) => {
+                self.visit_expression(expr, span)
+            }
+            Expr::Call(f, args) => {
+                self.visit_expression(f, span);
+                for arg in args {
+                    self.visit_expression(arg, span);
+                }
+            }
+            Expr::PrimitiveCall(_, args) | Expr::Variant(_, args) => {
+                for arg in args {
+                    self.visit_expression(arg, span);
+                }
+            }
+            Expr::StructLiteral(_, fields) => {
+                for (_, expr) in fields {
+                    self.visit_expression(expr, span);
+                }
+            }
+            Expr::Nil | Expr::Num(_) | Expr::Bool(_) | Expr::Str(_) | Expr::Var(_) => (),
+        }
+    }
+
+    fn visit_params(&mut self, params: &[Param<Expr>], span: Span) {
+        for param in params {
+            if let Some(default) = &param.default {
+                self.visit_expression(default, span);
+            }
+        }
+    }
+}
+
+impl Observer for Coverage {
+    fn on_statement(&mut self, span: Span) {
+        *self.statements.entry(span).or_insert(0) += 1;
+    }
+
+    fn on_branch(&mut self, span: Span, branch: usize) {
+        let counts = self.branches.entry(span).or_default();
+        if counts.len() <= branch {
+            counts.resize(branch + 1, 0);
+        }
+        counts[branch] += 1;
+    }
+}
+
+fn proportion(hit: usize, found: usize) -> String {
+    match found {
+        0 => "0/0".to_string(),
+        _ => format!("{hit}/{found} ({:.1}%)", hit as f64 * 100.0 / found as f64),
+    }
+}
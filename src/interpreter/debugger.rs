@@ -0,0 +1,233 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    environment::Environment,
+    error::RuntimeError::{self, *},
+    parser::{parse_expression, Span},
+    tokenizer::tokenize,
+};
+
+use super::{expressions::interp_expression, Interpreter, Value};
+
+/// A debugger front end, such as an interactive command line or a Debug Adapter Protocol server,
+/// which is consulted every time a program running under a [Debugger] pauses.
+pub trait DebugFrontend {
+    /// Called when the program pauses at a statement, returning how the program should resume.
+    /// While the program is paused, `pause` can be used to inspect its state and to change the
+    /// breakpoints.
+    fn paused(&mut self, pause: &mut Pause) -> Resume;
+}
+
+/// How a paused program continues.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resume {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Pause at the next statement, including one in a procedure called by the current statement.
+    StepIn,
+    /// Pause at the next statement in the current procedure or in one of its callers.
+    StepOver,
+    /// Pause at the next statement after the current procedure returns.
+    StepOut,
+    /// Stop the program, which fails with [Cancelled].
+    Stop,
+}
+
+/// Why a program paused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseReason {
+    /// The program paused at its first statement, before running it.
+    Entry,
+    /// The program reached a statement on a line with a breakpoint.
+    Breakpoint,
+    /// The program finished a step.
+    Step,
+}
+
+/// A procedure call which has not yet returned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// The name of the procedure, or `<lambda>` for a lambda which is not called through a name.
+    pub name: String,
+    /// The line of the statement which the procedure is running.
+    pub line: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Run,
+    Entry,
+    StepIn,
+    /// Stepping over the statement of the frame at the given depth.
+    StepOver(usize),
+    /// Stepping out of the frame at the given depth.
+    StepOut(usize),
+}
+
+/// A debugger which can be attached to an [Interpreter] with [Interpreter::with_debugger]. The
+/// program pauses at statements on lines with breakpoints and after each step, and its
+/// [DebugFrontend] decides how it continues.
+pub struct Debugger {
+    frontend: Box<dyn DebugFrontend>,
+    breakpoints: BTreeSet<usize>,
+    mode: Mode,
+    frames: Vec<Frame>,
+}
+
+impl Debugger {
+    /// Creates a debugger without breakpoints which runs the program until `frontend` sets one.
+    pub fn new(frontend: impl DebugFrontend + 'static) -> Self {
+        Self {
+            frontend: Box::new(frontend),
+            breakpoints: BTreeSet::new(),
+            mode: Mode::Run,
+            frames: vec![],
+        }
+    }
+
+    /// Sets breakpoints on each of `lines`, which start at 1.
+    pub fn with_breakpoints(mut self, lines: impl IntoIterator<Item = usize>) -> Self {
+        self.breakpoints.extend(lines);
+        self
+    }
+
+    /// Pauses the program at its first statement.
+    pub fn with_stop_on_entry(mut self) -> Self {
+        self.mode = Mode::Entry;
+        self
+    }
+
+    /// Records the start of a run, in which `main` is the only procedure which has been called.
+    pub(super) fn start(&mut self) {
+        self.frames = vec![Frame {
+            name: "main".to_string(),
+            line: 0,
+        }];
+    }
+
+    /// Records entry into a call to the procedure `name`.
+    pub(super) fn enter_frame(&mut self, name: &str) {
+        let line = self.frames.last().map_or(0, |frame| frame.line);
+        self.frames.push(Frame {
+            name: name.to_string(),
+            line,
+        });
+    }
+
+    pub(super) fn exit_frame(&mut self) {
+        self.frames.pop();
+    }
+}
+
+/// The state of a paused program, which a [DebugFrontend] can inspect until it resumes the program.
+pub struct Pause<'p, 'w, 'a> {
+    reason: PauseReason,
+    span: Span,
+    breakpoints: &'p mut BTreeSet<usize>,
+    frames: &'p [Frame],
+    env: &'p mut Environment,
+    ctx: &'p mut Interpreter<'w, 'a>,
+}
+
+impl Pause<'_, '_, '_> {
+    pub fn reason(&self) -> PauseReason {
+        self.reason
+    }
+
+    /// Returns the position of the statement which the program is about to run.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the procedure calls which have not yet returned, starting with `main` and ending
+    /// with the procedure which the program is paused in.
+    pub fn stack(&self) -> &[Frame] {
+        self.frames
+    }
+
+    /// Returns the variables in scope at the paused statement, sorted by name. The procedures and
+    /// variant constructors declared by the program are left out.
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        let mut bindings: Vec<(String, Value)> = self
+            .env
+            .bindings()
+            .into_iter()
+            .filter(|(name, _)| !self.ctx.variants.contains_key(name))
+            .map(|(name, (value, ..))| (name, value))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    /// Returns the lines with breakpoints, which can be changed while the program is paused.
+    pub fn breakpoints(&mut self) -> &mut BTreeSet<usize> {
+        self.breakpoints
+    }
+
+    /// Evaluates the expression `source` in the scope of the paused statement. The expression
+    /// cannot pause the program, even if it calls a procedure with a breakpoint.
+    pub fn evaluate(&mut self, source: &str) -> Result<Value, String> {
+        let tokens = tokenize(source).map_err(|e| e.to_string())?;
+        let expr = parse_expression(tokens.as_slice()).map_err(|e| e.to_string())?;
+        interp_expression(self.env, expr, self.ctx).map_err(|e| e.to_string())
+    }
+}
+
+/// Records that the statement at `span` is about to run, and pauses the program if it should stop
+/// there. This must only be called while a debugger is attached to `ctx`.
+pub fn debug_statement(
+    span: Span,
+    env: &mut Environment,
+    ctx: &mut Interpreter,
+) -> Result<(), RuntimeError> {
+    // the debugger is detached while the program is paused, so that expressions evaluated by the
+    // front end run without it
+    let mut debugger = match ctx.debugger.take() {
+        Some(debugger) => debugger,
+        None => return Ok(()),
+    };
+    if let Some(frame) = debugger.frames.last_mut() {
+        frame.line = span.line;
+    }
+
+    let depth = debugger.frames.len();
+    let reason = match debugger.mode {
+        _ if debugger.breakpoints.contains(&span.line) => Some(PauseReason::Breakpoint),
+        Mode::Entry => Some(PauseReason::Entry),
+        Mode::StepIn => Some(PauseReason::Step),
+        Mode::StepOver(from) if depth <= from => Some(PauseReason::Step),
+        Mode::StepOut(from) if depth < from => Some(PauseReason::Step),
+        _ => None,
+    };
+
+    let mut result = Ok(());
+    if let Some(reason) = reason {
+        let Debugger {
+            frontend,
+            breakpoints,
+            frames,
+            ..
+        } = &mut debugger;
+        let mut pause = Pause {
+            reason,
+            span,
+            breakpoints,
+            frames,
+            env,
+            ctx,
+        };
+        debugger.mode = match frontend.paused(&mut pause) {
+            Resume::Continue => Mode::Run,
+            Resume::StepIn => Mode::StepIn,
+            Resume::StepOver => Mode::StepOver(depth),
+            Resume::StepOut => Mode::StepOut(depth),
+            Resume::Stop => {
+                result = Err(Cancelled);
+                Mode::Run
+            }
+        };
+    }
+
+    ctx.debugger = Some(debugger);
+    result
+}
@@ -1,373 +1,552 @@
 use crate::{
-    desugar::Expr,
-    environment::{AssignmentType, Binding, Entry, Environment, Mutability},
+    desugar::{Expr, MatchArm, Statement},
+    environment::{AssignmentType, Binding, Environment, Mutability},
     error::RuntimeError::{self, *},
-    tokenizer::Operator, Writer,
+    parser::{match_args, ArgSource, Builtin, Param},
+    tokenizer::Operator,
 };
 
 use super::{
-    statements::interp_statement,
-    utils::{ensure_list, ensure_single_arg},
-    Value,
+    assertions::interp_assertion,
+    io::{interp_io_builtin, io_error},
+    patterns::interp_match,
+    statements::{interp_statement, ControlFlow},
+    utils::{ensure_list, ensure_single_arg, get_field, structurally_equal},
+    Interpreter, Value,
 };
 
-pub fn interp_expression<'a>(env: &mut Environment, expr: Expr, writer: &mut Writer) -> Result<Value, RuntimeError> {
+pub fn interp_expression(
+    env: &mut Environment,
+    expr: Expr,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    ctx.step()?;
     match expr {
         Expr::Nil => Ok(Value::Nil),
         Expr::Num(n) => Ok(Value::Num(n)),
         Expr::Bool(b) => Ok(Value::Bool(b)),
         Expr::Str(s) => Ok(Value::Str(s)),
-        Expr::Lambda(params, body) => Ok(Value::Proc(params, *body, env.clone())),
+        Expr::Lambda(span, params, body) => {
+            let name = format!("<lambda@{span}>");
+            Ok(Value::Proc(name, params, body, env.clone()))
+        }
         Expr::Var(id) => match env.get(id.to_string())? {
             v => Ok(v),
         },
-        Expr::Binary(op, left, right) => match op {
-            Operator::Plus => {
-                match (
-                    interp_expression(env, *left, writer)?,
-                    interp_expression(env, *right, writer)?,
-                ) {
-                    (Value::Num(num_left), Value::Num(num_right)) => {
-                        Ok(Value::Num(num_left + num_right))
-                    }
-                    (Value::Str(num_left), Value::Str(num_right)) => {
-                        Ok(Value::Str(num_left + num_right.as_str()))
-                    }
-                    (Value::List(mut list_left), Value::List(mut list_right)) => {
-                        list_left.append(&mut list_right);
-                        Ok(Value::List(list_left))
-                    }
-                    (Value::Num(_), v) => Err(BadArg(v)),
-                    (v, _) => Err(BadArg(v)),
-                }
-            }
-            Operator::Minus => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Num(num_left - num_right))
-                }
-                (Value::Num(_), v) => Err(BadArg(v)),
-                (v, _) => Err(BadArg(v)),
-            },
-            Operator::Eq => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Bool(num_left == num_right))
-                }
-                (Value::Bool(bool_left), Value::Bool(bool_right)) => {
-                    Ok(Value::Bool(bool_left == bool_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::Ne => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Bool(num_left != num_right))
-                }
-                (Value::Bool(bool_left), Value::Bool(bool_right)) => {
-                    Ok(Value::Bool(bool_left != bool_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::LT => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Bool(num_left < num_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::GT => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Bool(num_left > num_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::LTE => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Bool(num_left <= num_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::GTE => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Bool(num_left >= num_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::LogicOr => match interp_expression(env, *left, writer)? {
-                Value::Bool(b) => match b {
-                    true => Ok(Value::Bool(true)),
-                    false => match interp_expression(env, *right, writer)? {
-                        Value::Bool(b) => Ok(Value::Bool(b)),
-                        right_value => Err(BadArg(right_value)),
-                    },
-                },
-                left_value => Err(BadArg(left_value)),
-            },
-            Operator::LogicAnd => match interp_expression(env, *left, writer)? {
-                Value::Bool(b) => match b {
-                    false => Ok(Value::Bool(false)),
-                    true => match interp_expression(env, *right, writer)? {
-                        Value::Bool(b) => Ok(Value::Bool(b)),
-                        right_value => Err(BadArg(right_value)),
-                    },
-                },
-                left_value => Err(BadArg(left_value)),
-            },
-            Operator::Times => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Num(num_left * num_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::Mod => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Num(num_left % num_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            Operator::Div => match (
-                interp_expression(env, *left, writer)?,
-                interp_expression(env, *right, writer)?,
-            ) {
-                (Value::Num(num_left), Value::Num(num_right)) => {
-                    Ok(Value::Num(num_left / num_right))
-                }
-                (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
-            },
-            op => Err(UnaryAsBinary(op)),
-        },
-        Expr::Unary(op, operand) => match op {
-            Operator::PreIncrement => {
-                let var_name = match *operand {
-                    Expr::Var(ref id) => id.to_string(),
-                    _ => return Err(InvalidAssignmentTarget),
-                };
+        Expr::Binary(op, left, right) => interp_binary(env, op, *left, *right, ctx),
+        Expr::Unary(op, operand) => interp_unary(env, op, *operand, ctx),
+        Expr::Call(f_expr, args) => {
+            let f_name = match *f_expr {
+                Expr::Var(ref f_name) => f_name.to_string(),
+                _ => "<lambda>".to_string(),
+            };
 
-                let num_value = match interp_expression(env, *operand, writer)? {
-                    Value::Num(n) => n,
-                    v => return Err(BadArg(v)),
-                };
+            let (name, f_params, f_body, f_env) = match interp_expression(env, *f_expr, ctx)? {
+                Value::Proc(name, params, body, env) => (name, params, body, env),
+                v => return Err(BadArg(Box::new(v))),
+            };
 
-                env.reassign(var_name, Value::Num(num_value + 1.0))?;
+            let param_bindings = interp_args(env, &f_name, f_params, args, &f_env, ctx)?;
+            interp_call(&name, *f_body, f_env, param_bindings, ctx)
+        }
+        Expr::PrimitiveCall(builtin, args) => interp_primitive_call(env, builtin, args, ctx),
+        Expr::Index(indexable_expr, index_expr) => {
+            interp_index(env, *indexable_expr, *index_expr, ctx)
+        }
+        Expr::StructLiteral(name, field_exprs) => {
+            interp_struct_literal(env, name, field_exprs, ctx)
+        }
+        Expr::Field(struct_expr, field) => {
+            get_field(interp_expression(env, *struct_expr, ctx)?, &field)
+        }
+        Expr::Match(scrutinee, arms) => interp_match_expression(env, *scrutinee, arms, ctx),
+        Expr::Variant(variant, field_exprs) => interp_variant(env, variant, field_exprs, ctx),
+        // named arguments are taken apart by `interp_args`, so this is only reached if a named
+        // argument is evaluated on its own
+        Expr::NamedArg(_, expr) => interp_expression(env, *expr, ctx),
+    }
+}
 
-                return Ok(Value::Num(num_value + 1.0));
-            }
-            Operator::PostIncrement => {
-                let var_name = match *operand {
-                    Expr::Var(ref id) => id.to_string(),
-                    _ => return Err(InvalidAssignmentTarget),
-                };
+// the following are kept out of `interp_expression` so that they do not add to the size of its
+// stack frame, which limits how deeply procedure calls can be nested
 
-                let original_num_value = match interp_expression(env, *operand, writer)? {
-                    Value::Num(n) => n,
-                    v => return Err(BadArg(v)),
-                };
+/// Evaluates the binary expression `left op right` along with every binary expression down its
+/// left operand, such as the whole of `a + b + c`. The operators are applied in a loop rather
+/// than by recursion, so that a long chain of them cannot overflow the stack.
+fn interp_binary(
+    env: &mut Environment,
+    op: Operator,
+    left: Expr,
+    right: Expr,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    // the operators and right operands of the chain, from the outermost expression in
+    let mut chain = vec![(op, right)];
+    let mut left = left;
+    while let Expr::Binary(op, inner_left, right) = left {
+        ctx.step()?;
+        chain.push((op, *right));
+        left = *inner_left;
+    }
 
-                env.reassign(var_name, Value::Num(original_num_value + 1.0))?;
+    let mut value = interp_expression(env, left, ctx)?;
+    for (op, right) in chain.into_iter().rev() {
+        value = match (op, value) {
+            (Operator::LogicOr, Value::Bool(true)) => Value::Bool(true),
+            (Operator::LogicAnd, Value::Bool(false)) => Value::Bool(false),
+            (Operator::LogicOr | Operator::LogicAnd, Value::Bool(_)) => {
+                match interp_expression(env, right, ctx)? {
+                    Value::Bool(b) => Value::Bool(b),
+                    right_value => return Err(BadArg(Box::new(right_value))),
+                }
+            }
+            (Operator::LogicOr | Operator::LogicAnd, left_value) => {
+                return Err(BadArg(Box::new(left_value)))
+            }
+            (op, left_value) => {
+                let right_value = interp_expression(env, right, ctx)?;
+                apply_binary_operator(op, left_value, right_value, ctx)?
+            }
+        };
+    }
+    Ok(value)
+}
 
-                return Ok(Value::Num(original_num_value));
+/// Applies the binary operator `op`, which is not a logical operator, to the values of its
+/// operands.
+fn apply_binary_operator(
+    op: Operator,
+    left: Value,
+    right: Value,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    match op {
+        Operator::Plus => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Num(num_left + num_right)),
+            (Value::Str(num_left), Value::Str(num_right)) => {
+                ctx.allocate(num_left.chars().count() + num_right.chars().count())?;
+                Ok(Value::Str(num_left + num_right.as_str()))
             }
-            Operator::PreDecrement => {
-                let var_name = match *operand {
-                    Expr::Var(ref id) => id.to_string(),
-                    _ => return Err(InvalidAssignmentTarget),
-                };
+            (Value::List(mut list_left), Value::List(mut list_right)) => {
+                ctx.allocate(list_left.len() + list_right.len())?;
+                list_left.append(&mut list_right);
+                Ok(Value::List(list_left))
+            }
+            (Value::Num(_), v) => Err(BadArg(Box::new(v))),
+            (v, _) => Err(BadArg(Box::new(v))),
+        },
+        Operator::Minus => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Num(num_left - num_right)),
+            (Value::Num(_), v) => Err(BadArg(Box::new(v))),
+            (v, _) => Err(BadArg(Box::new(v))),
+        },
+        Operator::Eq => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Bool(num_left == num_right)),
+            (Value::Bool(bool_left), Value::Bool(bool_right)) => {
+                Ok(Value::Bool(bool_left == bool_right))
+            }
+            (left @ Value::Struct(..), right @ Value::Struct(..))
+            | (left @ Value::Variant(..), right @ Value::Variant(..)) => {
+                Ok(Value::Bool(structurally_equal(&left, &right)))
+            }
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::Ne => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Bool(num_left != num_right)),
+            (Value::Bool(bool_left), Value::Bool(bool_right)) => {
+                Ok(Value::Bool(bool_left != bool_right))
+            }
+            (left @ Value::Struct(..), right @ Value::Struct(..))
+            | (left @ Value::Variant(..), right @ Value::Variant(..)) => {
+                Ok(Value::Bool(!structurally_equal(&left, &right)))
+            }
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::LT => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Bool(num_left < num_right)),
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::GT => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Bool(num_left > num_right)),
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::LTE => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Bool(num_left <= num_right)),
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::GTE => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Bool(num_left >= num_right)),
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::Times => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Num(num_left * num_right)),
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::Mod => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Num(num_left % num_right)),
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        Operator::Div => match (left, right) {
+            (Value::Num(num_left), Value::Num(num_right)) => Ok(Value::Num(num_left / num_right)),
+            (v_left, v_right) => Err(BadArgs(vec![v_left, v_right])),
+        },
+        op => Err(UnaryAsBinary(op)),
+    }
+}
 
-                let num_value = match interp_expression(env, *operand, writer)? {
-                    Value::Num(n) => n,
-                    v => return Err(BadArg(v)),
-                };
+fn interp_unary(
+    env: &mut Environment,
+    op: Operator,
+    operand: Expr,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    match op {
+        Operator::PreIncrement => {
+            let var_name = match operand {
+                Expr::Var(ref id) => id.to_string(),
+                _ => return Err(InvalidAssignmentTarget),
+            };
 
-                env.reassign(var_name, Value::Num(num_value - 1.0))?;
+            let num_value = match interp_expression(env, operand, ctx)? {
+                Value::Num(n) => n,
+                v => return Err(BadArg(Box::new(v))),
+            };
 
-                return Ok(Value::Num(num_value - 1.0));
-            }
-            Operator::PostDecrement => {
-                let var_name = match *operand {
-                    Expr::Var(ref id) => id.to_string(),
-                    _ => return Err(InvalidAssignmentTarget),
-                };
+            env.reassign(var_name, Value::Num(num_value + 1.0))?;
 
-                let original_num_value = match interp_expression(env, *operand, writer)? {
-                    Value::Num(n) => n,
-                    v => return Err(BadArg(v)),
-                };
+            return Ok(Value::Num(num_value + 1.0));
+        }
+        Operator::PostIncrement => {
+            let var_name = match operand {
+                Expr::Var(ref id) => id.to_string(),
+                _ => return Err(InvalidAssignmentTarget),
+            };
+
+            let original_num_value = match interp_expression(env, operand, ctx)? {
+                Value::Num(n) => n,
+                v => return Err(BadArg(Box::new(v))),
+            };
 
-                env.reassign(var_name, Value::Num(original_num_value - 1.0))?;
+            env.reassign(var_name, Value::Num(original_num_value + 1.0))?;
 
-                return Ok(Value::Num(original_num_value));
-            }
-            Operator::Minus => match interp_expression(env, *operand, writer)? {
-                Value::Num(n) => Ok(Value::Num(-n)),
-                v => Err(BadArg(v)),
-            },
-            Operator::LogicNot => match interp_expression(env, *operand, writer)? {
-                Value::Bool(b) => Ok(Value::Bool(!b)),
-                v => Err(BadArg(v)),
-            },
-            op => Err(BinaryAsUnary(op)),
-        },
-        Expr::Call(f_expr, args) => {
-            let f_name = match *f_expr {
-                Expr::Var(ref f_name) => f_name.to_string(),
-                _ => "<lambda>".to_string(),
+            return Ok(Value::Num(original_num_value));
+        }
+        Operator::PreDecrement => {
+            let var_name = match operand {
+                Expr::Var(ref id) => id.to_string(),
+                _ => return Err(InvalidAssignmentTarget),
             };
 
-            let (f_params, f_body, f_env) = match interp_expression(env, *f_expr, writer)? {
-                Value::Proc(params, body, env) => (params, body, env),
-                v => return Err(BadArg(v)),
+            let num_value = match interp_expression(env, operand, ctx)? {
+                Value::Num(n) => n,
+                v => return Err(BadArg(Box::new(v))),
             };
 
-            if args.len() != f_params.len() {
-                return Err(ArgMismatch(
-                    f_name.to_string(),
-                    f_params.len(), // expected
-                    args.len(),     // actual
-                ));
-            }
+            env.reassign(var_name, Value::Num(num_value - 1.0))?;
 
-            let arg_values_result: Result<Vec<Value>, RuntimeError> = args
-                .into_iter()
-                .map(|arg| interp_expression(env, arg, writer))
-                .collect();
-            let arg_values = match arg_values_result {
-                Ok(values) => values,
-                Err(e) => return Err(e),
+            return Ok(Value::Num(num_value - 1.0));
+        }
+        Operator::PostDecrement => {
+            let var_name = match operand {
+                Expr::Var(ref id) => id.to_string(),
+                _ => return Err(InvalidAssignmentTarget),
             };
 
-            let entries: Vec<Entry> = arg_values
-                .into_iter()
-                .map(|v| (v, AssignmentType::Initialized, Mutability::Constant))
-                .collect();
+            let original_num_value = match interp_expression(env, operand, ctx)? {
+                Value::Num(n) => n,
+                v => return Err(BadArg(Box::new(v))),
+            };
 
-            let param_bindings: Vec<Binding> = f_params
-                .iter()
-                .map(|param| param.to_string())
-                .zip(entries)
-                .collect();
+            env.reassign(var_name, Value::Num(original_num_value - 1.0))?;
 
-            return match interp_statement(&mut f_env.extend(param_bindings), f_body, false, writer)? {
-                (value, _) => Ok(value),
-            };
+            return Ok(Value::Num(original_num_value));
         }
-        Expr::PrimitiveCall(builtin, args) => match builtin {
-            crate::parser::Builtin::Print => {
-                let mut values: Vec<Value> = vec![];
-                for expr in args {
-                    values.push(interp_expression(env, expr, writer)?);
-                }
-                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
-                let values = values.join(" ");
+        Operator::Minus => match interp_expression(env, operand, ctx)? {
+            Value::Num(n) => Ok(Value::Num(-n)),
+            v => Err(BadArg(Box::new(v))),
+        },
+        Operator::LogicNot => match interp_expression(env, operand, ctx)? {
+            Value::Bool(b) => Ok(Value::Bool(!b)),
+            v => Err(BadArg(Box::new(v))),
+        },
+        op => Err(BinaryAsUnary(op)),
+    }
+}
 
-                // TODO: write a better expect message
-                writer.w.write(values.as_bytes()).expect("should be able to write");
-                // print!("{}", values);
-                Ok(Value::Nil)
+fn interp_primitive_call(
+    env: &mut Environment,
+    builtin: Builtin,
+    args: Vec<Expr>,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    match builtin {
+        crate::parser::Builtin::Print | crate::parser::Builtin::Eprint => {
+            let mut values: Vec<Value> = vec![];
+            for expr in args {
+                values.push(interp_expression(env, expr, ctx)?);
             }
-            crate::parser::Builtin::List => {
-                let mut values = vec![];
-                for expr in args {
-                    values.push(interp_expression(env, expr, writer)?);
+            let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            let values = values.join(" ");
+
+            let write_result = match builtin {
+                crate::parser::Builtin::Print => {
+                    ctx.observe_print(values.as_str());
+                    ctx.writer.write_out(values.as_str())
                 }
-                Ok(Value::List(values))
+                _ => ctx.writer.write_err(values.as_str()),
+            };
+            write_result.map_err(io_error)?;
+            Ok(Value::Nil)
+        }
+        crate::parser::Builtin::List => {
+            let mut values = vec![];
+            for expr in args {
+                values.push(interp_expression(env, expr, ctx)?);
             }
-            crate::parser::Builtin::IsEmpty => {
-                let arg = ensure_single_arg(args)?;
-                let list = ensure_list(interp_expression(env, arg, writer)?)?;
-                Ok(Value::Bool(list.is_empty()))
+            ctx.allocate(values.len())?;
+            Ok(Value::List(values))
+        }
+        crate::parser::Builtin::IsEmpty => {
+            let arg = ensure_single_arg(args)?;
+            let list = ensure_list(interp_expression(env, arg, ctx)?)?;
+            Ok(Value::Bool(list.is_empty()))
+        }
+        crate::parser::Builtin::IsNil => {
+            let arg = ensure_single_arg(args)?;
+            match interp_expression(env, arg, ctx)? {
+                Value::Nil => Ok(Value::Bool(true)),
+                _ => Ok(Value::Bool(false)),
             }
-            crate::parser::Builtin::IsNil => {
-                let arg = ensure_single_arg(args)?;
-                match interp_expression(env, arg, writer)? {
-                    Value::Nil => Ok(Value::Bool(true)),
-                    _ => Ok(Value::Bool(false)),
-                }
+        }
+        crate::parser::Builtin::Head => {
+            let arg = ensure_single_arg(args)?;
+            let list = ensure_list(interp_expression(env, arg, ctx)?)?;
+
+            match list.as_slice() {
+                [hd, ..] => Ok(hd.clone()),
+                [] => Ok(Value::Nil),
             }
-            crate::parser::Builtin::Head => {
-                let arg = ensure_single_arg(args)?;
-                let list = ensure_list(interp_expression(env, arg, writer)?)?;
+        }
+        crate::parser::Builtin::Rest => {
+            let arg = ensure_single_arg(args)?;
+            let list = ensure_list(interp_expression(env, arg, ctx)?)?;
 
-                match list.as_slice() {
-                    [hd, ..] => Ok(hd.clone()),
-                    [] => Ok(Value::Nil),
+            match list.as_slice() {
+                [_, tail @ ..] => {
+                    ctx.allocate(tail.len())?;
+                    Ok(Value::List(tail.to_vec()))
                 }
+                [] => Ok(Value::Nil),
             }
-            crate::parser::Builtin::Rest => {
-                let arg = ensure_single_arg(args)?;
-                let list = ensure_list(interp_expression(env, arg, writer)?)?;
+        }
+        crate::parser::Builtin::ReadLine
+        | crate::parser::Builtin::ReadFile
+        | crate::parser::Builtin::WriteFile
+        | crate::parser::Builtin::AppendFile
+        | crate::parser::Builtin::ListDir
+        | crate::parser::Builtin::GetEnv
+        | crate::parser::Builtin::Args
+        | crate::parser::Builtin::Now
+        | crate::parser::Builtin::MonotonicMs
+        | crate::parser::Builtin::Exit => {
+            let mut values = vec![];
+            for expr in args {
+                values.push(interp_expression(env, expr, ctx)?);
+            }
+            interp_io_builtin(builtin, values, ctx)
+        }
+        crate::parser::Builtin::Assert
+        | crate::parser::Builtin::AssertEq
+        | crate::parser::Builtin::AssertError => interp_assertion(env, builtin, args, ctx),
+    }
+}
+
+fn interp_index(
+    env: &mut Environment,
+    indexable_expr: Expr,
+    index_expr: Expr,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    match interp_expression(env, indexable_expr, ctx)? {
+        Value::List(list) => match interp_expression(env, index_expr, ctx)? {
+            Value::Num(num) => {
+                if num.fract() != 0.0 {
+                    return Err(ExpectedInteger(Box::new(Value::Num(num))));
+                }
 
-                match list.as_slice() {
-                    [_, tail @ ..] => Ok(Value::List(tail.to_vec())),
-                    [] => Ok(Value::Nil),
+                let index = num as i64;
+                if index < 0 {
+                    return Err(IndexOutOfBounds(index));
                 }
+
+                let value = match list.into_iter().nth(index as usize) {
+                    Some(v) => v,
+                    None => return Err(IndexOutOfBounds(index)),
+                };
+
+                return Ok(value);
             }
+            bad_value => return Err(ExpectedInteger(Box::new(bad_value))),
         },
-        Expr::Index(indexable_expr, index_expr) => match interp_expression(env, *indexable_expr, writer)? {
-            Value::List(list) => match interp_expression(env, *index_expr, writer)? {
-                Value::Num(num) => {
-                    if num.fract() != 0.0 {
-                        return Err(ExpectedInteger(Value::Num(num)));
-                    }
-
-                    let index = num as i64;
-                    if index < 0 {
-                        return Err(IndexOutOfBounds(index));
-                    }
-
-                    let value = match list.into_iter().nth(index as usize) {
-                        Some(v) => v,
-                        None => return Err(IndexOutOfBounds(index)),
-                    };
-
-                    return Ok(value);
+        Value::Str(str) => match interp_expression(env, index_expr, ctx)? {
+            Value::Num(num) => {
+                if num.fract() != 0.0 {
+                    return Err(ExpectedInteger(Box::new(Value::Num(num))));
                 }
-                bad_value => return Err(ExpectedInteger(bad_value)),
-            },
-            Value::Str(str) => match interp_expression(env, *index_expr, writer)? {
-                Value::Num(num) => {
-                    if num.fract() != 0.0 {
-                        return Err(ExpectedInteger(Value::Num(num)));
-                    }
-
-                    let index = num as i64;
-                    if index < 0 {
-                        return Err(IndexOutOfBounds(index));
-                    }
-
-                    let character = match str.chars().nth(index as usize) {
-                        Some(char) => char.to_string(),
-                        None => return Err(IndexOutOfBounds(index)),
-                    };
-
-                    return Ok(Value::Str(character));
+
+                let index = num as i64;
+                if index < 0 {
+                    return Err(IndexOutOfBounds(index));
                 }
-                bad_value => return Err(ExpectedInteger(bad_value)),
-            },
-            value => return Err(NotIndexable(value)),
+
+                let character = match str.chars().nth(index as usize) {
+                    Some(char) => char.to_string(),
+                    None => return Err(IndexOutOfBounds(index)),
+                };
+
+                return Ok(Value::Str(character));
+            }
+            bad_value => return Err(ExpectedInteger(Box::new(bad_value))),
         },
+        value => return Err(NotIndexable(Box::new(value))),
+    }
+}
+
+fn interp_struct_literal(
+    env: &mut Environment,
+    name: String,
+    field_exprs: Vec<(String, Expr)>,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    let declared = match ctx.structs.get(&name) {
+        Some(fields) => fields.clone(),
+        None => return Err(UnknownStruct(name)),
+    };
+    if let Some((field, _)) = field_exprs.iter().find(|(f, _)| !declared.contains(f)) {
+        return Err(UnknownField(name, field.to_string()));
+    }
+
+    let mut values = vec![];
+    for (field, expr) in field_exprs {
+        values.push((field, interp_expression(env, expr, ctx)?));
+    }
+
+    // fields are stored in declaration order, whatever order they are initialized in
+    let mut fields = vec![];
+    for field in declared {
+        match values.iter().position(|(f, _)| *f == field) {
+            Some(index) => fields.push(values.swap_remove(index)),
+            None => return Err(MissingField(name, field)),
+        }
+    }
+    ctx.allocate(fields.len())?;
+    Ok(Value::Struct(name, fields))
+}
+
+/// Evaluates a `match` which is not used as a statement. Like the body of a lambda, the body of
+/// one of its arms cannot return from the enclosing procedure.
+fn interp_match_expression(
+    env: &mut Environment,
+    scrutinee: Expr,
+    arms: Vec<MatchArm>,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    match interp_match(env, scrutinee, arms, false, ctx)? {
+        (_, ControlFlow::Break) => Err(BreakNotInLoop),
+        (_, ControlFlow::Continue) => Err(ContinueNotInLoop),
+        (value, _) => Ok(value),
+    }
+}
+
+fn interp_variant(
+    env: &mut Environment,
+    variant: String,
+    field_exprs: Vec<Expr>,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    let enumeration = match ctx.variants.get(&variant) {
+        Some((enumeration, _)) => enumeration.clone(),
+        None => return Err(UnknownVariant(variant)),
+    };
+    let mut fields = vec![];
+    for expr in field_exprs {
+        fields.push(interp_expression(env, expr, ctx)?);
+    }
+    ctx.allocate(fields.len())?;
+    Ok(Value::Variant(enumeration, variant, fields))
+}
+
+/// Evaluates the arguments of a call to the procedure `f_name` from left to right, and binds its
+/// parameters to them.
+pub(super) fn interp_args(
+    env: &mut Environment,
+    f_name: &str,
+    f_params: Vec<Param<Expr>>,
+    args: Vec<Expr>,
+    f_env: &Environment,
+    ctx: &mut Interpreter,
+) -> Result<Vec<Binding>, RuntimeError> {
+    let mut positional = vec![];
+    let mut named = vec![];
+    for arg in args {
+        match arg {
+            Expr::NamedArg(name, expr) => named.push((name, interp_expression(env, *expr, ctx)?)),
+            arg => positional.push(interp_expression(env, arg, ctx)?),
+        }
+    }
+    bind_params(f_name, f_params, positional, named, f_env, ctx)
+}
+
+/// Runs the body of the procedure `name` in `f_env` extended with the bindings of its parameters.
+pub(super) fn interp_call(
+    name: &str,
+    f_body: Statement,
+    f_env: Environment,
+    param_bindings: Vec<Binding>,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    ctx.enter_call(name, &param_bindings)?;
+    let result = interp_statement(&mut f_env.extend(param_bindings), f_body, false, ctx);
+    ctx.exit_call(name, &result);
+
+    let (value, _) = result?;
+    Ok(value)
+}
+
+/// Binds the parameters of the procedure `f_name` to the values of the arguments of a call. The
+/// default values of the parameters which the call leaves out are evaluated in order, in `f_env`
+/// extended with the parameters before them.
+fn bind_params(
+    f_name: &str,
+    params: Vec<Param<Expr>>,
+    mut positional: Vec<Value>,
+    mut named: Vec<(String, Value)>,
+    f_env: &Environment,
+    ctx: &mut Interpreter,
+) -> Result<Vec<Binding>, RuntimeError> {
+    let names: Vec<&str> = named.iter().map(|(name, _)| name.as_str()).collect();
+    let sources = match_args(f_name, &params, positional.len(), &names)?;
+
+    let mut bindings: Vec<Binding> = vec![];
+    for (param, source) in params.into_iter().zip(sources) {
+        let value = match source {
+            ArgSource::Positional(i) => std::mem::replace(&mut positional[i], Value::Nil),
+            ArgSource::Named(i) => std::mem::replace(&mut named[i].1, Value::Nil),
+            ArgSource::Rest => {
+                let rest = positional.split_off(bindings.len().min(positional.len()));
+                ctx.allocate(rest.len())?;
+                Value::List(rest)
+            }
+            ArgSource::Default => {
+                let default = param
+                    .default
+                    .ok_or_else(|| MissingArg(f_name.to_string(), param.name.clone()))?;
+                let mut default_env = f_env.clone().extend(bindings.clone());
+                interp_expression(&mut default_env, default, ctx)?
+            }
+        };
+        let entry = (value, AssignmentType::Initialized, Mutability::Constant);
+        bindings.push((param.name, entry));
     }
+    Ok(bindings)
 }
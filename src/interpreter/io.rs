@@ -0,0 +1,243 @@
+use std::{
+    collections::BTreeSet,
+    env, fmt, fs,
+    io::{self, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::RuntimeError::{self, *},
+    parser::Builtin,
+};
+
+use super::{Interpreter, Value};
+
+/// The exit codes from this one up are reserved for the errors of the interpreter, so that a
+/// program cannot exit with a code which looks like one of them.
+pub const FIRST_RESERVED_EXIT_CODE: u8 = 250;
+
+/// A group of side effects that the embedding host can allow a program to perform. Every I/O
+/// builtin requires exactly one capability.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Capability {
+    /// Reading from standard input (`read_line`).
+    Stdin,
+    /// Reading files and directories (`read_file`, `list_dir`).
+    FileRead,
+    /// Creating and modifying files (`write_file`, `append_file`).
+    FileWrite,
+    /// Reading environment variables and command-line arguments (`getenv`, `args`).
+    Env,
+    /// Reading the clock (`now`, `monotonic_ms`).
+    Time,
+    /// Controlling the process (`exit`).
+    Process,
+}
+
+/// The set of [Capabilities](Capability) granted to a program. By default no capabilities are
+/// granted, so untrusted programs cannot perform any I/O besides printing.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    granted: BTreeSet<Capability>,
+}
+
+impl Capabilities {
+    /// Returns a set with no capabilities granted.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Returns a set with every capability granted.
+    pub fn all() -> Self {
+        Self::none()
+            .with(Capability::Stdin)
+            .with(Capability::FileRead)
+            .with(Capability::FileWrite)
+            .with(Capability::Env)
+            .with(Capability::Time)
+            .with(Capability::Process)
+    }
+
+    pub fn with(mut self, capability: Capability) -> Self {
+        self.granted.insert(capability);
+        self
+    }
+
+    pub fn without(mut self, capability: Capability) -> Self {
+        self.granted.remove(&capability);
+        self
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.granted.contains(&capability)
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::Stdin => write!(f, "stdin"),
+            Capability::FileRead => write!(f, "file-read"),
+            Capability::FileWrite => write!(f, "file-write"),
+            Capability::Env => write!(f, "env"),
+            Capability::Time => write!(f, "time"),
+            Capability::Process => write!(f, "process"),
+        }
+    }
+}
+
+/// Returns the [Capability] needed to call `builtin`, or `None` if `builtin` has no side effects
+/// that need to be granted.
+pub fn required_capability(builtin: Builtin) -> Option<Capability> {
+    match builtin {
+        Builtin::ReadLine => Some(Capability::Stdin),
+        Builtin::ReadFile | Builtin::ListDir => Some(Capability::FileRead),
+        Builtin::WriteFile | Builtin::AppendFile => Some(Capability::FileWrite),
+        Builtin::GetEnv | Builtin::Args => Some(Capability::Env),
+        Builtin::Now | Builtin::MonotonicMs => Some(Capability::Time),
+        Builtin::Exit => Some(Capability::Process),
+        Builtin::Print
+        | Builtin::Eprint
+        | Builtin::List
+        | Builtin::IsEmpty
+        | Builtin::IsNil
+        | Builtin::Head
+        | Builtin::Rest
+        | Builtin::Assert
+        | Builtin::AssertEq
+        | Builtin::AssertError => None,
+    }
+}
+
+/// Calls the I/O builtin `builtin` with the already evaluated `args`, failing if the interpreter
+/// was not granted the capability that the builtin requires.
+pub fn interp_io_builtin(
+    builtin: Builtin,
+    args: Vec<Value>,
+    ctx: &mut Interpreter,
+) -> Result<Value, RuntimeError> {
+    if let Some(capability) = required_capability(builtin) {
+        if !ctx.capabilities.allows(capability) {
+            return Err(CapabilityDenied(builtin.to_string(), capability));
+        }
+    }
+
+    match builtin {
+        Builtin::ReadLine => {
+            ensure_arg_count(builtin, &args, 0)?;
+            match ctx.writer.read_line().map_err(io_error)? {
+                Some(line) => {
+                    ctx.allocate(line.chars().count())?;
+                    Ok(Value::Str(line))
+                }
+                None => Ok(Value::Nil),
+            }
+        }
+        Builtin::ReadFile => {
+            let [path] = ensure_str_args(builtin, args)?;
+            let contents = fs::read_to_string(path).map_err(io_error)?;
+            ctx.allocate(contents.chars().count())?;
+            Ok(Value::Str(contents))
+        }
+        Builtin::WriteFile => {
+            let [path, contents] = ensure_str_args(builtin, args)?;
+            fs::write(path, contents).map_err(io_error)?;
+            Ok(Value::Nil)
+        }
+        Builtin::AppendFile => {
+            let [path, contents] = ensure_str_args(builtin, args)?;
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(io_error)?;
+            file.write_all(contents.as_bytes()).map_err(io_error)?;
+            Ok(Value::Nil)
+        }
+        Builtin::ListDir => {
+            let [path] = ensure_str_args(builtin, args)?;
+            let mut names = vec![];
+            for entry in fs::read_dir(path).map_err(io_error)? {
+                let entry = entry.map_err(io_error)?;
+                names.push(entry.file_name().to_string_lossy().to_string());
+            }
+            names.sort();
+            ctx.allocate(names.len())?;
+            Ok(Value::List(names.into_iter().map(Value::Str).collect()))
+        }
+        Builtin::GetEnv => {
+            let [name] = ensure_str_args(builtin, args)?;
+            match env::var(name) {
+                Ok(value) => Ok(Value::Str(value)),
+                Err(_) => Ok(Value::Nil),
+            }
+        }
+        Builtin::Args => {
+            ensure_arg_count(builtin, &args, 0)?;
+            ctx.allocate(ctx.args.len())?;
+            Ok(Value::List(
+                ctx.args.iter().map(|arg| Value::Str(arg.to_string())).collect(),
+            ))
+        }
+        Builtin::Now => {
+            ensure_arg_count(builtin, &args, 0)?;
+            let since_epoch = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| Io(e.to_string()))?;
+            Ok(Value::Num(since_epoch.as_secs_f64()))
+        }
+        Builtin::MonotonicMs => {
+            ensure_arg_count(builtin, &args, 0)?;
+            Ok(Value::Num(ctx.started.elapsed().as_secs_f64() * 1000.0))
+        }
+        Builtin::Exit => {
+            ensure_arg_count(builtin, &args, 1)?;
+            match args.into_iter().next() {
+                Some(value) => Err(Exit(exit_code(value)?.into())),
+                None => Err(ArgMismatch(builtin.to_string(), 1, 0)),
+            }
+        }
+        builtin => unreachable!("\"{builtin}\" is not an I/O builtin"),
+    }
+}
+
+/// Returns the exit code which `value` stands for when it is passed to `exit` or returned by
+/// `main`, failing if it is not an integer between 0 and 255 or if it is reserved.
+pub fn exit_code(value: Value) -> Result<u8, RuntimeError> {
+    match value {
+        Value::Num(n) if n.fract() != 0.0 => Err(ExpectedInteger(Box::new(value))),
+        Value::Num(n) if !(0.0..=255.0).contains(&n) => Err(ExitCodeOutOfRange(Box::new(value))),
+        Value::Num(n) if n as u8 >= FIRST_RESERVED_EXIT_CODE => Err(ReservedExitCode(n as i32)),
+        Value::Num(n) => Ok(n as u8),
+        value => Err(ExpectedInteger(Box::new(value))),
+    }
+}
+
+pub fn io_error(e: io::Error) -> RuntimeError {
+    Io(e.to_string())
+}
+
+fn ensure_arg_count(builtin: Builtin, args: &[Value], expected: usize) -> Result<(), RuntimeError> {
+    if args.len() != expected {
+        return Err(ArgMismatch(builtin.to_string(), expected, args.len()));
+    }
+    Ok(())
+}
+
+/// Ensures that `args` contains exactly `N` strings, returning them as an array.
+fn ensure_str_args<const N: usize>(
+    builtin: Builtin,
+    args: Vec<Value>,
+) -> Result<[String; N], RuntimeError> {
+    ensure_arg_count(builtin, &args, N)?;
+    let strings: Vec<String> = args
+        .into_iter()
+        .map(|arg| match arg {
+            Value::Str(s) => Ok(s),
+            bad_value => Err(BadArg(Box::new(bad_value))),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(strings
+        .try_into()
+        .expect("the number of arguments to have been checked"))
+}
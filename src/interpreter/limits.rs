@@ -0,0 +1,82 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Resource limits for a single run of the interpreter. A limit of `None` means that the resource
+/// is unbounded, which is the default for every limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionLimits {
+    /// The maximum number of statements and expressions that may be evaluated.
+    pub max_steps: Option<u64>,
+    /// The maximum number of nested procedure calls.
+    pub max_call_depth: Option<usize>,
+    /// The maximum total number of list elements and string characters that may be allocated
+    /// over the course of the run.
+    pub max_allocation: Option<usize>,
+    /// The maximum amount of wall-clock time that the run may take.
+    pub deadline: Option<Duration>,
+}
+
+impl ExecutionLimits {
+    /// Returns limits with every resource unbounded.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Returns conservative limits suitable for running untrusted programs.
+    pub fn sandboxed() -> Self {
+        Self {
+            max_steps: Some(10_000_000),
+            max_call_depth: Some(256),
+            max_allocation: Some(1_000_000),
+            deadline: Some(Duration::from_secs(5)),
+        }
+    }
+
+    pub fn with_max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = Some(max_call_depth);
+        self
+    }
+
+    pub fn with_max_allocation(mut self, max_allocation: usize) -> Self {
+        self.max_allocation = Some(max_allocation);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+}
+
+/// A handle which can be used by the host to stop a running program from another thread. Clones
+/// of a handle all refer to the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests that the program stop. The interpreter returns a
+    /// [Cancelled](crate::error::RuntimeError::Cancelled) error at its next step.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,69 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{error::RuntimeError, parser::Span};
+
+use super::Value;
+
+/// Callbacks through which an embedder can follow a running program, for example to meter, audit
+/// or visualize it. Observers are attached to an [Interpreter](super::Interpreter) with
+/// [with_observer](super::Interpreter::with_observer), and every callback does nothing unless it
+/// is overridden.
+///
+/// Observers cannot change how the program runs. To stop a program, such as when a meter runs
+/// out, an observer can hold a [CancellationHandle](super::CancellationHandle).
+pub trait Observer {
+    /// Called before the statement at `span` runs.
+    fn on_statement(&mut self, _span: Span) {}
+
+    /// Called when a branch of an `if` statement or a loop is taken, with the position of the
+    /// statement which contains it. The branches of an `if` statement are numbered from 0 for its
+    /// first condition, followed by one for each `else if` and then one for the `else`, which is
+    /// taken when no condition holds even if it is left out. A loop takes branch 0 when its body
+    /// runs at least once, and branch 1 when it is skipped.
+    fn on_branch(&mut self, _span: Span, _branch: usize) {}
+
+    /// Called when the procedure `name` is called, with the value bound to each of its
+    /// parameters. The body of `main` is treated as a call to `main` with the command-line
+    /// arguments bound to its parameter, if it has one. Every call
+    /// is followed by a call to either [Self::on_return] or [Self::on_error].
+    fn on_call(&mut self, _name: &str, _args: &[(String, Value)]) {}
+
+    /// Called when the innermost procedure call which has not yet returned returns `value`.
+    fn on_return(&mut self, _value: &Value) {}
+
+    /// Called when the innermost procedure call which has not yet returned fails with `error`.
+    /// An error which is not handled fails every call it passes through, so this is called once for
+    /// each of them.
+    fn on_error(&mut self, _error: &RuntimeError) {}
+
+    /// Called when the program writes `text` to its output stream with `print`.
+    fn on_print(&mut self, _text: &str) {}
+}
+
+/// A shared observer, which the host can still read from once it has been attached to an
+/// interpreter.
+impl<T: Observer> Observer for Rc<RefCell<T>> {
+    fn on_statement(&mut self, span: Span) {
+        self.borrow_mut().on_statement(span)
+    }
+
+    fn on_branch(&mut self, span: Span, branch: usize) {
+        self.borrow_mut().on_branch(span, branch)
+    }
+
+    fn on_call(&mut self, name: &str, args: &[(String, Value)]) {
+        self.borrow_mut().on_call(name, args)
+    }
+
+    fn on_return(&mut self, value: &Value) {
+        self.borrow_mut().on_return(value)
+    }
+
+    fn on_error(&mut self, error: &RuntimeError) {
+        self.borrow_mut().on_error(error)
+    }
+
+    fn on_print(&mut self, text: &str) {
+        self.borrow_mut().on_print(text)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    desugar::{Expr, MatchArm},
+    environment::{AssignmentType, Binding, Environment, Mutability},
+    error::RuntimeError::{self, *},
+    parser::Pattern,
+};
+
+use super::{
+    expressions::interp_expression,
+    statements::{interp_statement, ControlFlow},
+    Interpreter, Value,
+};
+
+/// Runs the first arm of a `match` expression whose pattern matches the value of `scrutinee` and
+/// whose guard holds, in a copy of `env` extended with the variables bound by the pattern.
+pub fn interp_match(
+    env: &mut Environment,
+    scrutinee: Expr,
+    arms: Vec<MatchArm>,
+    in_loop: bool,
+    ctx: &mut Interpreter,
+) -> Result<(Value, ControlFlow), RuntimeError> {
+    let value = interp_expression(env, scrutinee, ctx)?;
+    for arm in arms {
+        let mut bindings = vec![];
+        if !match_pattern(&arm.pattern, &value, &mut bindings, ctx)? {
+            continue;
+        }
+
+        let mut arm_env = env.clone().extend(bindings);
+        if let Some(guard) = arm.guard {
+            let holds = match interp_expression(&mut arm_env, guard, ctx)? {
+                Value::Bool(b) => b,
+                v => return Err(BadArg(Box::new(v))),
+            };
+            env.update_reassigned_entries(&arm_env)?;
+            if !holds {
+                continue;
+            }
+        }
+
+        let result = interp_statement(&mut arm_env, arm.body, in_loop, ctx);
+        env.update_reassigned_entries(&arm_env)?;
+        return result;
+    }
+    Err(NoMatch(Box::new(value)))
+}
+
+/// Returns whether `value` matches `pattern`, appending the variables bound by the pattern to
+/// `bindings`. A binding whose name is a declared variant without fields matches that variant
+/// instead of binding a variable.
+fn match_pattern(
+    pattern: &Pattern,
+    value: &Value,
+    bindings: &mut Vec<Binding>,
+    ctx: &mut Interpreter,
+) -> Result<bool, RuntimeError> {
+    match (pattern, value) {
+        (Pattern::Wildcard, _) => Ok(true),
+        (Pattern::Binding(name), value) => match ctx.variants.get(name) {
+            Some((_, fields)) if !fields.is_empty() => {
+                Err(VariantArity(name.to_string(), fields.len(), 0))
+            }
+            Some(_) => Ok(matches!(value, Value::Variant(_, variant, _) if variant == name)),
+            None => {
+                let entry = (
+                    value.clone(),
+                    AssignmentType::Initialized,
+                    Mutability::Constant,
+                );
+                bindings.push((name.to_string(), entry));
+                Ok(true)
+            }
+        },
+        (Pattern::Nil, value) => Ok(matches!(value, Value::Nil)),
+        (Pattern::Num(n), value) => Ok(matches!(value, Value::Num(m) if m == n)),
+        (Pattern::Bool(b), value) => Ok(matches!(value, Value::Bool(c) if c == b)),
+        (Pattern::Str(s), value) => Ok(matches!(value, Value::Str(t) if t == s)),
+        (Pattern::List(elements, rest), value) => {
+            let list = match value {
+                Value::List(list) => list,
+                _ => return Ok(false),
+            };
+            let fits = match rest {
+                Some(_) => list.len() >= elements.len(),
+                None => list.len() == elements.len(),
+            };
+            if !fits {
+                return Ok(false);
+            }
+            for (element, value) in elements.iter().zip(list) {
+                if !match_pattern(element, value, bindings, ctx)? {
+                    return Ok(false);
+                }
+            }
+            match rest {
+                Some(rest) => {
+                    let tail = list[elements.len()..].to_vec();
+                    ctx.allocate(tail.len())?;
+                    match_pattern(rest, &Value::List(tail), bindings, ctx)
+                }
+                None => Ok(true),
+            }
+        }
+        (Pattern::Variant(name, patterns), value) => {
+            let field_count = match ctx.variants.get(name) {
+                Some((_, fields)) => fields.len(),
+                None => return Err(UnknownVariant(name.to_string())),
+            };
+            if patterns.len() != field_count {
+                return Err(VariantArity(name.to_string(), field_count, patterns.len()));
+            }
+            let fields = match value {
+                Value::Variant(_, variant, fields) if variant == name => fields,
+                _ => return Ok(false),
+            };
+            for (pattern, value) in patterns.iter().zip(fields) {
+                if !match_pattern(pattern, value, bindings, ctx)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// The number of calls to a procedure during a profiled run, and the time spent in them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcedureProfile {
+    /// The name of the procedure, or its definition site for a lambda, such as `<lambda@3:11>`.
+    pub name: String,
+    pub calls: u64,
+    /// The time spent in calls to the procedure, including the time spent in the procedures that
+    /// it calls. The time of a recursive call is only counted once.
+    pub inclusive: Duration,
+    /// The time spent in calls to the procedure, excluding the time spent in the procedures that
+    /// it calls.
+    pub exclusive: Duration,
+}
+
+/// A procedure call which has not yet returned.
+struct ActiveCall {
+    name: String,
+    started: Instant,
+    /// The time spent in the procedures which this call has called so far.
+    children: Duration,
+}
+
+/// A profiler which can be attached to an [Interpreter](super::Interpreter) with
+/// [with_profiler](super::Interpreter::with_profiler). It counts the calls to each procedure and
+/// measures the time spent in them, treating the body of `main` as a call to `main`.
+#[derive(Default)]
+pub struct Profiler {
+    stack: Vec<ActiveCall>,
+    procedures: HashMap<String, ProcedureProfile>,
+    /// The exclusive time spent in each call stack, keyed by the names of its procedures from the
+    /// outermost to the innermost, separated by semicolons.
+    stacks: HashMap<String, Duration>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discards the profile of any previous run, and records the start of a call to `main`.
+    pub(super) fn start(&mut self) {
+        *self = Self::default();
+        self.enter("main");
+    }
+
+    /// Records the start of a call to the procedure `name`.
+    pub(super) fn enter(&mut self, name: &str) {
+        self.stack.push(ActiveCall {
+            name: name.to_string(),
+            started: Instant::now(),
+            children: Duration::ZERO,
+        });
+    }
+
+    /// Records the end of the innermost call which has not yet returned.
+    pub(super) fn exit(&mut self) {
+        let stack: Vec<&str> = self.stack.iter().map(|call| call.name.as_str()).collect();
+        let stack = stack.join(";");
+        let call = match self.stack.pop() {
+            Some(call) => call,
+            None => return,
+        };
+
+        let inclusive = call.started.elapsed();
+        let exclusive = inclusive.saturating_sub(call.children);
+        if let Some(caller) = self.stack.last_mut() {
+            caller.children += inclusive;
+        }
+        *self.stacks.entry(stack).or_default() += exclusive;
+
+        let recursive = self.stack.iter().any(|caller| caller.name == call.name);
+        let profile =
+            self.procedures
+                .entry(call.name.clone())
+                .or_insert_with(|| ProcedureProfile {
+                    name: call.name,
+                    calls: 0,
+                    inclusive: Duration::ZERO,
+                    exclusive: Duration::ZERO,
+                });
+        profile.calls += 1;
+        profile.exclusive += exclusive;
+        if !recursive {
+            profile.inclusive += inclusive;
+        }
+    }
+
+    /// Returns the profile of each procedure which was called, sorted by exclusive time with the
+    /// longest first.
+    pub fn procedures(&self) -> Vec<ProcedureProfile> {
+        let mut procedures: Vec<ProcedureProfile> = self.procedures.values().cloned().collect();
+        procedures.sort_by(|a, b| b.exclusive.cmp(&a.exclusive).then(a.name.cmp(&b.name)));
+        procedures
+    }
+
+    /// Returns the exclusive time spent in each call stack in the folded format read by flame graph
+    /// tools, with one line per stack holding the names of its procedures separated by semicolons,
+    /// a space and the time in microseconds.
+    pub fn folded_stacks(&self) -> String {
+        let mut stacks: Vec<(&String, &Duration)> = self.stacks.iter().collect();
+        stacks.sort();
+        stacks
+            .into_iter()
+            .map(|(stack, time)| format!("{stack} {}\n", time.as_micros()))
+            .collect()
+    }
+
+    /// Returns a table of the profile of each procedure, in the order of [Self::procedures].
+    pub fn summary(&self) -> String {
+        let procedures = self.procedures();
+        let width = procedures
+            .iter()
+            .map(|procedure| procedure.name.chars().count())
+            .chain(["procedure".len()])
+            .max()
+            .unwrap_or_default();
+
+        let mut summary = format!(
+            "{:width$}  {:>8}  {:>12}  {:>12}\n",
+            "procedure", "calls", "inclusive", "exclusive"
+        );
+        for procedure in procedures {
+            summary += &format!(
+                "{:width$}  {:>8}  {:>12}  {:>12}\n",
+                procedure.name,
+                procedure.calls,
+                milliseconds(procedure.inclusive),
+                milliseconds(procedure.exclusive)
+            );
+        }
+        summary
+    }
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
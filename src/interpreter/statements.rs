@@ -1,10 +1,13 @@
 use crate::{
-    desugar::Statement,
+    desugar::{Expr, Statement},
     environment::Environment,
-    error::RuntimeError::{self, *}, Writer,
+    error::RuntimeError::{self, *},
 };
 
-use super::{expressions::interp_expression, Value};
+use super::{
+    debugger::debug_statement, expressions::interp_expression, patterns::interp_match,
+    utils::set_field, Interpreter, Value,
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum ControlFlow {
@@ -18,94 +21,183 @@ pub fn interp_statement(
     env: &mut Environment,
     statement: Statement,
     in_loop: bool,
-    writer: &mut Writer
+    ctx: &mut Interpreter,
 ) -> Result<(Value, ControlFlow), RuntimeError> {
+    ctx.step()?;
     match statement {
-        Statement::Expr(expr) => match interp_expression(env, expr, writer)? {
+        Statement::Expr(Expr::Match(scrutinee, arms)) => {
+            interp_match(env, *scrutinee, arms, in_loop, ctx)
+        }
+        Statement::Expr(expr) => match interp_expression(env, expr, ctx)? {
             value => Ok((value, ControlFlow::Normal)),
         },
         Statement::Let(id, new_expr) => {
-            let new_value = interp_expression(env, new_expr, writer)?;
+            let new_value = interp_expression(env, new_expr, ctx)?;
             env.insert_new_mutable_value(id, new_value);
             Ok((Value::Nil, ControlFlow::Normal))
         }
         Statement::Const(id, new_expr) => {
-            let new_value = interp_expression(env, new_expr, writer)?;
+            let new_value = interp_expression(env, new_expr, ctx)?;
             env.insert_new_constant_value(id, new_value);
             Ok((Value::Nil, ControlFlow::Normal))
         }
         Statement::Assign(id, expr) => {
-            let value = interp_expression(env, expr, writer)?;
+            let value = interp_expression(env, expr, ctx)?;
             env.reassign(id, value)?;
             Ok((Value::Nil, ControlFlow::Normal))
         }
+        Statement::FieldAssign(id, fields, expr) => {
+            let value = interp_expression(env, expr, ctx)?;
+            let mut strukt = env.get(id.to_string())?;
+            set_field(&mut strukt, &fields, value)?;
+            env.reassign(id, strukt)?;
+            Ok((Value::Nil, ControlFlow::Normal))
+        }
         Statement::If(cond_expr, then_statement, else_statement_option) => {
-            let cond_bool = match interp_expression(env, cond_expr, writer)? {
-                Value::Bool(b) => b,
-                v => return Err(BadArg(v)),
-            };
-            if cond_bool {
-                interp_statement(env, *then_statement, in_loop, writer)
-            } else {
-                match else_statement_option {
-                    Some(else_statement) => interp_statement(env, *else_statement, in_loop, writer),
-                    None => Ok((Value::Nil, ControlFlow::Normal)),
-                }
-            }
+            interp_if(env, cond_expr, *then_statement, else_statement_option, in_loop, ctx)
         }
-        Statement::While(cond_expr, while_block) => Ok(loop {
-            let cond_bool = match interp_expression(env, cond_expr.clone(), writer)? {
-                Value::Bool(b) => b,
-                v => return Err(BadArg(v)),
-            };
-            if cond_bool {
-                match interp_statement(env, *while_block.clone(), true, writer)? {
-                    (value, ControlFlow::Return) => break (value, ControlFlow::Return),
-                    (_, ControlFlow::Break) => break (Value::Nil, ControlFlow::Normal),
-                    (_, ControlFlow::Normal) => (),
-                    (_, ControlFlow::Continue) => (),
-                };
-            } else {
-                break (Value::Nil, ControlFlow::Normal);
-            }
-        }),
+        Statement::While(cond_expr, while_block) => interp_while(env, cond_expr, *while_block, ctx),
         Statement::Return(expr_option) => match expr_option {
-            Some(expr) => Ok((interp_expression(env, expr, writer)?, ControlFlow::Return)),
+            Some(expr) => Ok((interp_expression(env, expr, ctx)?, ControlFlow::Return)),
             None => Ok((Value::Nil, ControlFlow::Return)),
         },
+        Statement::Spanned(span, statement) => {
+            ctx.observe_statement(span);
+            if ctx.debugger.is_some() {
+                debug_statement(span, env, ctx)?;
+            }
+            interp_statement(env, *statement, in_loop, ctx)
+        }
         Statement::Break => Ok((Value::Nil, ControlFlow::Break)),
         Statement::Continue => Ok((Value::Nil, ControlFlow::Continue)),
-        Statement::Block(statements) => {
-            let mut block_value = Value::Nil;
-            let mut block_env = env.clone();
-            for statement in statements {
-                let statement_value = match interp_statement(&mut block_env, statement, in_loop, writer)? {
-                    (value, ControlFlow::Normal) => value,
-                    (value, ControlFlow::Return) => {
-                        env.update_reassigned_entries(&block_env)?;
-                        return Ok((value, ControlFlow::Return));
-                    }
-                    (value, ControlFlow::Break) => {
-                        if in_loop {
-                            env.update_reassigned_entries(&block_env)?;
-                            return Ok((value, ControlFlow::Break));
-                        } else {
-                            return Err(BreakNotInLoop);
-                        }
-                    }
-                    (value, ControlFlow::Continue) => {
-                        if in_loop {
-                            env.update_reassigned_entries(&block_env)?;
-                            return Ok((value, ControlFlow::Continue));
-                        } else {
-                            return Err(ContinueNotInLoop);
-                        }
-                    }
-                };
-                block_value = statement_value;
+        Statement::Block(statements) => interp_block(env, statements, in_loop, ctx),
+    }
+}
+
+// this is kept out of `interp_statement` so that it does not add to the size of its stack frame,
+// which limits how deeply procedure calls can be nested
+fn interp_block(
+    env: &mut Environment,
+    statements: Vec<Statement>,
+    in_loop: bool,
+    ctx: &mut Interpreter,
+) -> Result<(Value, ControlFlow), RuntimeError> {
+    let mut block_value = Value::Nil;
+    let mut block_env = env.clone();
+    for statement in statements {
+        // the statements of a block are spanned, and are unwrapped here rather than by
+        // `interp_statement` so that running them does not take an extra stack frame
+        let statement = match statement {
+            Statement::Spanned(span, statement) => {
+                ctx.observe_statement(span);
+                if ctx.debugger.is_some() {
+                    debug_statement(span, &mut block_env, ctx)?;
+                }
+                *statement
+            }
+            statement => statement,
+        };
+        let statement_value = match interp_statement(&mut block_env, statement, in_loop, ctx)? {
+            (value, ControlFlow::Normal) => value,
+            (value, ControlFlow::Return) => {
+                env.update_reassigned_entries(&block_env)?;
+                return Ok((value, ControlFlow::Return));
+            }
+            (value, ControlFlow::Break) => {
+                if in_loop {
+                    env.update_reassigned_entries(&block_env)?;
+                    return Ok((value, ControlFlow::Break));
+                } else {
+                    return Err(BreakNotInLoop);
+                }
+            }
+            (value, ControlFlow::Continue) => {
+                if in_loop {
+                    env.update_reassigned_entries(&block_env)?;
+                    return Ok((value, ControlFlow::Continue));
+                } else {
+                    return Err(ContinueNotInLoop);
+                }
+            }
+        };
+        block_value = statement_value;
+    }
+    env.update_reassigned_entries(&block_env)?;
+    Ok((block_value, ControlFlow::Normal))
+}
+
+/// Runs an `if` statement along with the `else if` statements chained to it, which are desugared
+/// into `if` statements nested directly in the else branch. The branch which is taken is numbered
+/// from 0 for the first condition, and the else branch follows the last condition.
+fn interp_if(
+    env: &mut Environment,
+    cond_expr: Expr,
+    then_statement: Statement,
+    else_statement_option: Option<Box<Statement>>,
+    in_loop: bool,
+    ctx: &mut Interpreter,
+) -> Result<(Value, ControlFlow), RuntimeError> {
+    let span = ctx.statement_span;
+    let (mut cond_expr, mut then_statement, mut else_statement_option) =
+        (cond_expr, then_statement, else_statement_option);
+    let mut branch = 0;
+    loop {
+        let cond_bool = match interp_expression(env, cond_expr, ctx)? {
+            Value::Bool(b) => b,
+            v => return Err(BadArg(Box::new(v))),
+        };
+        if cond_bool {
+            ctx.observe_branch(span, branch);
+            return interp_statement(env, then_statement, in_loop, ctx);
+        }
+
+        branch += 1;
+        match else_statement_option.map(|else_statement| *else_statement) {
+            Some(Statement::If(else_if_cond, else_if_then, else_if_else)) => {
+                cond_expr = else_if_cond;
+                then_statement = *else_if_then;
+                else_statement_option = else_if_else;
+            }
+            Some(else_statement) => {
+                ctx.observe_branch(span, branch);
+                return interp_statement(env, else_statement, in_loop, ctx);
+            }
+            None => {
+                ctx.observe_branch(span, branch);
+                return Ok((Value::Nil, ControlFlow::Normal));
             }
-            env.update_reassigned_entries(&block_env)?;
-            return Ok((block_value, ControlFlow::Normal));
         }
     }
 }
+
+/// Runs a `while` loop, which is also what a `for` loop is desugared into. The loop takes branch 0
+/// if its body runs at least once, and branch 1 if it is skipped.
+fn interp_while(
+    env: &mut Environment,
+    cond_expr: Expr,
+    while_block: Statement,
+    ctx: &mut Interpreter,
+) -> Result<(Value, ControlFlow), RuntimeError> {
+    let span = ctx.statement_span;
+    let mut checked = false;
+    Ok(loop {
+        let cond_bool = match interp_expression(env, cond_expr.clone(), ctx)? {
+            Value::Bool(b) => b,
+            v => return Err(BadArg(Box::new(v))),
+        };
+        if !checked {
+            ctx.observe_branch(span, if cond_bool { 0 } else { 1 });
+            checked = true;
+        }
+        if cond_bool {
+            match interp_statement(env, while_block.clone(), true, ctx)? {
+                (value, ControlFlow::Return) => break (value, ControlFlow::Return),
+                (_, ControlFlow::Break) => break (Value::Nil, ControlFlow::Normal),
+                (_, ControlFlow::Normal) => (),
+                (_, ControlFlow::Continue) => (),
+            };
+        } else {
+            break (Value::Nil, ControlFlow::Normal);
+        }
+    })
+}
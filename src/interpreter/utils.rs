@@ -16,9 +16,69 @@ pub fn ensure_single_arg(args: Vec<Expr>) -> Result<Expr, RuntimeError> {
     }
 }
 
+/// Returns whether `a` and `b` have the same structure, comparing lists element by element and
+/// structs and variants field by field. Procedures are never equal to each other.
+pub fn structurally_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Num(a), Value::Num(b)) => a == b,
+        (Value::Bool(a), Value::Bool(b)) => a == b,
+        (Value::Str(a), Value::Str(b)) => a == b,
+        (Value::Nil, Value::Nil) => true,
+        (Value::List(a), Value::List(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| structurally_equal(a, b))
+        }
+        (Value::Struct(a_name, a_fields), Value::Struct(b_name, b_fields)) => {
+            a_name == b_name
+                && a_fields.len() == b_fields.len()
+                && a_fields
+                    .iter()
+                    .zip(b_fields)
+                    .all(|((a_field, a), (b_field, b))| {
+                        a_field == b_field && structurally_equal(a, b)
+                    })
+        }
+        (Value::Variant(a_enum, a_variant, a_fields), Value::Variant(b_enum, b_variant, b_fields)) => {
+            a_enum == b_enum
+                && a_variant == b_variant
+                && a_fields.len() == b_fields.len()
+                && a_fields.iter().zip(b_fields).all(|(a, b)| structurally_equal(a, b))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the value of the field `field` of the struct `value`.
+pub fn get_field(value: Value, field: &str) -> Result<Value, RuntimeError> {
+    match value {
+        Value::Struct(name, fields) => match fields.into_iter().find(|(f, _)| f == field) {
+            Some((_, value)) => Ok(value),
+            None => Err(UnknownField(name, field.to_string())),
+        },
+        value => Err(NotAStruct(Box::new(value))),
+    }
+}
+
+/// Replaces the value at the end of the path of fields `fields` in the struct `value` with
+/// `new_value`.
+pub fn set_field(value: &mut Value, fields: &[String], new_value: Value) -> Result<(), RuntimeError> {
+    let [field, rest @ ..] = fields else {
+        *value = new_value;
+        return Ok(());
+    };
+    match value {
+        Value::Struct(name, struct_fields) => {
+            match struct_fields.iter_mut().find(|(f, _)| f == field) {
+                Some((_, value)) => set_field(value, rest, new_value),
+                None => Err(UnknownField(name.to_string(), field.to_string())),
+            }
+        }
+        value => Err(NotAStruct(Box::new(value.clone()))),
+    }
+}
+
 pub fn ensure_list(value: Value) -> Result<Vec<Value>, RuntimeError> {
     match value {
         Value::List(list) => Ok(list),
-        bad_value => Err(ExpectedList(bad_value)),
+        bad_value => Err(ExpectedList(Box::new(bad_value))),
     }
 }
@@ -1,23 +1,83 @@
-use std::{fs::File, io::Write, path::Path};
+use std::{
+    fs::File,
+    io::{self, BufRead, Write},
+    path::Path,
+};
 
 use interpreter::interp_program;
 use parser::parse_program;
 use tokenizer::tokenize;
 
 mod desugar;
+pub mod analysis;
 pub mod environment;
 pub mod error;
+pub mod formatter;
 pub mod interpreter;
+pub mod lint;
 pub mod parser;
+pub mod testing;
 pub mod tokenizer;
+pub mod typecheck;
 
-
+/// The I/O streams available to a running Linger program. The `print` builtin writes to the output
+/// stream, `eprint` writes to the error stream and `read_line` reads from the input stream.
 pub struct Writer<'a> {
-    w: Box<dyn Write + 'a>,
+    out: Box<dyn Write + 'a>,
+    err: Box<dyn Write + 'a>,
+    /// The input stream, or `None` for the process's standard input, which is locked only while a
+    /// line is read from it so that other readers of standard input are not blocked.
+    input: Option<Box<dyn BufRead + 'a>>,
 }
 
 impl<'a> Writer<'a> {
-    pub fn new(w: Box<dyn Write + 'a>) -> Self { Self { w } }
+    /// Creates a writer which prints to `w`, and uses the process's standard error and standard
+    /// input for its error and input streams.
+    pub fn new(w: Box<dyn Write + 'a>) -> Self {
+        Self {
+            out: w,
+            err: Box::new(io::stderr()),
+            input: None,
+        }
+    }
+
+    /// Creates a writer which uses the process's standard output, standard error and standard
+    /// input.
+    pub fn stdio() -> Self {
+        Self::new(Box::new(io::stdout()))
+    }
+
+    pub fn with_stderr(mut self, err: Box<dyn Write + 'a>) -> Self {
+        self.err = err;
+        self
+    }
+
+    pub fn with_stdin(mut self, input: Box<dyn BufRead + 'a>) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    pub(crate) fn write_out(&mut self, s: &str) -> io::Result<()> {
+        self.out.write_all(s.as_bytes())
+    }
+
+    pub(crate) fn write_err(&mut self, s: &str) -> io::Result<()> {
+        self.err.write_all(s.as_bytes())
+    }
+
+    /// Reads a line from the input stream, without its line terminator. Returns `None` at the end
+    /// of the input stream.
+    pub(crate) fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        let read = match &mut self.input {
+            Some(input) => input.read_line(&mut line)?,
+            None => io::stdin().read_line(&mut line)?,
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(&['\n', '\r'][..]).to_string()))
+    }
 }
 
 /// Executes a linger program. On success, this program returns the return value of the main
@@ -33,9 +93,7 @@ pub fn interp<'a>(s: String) -> Result<String, String> {
         Err(e) => return Err(e.to_string()),
     };
 
-    let writer = &mut Writer {
-        w: Box::new(std::io::stdout()),
-    };
+    let writer = &mut Writer::stdio();
 
     return match interp_program(program, writer) {
         Ok(value) => Ok(value.to_string()),
@@ -58,7 +116,7 @@ pub fn interp_to_file<'a>(s: String, path: &Path) -> Result<String, String> {
         Err(e) => return Err(e.to_string()),
     };
 
-    let writer = &mut Writer { w: Box::new(file) };
+    let writer = &mut Writer::new(Box::new(file));
 
     return match interp_program(program, writer) {
         Ok(value) => Ok(value.to_string()),
@@ -76,7 +134,7 @@ pub fn interp_to_buffer<'a>(s: String, buf: &mut Vec<u8>) -> Result<String, Stri
         Err(e) => return Err(e.to_string()),
     };
 
-    let writer = &mut Writer { w: Box::new(buf) };
+    let writer = &mut Writer::new(Box::new(buf));
 
     return match interp_program(program, writer) {
         Ok(value) => Ok(value.to_string()),
@@ -0,0 +1,531 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::{
+    error::RuntimeError,
+    parser::{
+        match_args, Span, SugaredExpr, SugaredMatchArm, SugaredParam, SugaredProcedure,
+        SugaredStatement,
+    },
+};
+
+/// A mistake which the linter can find without running a program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// A `let` or `const` variable which is never read.
+    UnusedVariable,
+    /// An assignment to a `const` variable.
+    ReassignConstant,
+    /// A `break` statement outside of a loop.
+    BreakNotInLoop,
+    /// A `continue` statement outside of a loop.
+    ContinueNotInLoop,
+    /// A call to a top-level procedure whose arguments do not match its parameters.
+    ArgMismatch,
+    /// A statement which follows a `return`, `break` or `continue` statement in the same block.
+    UnreachableCode,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 6] = [
+        Rule::UnusedVariable,
+        Rule::ReassignConstant,
+        Rule::BreakNotInLoop,
+        Rule::ContinueNotInLoop,
+        Rule::ArgMismatch,
+        Rule::UnreachableCode,
+    ];
+
+    /// Returns the rule named `name`, as it is written by [Display](fmt::Display).
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.to_string() == name)
+    }
+
+    /// Returns the level of the rule when it is not configured. Rules for mistakes which always
+    /// fail at runtime are errors, and the rest are warnings.
+    pub fn default_level(self) -> Level {
+        match self {
+            Rule::UnusedVariable | Rule::UnreachableCode => Level::Warn,
+            Rule::ReassignConstant
+            | Rule::BreakNotInLoop
+            | Rule::ContinueNotInLoop
+            | Rule::ArgMismatch => Level::Deny,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::UnusedVariable => write!(f, "unused-variable"),
+            Rule::ReassignConstant => write!(f, "reassign-constant"),
+            Rule::BreakNotInLoop => write!(f, "break-not-in-loop"),
+            Rule::ContinueNotInLoop => write!(f, "continue-not-in-loop"),
+            Rule::ArgMismatch => write!(f, "arg-mismatch"),
+            Rule::UnreachableCode => write!(f, "unreachable-code"),
+        }
+    }
+}
+
+/// How the linter reports violations of a [Rule].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Violations are not reported.
+    Allow,
+    /// Violations are reported as warnings.
+    Warn,
+    /// Violations are reported as errors.
+    Deny,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Allow => write!(f, "allowed"),
+            Level::Warn => write!(f, "warning"),
+            Level::Deny => write!(f, "error"),
+        }
+    }
+}
+
+/// The [Level] of each [Rule]. Rules which are not configured use their
+/// [default level](Rule::default_level).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    levels: BTreeMap<Rule, Level>,
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_level(mut self, rule: Rule, level: Level) -> Self {
+        self.levels.insert(rule, level);
+        self
+    }
+
+    pub fn level(&self, rule: Rule) -> Level {
+        match self.levels.get(&rule) {
+            Some(level) => *level,
+            None => rule.default_level(),
+        }
+    }
+}
+
+/// A violation of a [Rule] found by the linter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub level: Level,
+    /// The position of the statement which violates the rule.
+    pub span: Span,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}: {} [{}]",
+            self.span, self.level, self.message, self.rule
+        )
+    }
+}
+
+/// Checks `procedures` against every [Rule] which is not allowed by `config`, returning the
+/// violations in the order in which they appear in the source.
+pub fn lint(procedures: &[SugaredProcedure], config: &LintConfig) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        config,
+        procedures: procedures
+            .iter()
+            .map(|proc| (proc.name.as_str(), proc.params.as_slice()))
+            .collect(),
+        scopes: vec![],
+        loop_depth: 0,
+        span: Span { line: 1, col: 1 },
+        diagnostics: vec![],
+    };
+
+    for proc in procedures {
+        linter.span = proc.span;
+        linter.push_params(&proc.params);
+        linter.lint_statement(&proc.body);
+        linter.pop_scope();
+    }
+
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span);
+    diagnostics
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BindingKind {
+    Param,
+    Let,
+    Const,
+}
+
+#[derive(Clone, Debug)]
+struct Binding {
+    name: String,
+    kind: BindingKind,
+    span: Span,
+    used: bool,
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    /// The parameters of each top-level procedure.
+    procedures: HashMap<&'a str, &'a [SugaredParam]>,
+    /// The variables bound in each enclosing block, innermost last.
+    scopes: Vec<Vec<Binding>>,
+    /// The number of loops enclosing the current statement within the current procedure.
+    loop_depth: usize,
+    /// The position of the innermost statement being checked.
+    span: Span,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, rule: Rule, span: Span, message: String) {
+        let level = self.config.level(rule);
+        if level != Level::Allow {
+            self.diagnostics.push(Diagnostic {
+                rule,
+                level,
+                span,
+                message,
+            });
+        }
+    }
+
+    fn push_scope(&mut self, params: &[String]) {
+        let span = self.span;
+        self.scopes.push(
+            params
+                .iter()
+                .map(|param| Binding {
+                    name: param.to_string(),
+                    kind: BindingKind::Param,
+                    span,
+                    used: false,
+                })
+                .collect(),
+        );
+    }
+
+    /// Pushes a scope of the parameters `params`, and lints their default values in it.
+    fn push_params(&mut self, params: &[SugaredParam]) {
+        let names: Vec<String> = params.iter().map(|param| param.name.to_string()).collect();
+        self.push_scope(&names);
+        for default in params.iter().filter_map(|param| param.default.as_ref()) {
+            self.lint_expr(default);
+        }
+    }
+
+    fn pop_scope(&mut self) {
+        for binding in self.scopes.pop().unwrap_or_default() {
+            if !binding.used && binding.kind != BindingKind::Param {
+                self.report(
+                    Rule::UnusedVariable,
+                    binding.span,
+                    format!("variable \"{}\" is never used", binding.name),
+                );
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &str, kind: BindingKind, span: Span) {
+        let binding = Binding {
+            name: name.to_string(),
+            kind,
+            span,
+            used: false,
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(binding);
+        }
+    }
+
+    /// Returns the innermost binding of `name`, or `None` if `name` is not a local variable.
+    fn resolve(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|binding| binding.name == name)
+    }
+
+    fn lint_block(&mut self, statements: &[SugaredStatement]) {
+        self.push_scope(&[]);
+        let mut diverged = false;
+        let mut reported_unreachable = false;
+        for statement in statements {
+            if let SugaredStatement::Trivia(_) = statement {
+                continue;
+            }
+            if diverged && !reported_unreachable {
+                let span = match statement {
+                    SugaredStatement::Spanned(span, _) => *span,
+                    _ => self.span,
+                };
+                self.report(
+                    Rule::UnreachableCode,
+                    span,
+                    "unreachable statement".to_string(),
+                );
+                reported_unreachable = true; // only the first unreachable statement is reported
+            }
+            self.lint_statement(statement);
+            diverged = diverged || diverges(statement);
+        }
+        self.pop_scope();
+    }
+
+    fn lint_loop_body(&mut self, body: &SugaredStatement) {
+        self.loop_depth += 1;
+        self.lint_statement(body);
+        self.loop_depth -= 1;
+    }
+
+    fn lint_assignment(&mut self, name: &str, expr: &SugaredExpr) {
+        let span = self.span;
+        self.lint_expr(expr);
+        let is_constant = matches!(
+            self.resolve(name),
+            Some(Binding {
+                kind: BindingKind::Const,
+                ..
+            })
+        );
+        if is_constant {
+            self.report(
+                Rule::ReassignConstant,
+                span,
+                RuntimeError::ReassignConstant(name.to_string()).to_string(),
+            );
+        }
+    }
+
+    fn lint_statement(&mut self, statement: &SugaredStatement) {
+        match statement {
+            SugaredStatement::Spanned(span, statement) => {
+                self.span = *span;
+                self.lint_statement(statement);
+            }
+            SugaredStatement::Expr(SugaredExpr::Match(scrutinee, arms)) => {
+                self.lint_match(scrutinee, arms)
+            }
+            SugaredStatement::Expr(expr) => self.lint_expr(expr),
+            // the span is saved first, since the statements of a lambda in `expr` move it
+            SugaredStatement::Let(name, _, expr) => {
+                let span = self.span;
+                self.lint_expr(expr);
+                self.declare(name, BindingKind::Let, span);
+            }
+            SugaredStatement::Const(name, _, expr) => {
+                let span = self.span;
+                self.lint_expr(expr);
+                self.declare(name, BindingKind::Const, span);
+            }
+            SugaredStatement::Assign(name, expr) => self.lint_assignment(name, expr),
+            SugaredStatement::FieldAssign(name, _, expr) => self.lint_assignment(name, expr),
+            SugaredStatement::OperatorAssignment(_, name, expr) => self.lint_assignment(name, expr),
+            SugaredStatement::Block(statements) => self.lint_block(statements),
+            SugaredStatement::If(cond, then_block, else_ifs, else_block) => {
+                self.lint_expr(cond);
+                self.lint_statement(then_block);
+                for (else_if_cond, else_if_block) in else_ifs {
+                    self.lint_expr(else_if_cond);
+                    self.lint_statement(else_if_block);
+                }
+                if let Some(else_block) = else_block {
+                    self.lint_statement(else_block);
+                }
+            }
+            SugaredStatement::While(cond, body) => {
+                self.lint_expr(cond);
+                self.lint_loop_body(body);
+            }
+            SugaredStatement::For(init, cond, update, body) => {
+                let span = self.span;
+                self.push_scope(&[]);
+                self.lint_statement(init);
+                self.lint_expr(cond);
+                self.loop_depth += 1;
+                self.lint_block(body);
+                self.span = span;
+                self.lint_statement(update);
+                self.loop_depth -= 1;
+                self.pop_scope();
+            }
+            SugaredStatement::Break => {
+                if self.loop_depth == 0 {
+                    self.report(
+                        Rule::BreakNotInLoop,
+                        self.span,
+                        RuntimeError::BreakNotInLoop.to_string(),
+                    );
+                }
+            }
+            SugaredStatement::Continue => {
+                if self.loop_depth == 0 {
+                    self.report(
+                        Rule::ContinueNotInLoop,
+                        self.span,
+                        RuntimeError::ContinueNotInLoop.to_string(),
+                    );
+                }
+            }
+            SugaredStatement::Return(Some(expr)) => self.lint_expr(expr),
+            SugaredStatement::Return(None)
+            | SugaredStatement::Trivia(_)
+            | SugaredStatement::Error(_) => (),
+        }
+    }
+
+    fn lint_expr(&mut self, expr: &SugaredExpr) {
+        match expr {
+            SugaredExpr::Var(name) => {
+                if let Some(binding) = self.resolve(name) {
+                    binding.used = true;
+                }
+            }
+            SugaredExpr::Binary(_, lhs, rhs) => {
+                self.lint_expr(lhs);
+                self.lint_expr(rhs);
+            }
+            SugaredExpr::Unary(_, operand)
+            | SugaredExpr::Field(operand, _)
+            | SugaredExpr::NamedArg(_, operand) => self.lint_expr(operand),
+            SugaredExpr::PrimitiveCall(_, args) => {
+                for arg in args {
+                    self.lint_expr(arg);
+                }
+            }
+            SugaredExpr::Call(proc_expr, args) => {
+                self.lint_expr(proc_expr);
+                for arg in args {
+                    self.lint_expr(arg);
+                }
+                if let SugaredExpr::Var(name) = proc_expr.as_ref() {
+                    let (positional, named) = split_args(args);
+                    self.lint_call(name, positional, &named);
+                }
+            }
+            SugaredExpr::MethodCall(receiver, name, args) => {
+                self.lint_expr(receiver);
+                for arg in args {
+                    self.lint_expr(arg);
+                }
+                if let Some(binding) = self.resolve(name) {
+                    binding.used = true;
+                }
+                // the value before the dot is the first argument
+                let (positional, named) = split_args(args);
+                self.lint_call(name, positional + 1, &named);
+            }
+            SugaredExpr::Lambda(_, params, body) => {
+                // `break` and `continue` cannot leave the body of a lambda
+                let loop_depth = self.loop_depth;
+                self.loop_depth = 0;
+                self.push_params(params);
+                self.lint_statement(body);
+                self.pop_scope();
+                self.loop_depth = loop_depth;
+            }
+            SugaredExpr::Match(scrutinee, arms) => {
+                // like the body of a lambda, the arms of a `match` which is not a statement cannot
+                // leave the enclosing loop
+                let loop_depth = self.loop_depth;
+                self.loop_depth = 0;
+                self.lint_match(scrutinee, arms);
+                self.loop_depth = loop_depth;
+            }
+            SugaredExpr::Index(indexable, index) => {
+                self.lint_expr(indexable);
+                self.lint_expr(index);
+            }
+            SugaredExpr::StructLiteral(_, fields) => {
+                for (_, value) in fields {
+                    self.lint_expr(value);
+                }
+            }
+            SugaredExpr::Nil
+            | SugaredExpr::Num(_)
+            | SugaredExpr::Bool(_)
+            | SugaredExpr::Str(_)
+            | SugaredExpr::Trivia(_) => (),
+        }
+    }
+
+    /// Lints the arms of a `match` in a scope of the variables bound by their patterns, which are
+    /// never reported as unused, like parameters.
+    fn lint_match(&mut self, scrutinee: &SugaredExpr, arms: &[SugaredMatchArm]) {
+        self.lint_expr(scrutinee);
+        for arm in arms {
+            let bindings: Vec<String> = arm
+                .pattern
+                .bindings()
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            self.push_scope(&bindings);
+            if let Some(guard) = &arm.guard {
+                self.lint_expr(guard);
+            }
+            self.lint_statement(&arm.body);
+            self.pop_scope();
+        }
+    }
+
+    /// Checks that the arguments of a call to the top-level procedure `name`, made up of
+    /// `positional` positional arguments and the named arguments `named`, match its parameters,
+    /// unless `name` is shadowed by a local variable.
+    fn lint_call(&mut self, name: &str, positional: usize, named: &[&str]) {
+        if self.resolve(name).is_some() {
+            return;
+        }
+        let Some(params) = self.procedures.get(name).copied() else {
+            return;
+        };
+        if let Err(e) = match_args(name, params, positional, named) {
+            self.report(Rule::ArgMismatch, self.span, e.to_string());
+        }
+    }
+}
+
+/// Returns the number of positional arguments in `args`, which may contain trivia, along with the
+/// names of its named arguments.
+fn split_args(args: &[SugaredExpr]) -> (usize, Vec<&str>) {
+    let mut positional = 0;
+    let mut named = vec![];
+    for arg in args {
+        match arg {
+            SugaredExpr::NamedArg(name, _) => named.push(name.as_str()),
+            SugaredExpr::Trivia(_) => (),
+            _ => positional += 1,
+        }
+    }
+    (positional, named)
+}
+
+/// Returns whether `statement` always leaves the enclosing block, so that any statements after it
+/// are unreachable.
+pub(crate) fn diverges(statement: &SugaredStatement) -> bool {
+    match statement {
+        SugaredStatement::Spanned(_, statement) => diverges(statement),
+        SugaredStatement::Return(_) | SugaredStatement::Break | SugaredStatement::Continue => true,
+        SugaredStatement::Block(statements) => statements.iter().any(diverges),
+        SugaredStatement::If(_, then_block, else_ifs, Some(else_block)) => {
+            diverges(then_block)
+                && else_ifs.iter().all(|(_, block)| diverges(block))
+                && diverges(else_block)
+        }
+        _ => false,
+    }
+}
@@ -1,15 +1,82 @@
-use std::{env, fs, io::stdout, process::ExitCode};
+use std::{
+    cell::RefCell,
+    env, fs,
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+    rc::Rc,
+};
 
-use linger::{interpreter::interp_program, parser::parse_program, tokenizer::tokenize, Writer};
+use linger::{
+    error::{ParseError, RuntimeError},
+    formatter::format_source,
+    interpreter::{
+        exit_code, Capabilities, Coverage, DebugFrontend, Debugger, Interpreter, Pause,
+        PauseReason, Profiler, Resume, Value, FIRST_RESERVED_EXIT_CODE,
+    },
+    lint::{lint, Level, LintConfig, Rule},
+    parser::{parse_declarations_recovering, parse_program_recovering},
+    testing::{self, TestResult},
+    tokenizer::{tokenize, Token},
+    typecheck::typecheck,
+    Writer,
+};
+use serde::Serialize;
+use serde_json::json;
+
+const USAGE: &str =
+    "usage: linger [--profile[=<PATH>]] [--trace] [--coverage[=<PATH>]] [--print-result]
+              [--emit=<tokens|ast|desugared>] <FILE> [--] [ARGS]...
+       linger fmt [--check] <FILE>
+       linger check [--allow <RULE>]... [--warn <RULE>]... [--deny <RULE>]... <FILE>
+       linger debug <FILE> [ARGS]...
+       linger test [--format=<tap|junit>] <PATH>";
+
+/// The exit code of a program which cannot be tokenized.
+const EXIT_TOKENIZE_ERROR: u8 = 250;
+/// The exit code of a program which cannot be parsed.
+const EXIT_PARSE_ERROR: u8 = 251;
+/// The exit code of a program which stops with a runtime error.
+const EXIT_RUNTIME_ERROR: u8 = 252;
+
+const DEBUG_HELP: &str = "commands:
+  continue, c       run until the next breakpoint
+  step, s           run until the next statement
+  next, n           run until the next statement in the current procedure
+  finish, f         run until the current procedure returns
+  break, b <LINE>   set a breakpoint on a line
+  delete, d <LINE>  remove the breakpoint on a line
+  locals, l         print the variables in scope
+  backtrace, bt     print the procedure calls which have not returned
+  print, p <EXPR>   evaluate an expression in the current scope
+  quit, q           stop the program";
 
 fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("usage: linger <FILE>");
-        return ExitCode::FAILURE;
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("fmt") => fmt(&args[2..]),
+        Some("check") => check(&args[2..]),
+        Some("debug") => debug(&args[2..]),
+        Some("test") => test(&args[2..]),
+        Some(_) => run(&args[1..]),
+        None => {
+            eprintln!("{USAGE}");
+            ExitCode::FAILURE
+        }
     }
+}
 
-    let linger_file_name = args[1].as_str();
+/// Formats the Linger file named in `args` in place. With `--check`, the file is left unchanged
+/// and the command fails if the file is not already formatted.
+fn fmt(args: &[String]) -> ExitCode {
+    let (check, linger_file_name) = match args {
+        [flag, file_name] if flag == "--check" => (true, file_name.as_str()),
+        [file_name] => (false, file_name.as_str()),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
 
     let linger_file_content = match fs::read_to_string(linger_file_name) {
         Ok(content) => content,
@@ -19,9 +86,74 @@ fn main() -> ExitCode {
         }
     };
 
-    let debug_tokens = false;
-    let debug_program = false;
-    let debug_value = false;
+    let formatted = match format_source(linger_file_content.as_str()) {
+        Ok(formatted) => formatted,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if check {
+        if formatted != linger_file_content {
+            eprintln!("{linger_file_name} is not formatted");
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    if formatted != linger_file_content {
+        if let Err(e) = fs::write(linger_file_name, formatted) {
+            eprintln!("error writing {linger_file_name}: {e}");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Lints and type checks the Linger file named in `args` without running it, printing any problems
+/// that are found. The command fails if the file cannot be parsed, if any rule at the error level is
+/// violated or if there is a type error.
+fn check(args: &[String]) -> ExitCode {
+    let mut config = LintConfig::new();
+    let mut args = args;
+    let linger_file_name = loop {
+        match args {
+            [flag, rule_name, rest @ ..] if flag.starts_with("--") && !rest.is_empty() => {
+                let level = match flag.as_str() {
+                    "--allow" => Level::Allow,
+                    "--warn" => Level::Warn,
+                    "--deny" => Level::Deny,
+                    _ => {
+                        eprintln!("{USAGE}");
+                        return ExitCode::FAILURE;
+                    }
+                };
+                let rule = match Rule::from_name(rule_name) {
+                    Some(rule) => rule,
+                    None => {
+                        eprintln!("unknown lint rule \"{rule_name}\"");
+                        return ExitCode::FAILURE;
+                    }
+                };
+                config = config.with_level(rule, level);
+                args = rest;
+            }
+            [file_name] => break file_name.as_str(),
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+
+    let linger_file_content = match fs::read_to_string(linger_file_name) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("error opening {linger_file_name}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
     let tokens = match tokenize(linger_file_content.as_str()) {
         Ok(t) => t,
@@ -30,34 +162,416 @@ fn main() -> ExitCode {
             return ExitCode::FAILURE;
         }
     };
-    if debug_tokens {
-        dbg!(&tokens);
+
+    let declarations = match parse_declarations_recovering(tokens.as_slice()) {
+        (declarations, errors) if errors.is_empty() => declarations,
+        (_, errors) => {
+            for e in errors {
+                eprintln!("{e}");
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+    if !declarations
+        .procedures
+        .iter()
+        .any(|proc| proc.name == "main")
+    {
+        eprintln!("{}", ParseError::NoMain);
+        return ExitCode::FAILURE;
+    }
+
+    let diagnostics = lint(&declarations.procedures, &config);
+    for diagnostic in &diagnostics {
+        eprintln!("{linger_file_name}:{diagnostic}");
+    }
+
+    let type_errors = typecheck(&declarations);
+    for (span, e) in &type_errors {
+        eprintln!("{linger_file_name}:{span}: error: {e}");
+    }
+
+    if !type_errors.is_empty()
+        || diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.level == Level::Deny)
+    {
         return ExitCode::FAILURE;
     }
+    ExitCode::SUCCESS
+}
+
+/// Runs the Linger file named by the first element of `args` which is not a flag, passing the
+/// remaining elements to the program as its arguments. Flags may also follow the file name, and
+/// every element after `--` is passed to the program. With `--profile`, the time spent in each
+/// procedure is summarized on standard error and written as folded stacks to `profile.folded`, or
+/// to the path given with `--profile=<PATH>`. With `--trace`, every procedure call and return is
+/// written to standard error. With `--coverage`, the proportion of lines and branches which ran is
+/// summarized on standard error and the coverage is written in the LCOV format to `lcov.info`, or
+/// to the path given with `--coverage=<PATH>`. With `--print-result`, the value returned by `main`
+/// is printed after the program finishes. With `--emit`, the program is not run, and its tokens,
+/// syntax tree or desugared syntax tree is printed as JSON instead.
+///
+/// The command exits with a different code when the program cannot be tokenized, when it cannot be
+/// parsed and when it stops with a runtime error. Otherwise, it exits with the code returned by
+/// `main` if it returns a number, and successfully if it does not. The codes of the errors are
+/// reserved, so a program which exits with one of them fails with a runtime error.
+fn run(args: &[String]) -> ExitCode {
+    let mut profile_path = None;
+    let mut trace = false;
+    let mut coverage_path = None;
+    let mut print_result = false;
+    let mut emit = None;
+    let mut positional = vec![];
+    let mut args = args;
+    while let [arg, rest @ ..] = args {
+        args = rest;
+        match arg.as_str() {
+            "--" => break,
+            "--profile" => profile_path = Some("profile.folded"),
+            "--trace" => trace = true,
+            "--coverage" => coverage_path = Some("lcov.info"),
+            "--print-result" => print_result = true,
+            flag if flag.starts_with("--profile=") => {
+                profile_path = Some(&flag["--profile=".len()..])
+            }
+            flag if flag.starts_with("--coverage=") => {
+                coverage_path = Some(&flag["--coverage=".len()..])
+            }
+            flag if flag.starts_with("--emit=") => match &flag["--emit=".len()..] {
+                stage @ ("tokens" | "ast" | "desugared") => emit = Some(stage),
+                stage => {
+                    eprintln!("unknown stage \"{stage}\" to emit");
+                    return ExitCode::FAILURE;
+                }
+            },
+            flag if flag.starts_with("--") => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+            arg => positional.push(arg),
+        }
+    }
+    let (linger_file_name, script_args) = match positional.as_slice() {
+        [file_name, script_args @ ..] => (
+            *file_name,
+            script_args
+                .iter()
+                .map(|arg| arg.to_string())
+                .chain(args.iter().cloned())
+                .collect(),
+        ),
+        [] => match args {
+            [file_name, script_args @ ..] => (file_name.as_str(), script_args.to_vec()),
+            [] => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    let linger_file_content = match fs::read_to_string(linger_file_name) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("error opening {linger_file_name}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let tokens = match tokenize(linger_file_content.as_str()) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::from(EXIT_TOKENIZE_ERROR);
+        }
+    };
+    if emit == Some("tokens") {
+        let tokens: Vec<serde_json::Value> = tokens
+            .iter()
+            .map(|Token(value, line, col, _)| json!({ "value": value, "line": line, "col": col }))
+            .collect();
+        return emit_json(&tokens);
+    }
+    if emit == Some("ast") {
+        return match parse_declarations_recovering(tokens.as_slice()) {
+            (declarations, errors) if errors.is_empty() => emit_json(&declarations),
+            (_, errors) => {
+                for e in errors {
+                    eprintln!("{e}");
+                }
+                ExitCode::from(EXIT_PARSE_ERROR)
+            }
+        };
+    }
 
-    let program = match parse_program(tokens.as_slice()) {
+    let program = match parse_program_recovering(tokens.as_slice()) {
         Ok(p) => p,
+        Err(errors) => {
+            for e in errors {
+                eprintln!("{e}");
+            }
+            return ExitCode::from(EXIT_PARSE_ERROR);
+        }
+    };
+    if emit == Some("desugared") {
+        return emit_json(&program);
+    }
+
+    let coverage = coverage_path.map(|_| Rc::new(RefCell::new(Coverage::new(&program))));
+    let mut writer = Writer::stdio();
+    let mut interpreter = Interpreter::new(&mut writer)
+        .with_capabilities(Capabilities::all())
+        .with_args(script_args);
+    if let Some(coverage) = &coverage {
+        interpreter = interpreter.with_observer(coverage.clone());
+    }
+    if profile_path.is_some() {
+        interpreter = interpreter.with_profiler(Profiler::new());
+    }
+    if trace {
+        interpreter = interpreter.with_tracing();
+    }
+    let result = interpreter.run(program);
+
+    // the profile and coverage are written even if the program fails, since a failing run is still
+    // worth looking at
+    if let (Some(path), Some(profiler)) = (profile_path, interpreter.profiler()) {
+        eprint!("{}", profiler.summary());
+        if let Err(e) = fs::write(path, profiler.folded_stacks()) {
+            eprintln!("error writing {path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    }
+    if let (Some(path), Some(coverage)) = (coverage_path, coverage) {
+        let coverage = coverage.borrow();
+        eprint!("{}", coverage.summary());
+        if let Err(e) = fs::write(path, coverage.lcov(linger_file_name)) {
+            eprintln!("error writing {path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let value = match result {
+        Ok(v) => v,
+        Err(RuntimeError::Exit(code)) => return program_exit_code(code),
         Err(e) => {
             eprintln!("{e}");
+            return ExitCode::from(EXIT_RUNTIME_ERROR);
+        }
+    };
+    if print_result {
+        println!("{}", value.to_quoted_string());
+    }
+
+    // like the argument to `exit`, a number returned by `main` is the exit code
+    match value {
+        Value::Num(_) => match exit_code(value) {
+            Ok(code) => ExitCode::from(code),
+            Err(e) => {
+                eprintln!("{e}");
+                ExitCode::from(EXIT_RUNTIME_ERROR)
+            }
+        },
+        _ => ExitCode::SUCCESS,
+    }
+}
+
+/// Returns the exit code `code` which a program exits with, failing with a runtime error instead if
+/// the code is reserved.
+fn program_exit_code(code: i32) -> ExitCode {
+    let error = match u8::try_from(code) {
+        Ok(code) if code < FIRST_RESERVED_EXIT_CODE => return ExitCode::from(code),
+        Ok(_) => RuntimeError::ReservedExitCode(code),
+        Err(_) => RuntimeError::ExitCodeOutOfRange(Box::new(Value::Num(code.into()))),
+    };
+    eprintln!("{error}");
+    ExitCode::from(EXIT_RUNTIME_ERROR)
+}
+
+/// Prints `value` to standard output as pretty-printed JSON.
+fn emit_json(value: &impl Serialize) -> ExitCode {
+    let mut stdout = io::stdout().lock();
+    let written = serde_json::to_writer_pretty(&mut stdout, value)
+        .map_err(io::Error::from)
+        .and_then(|_| writeln!(stdout));
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error emitting JSON: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the tests in the Linger file or directory named in `args`, printing a report for people to
+/// read, or a TAP or JUnit XML report with `--format`. The command fails if any test fails.
+fn test(args: &[String]) -> ExitCode {
+    let (format, path) = match args {
+        [flag, path] if flag.starts_with("--format=") => (&flag["--format=".len()..], path),
+        [path] => ("", path),
+        _ => {
+            eprintln!("{USAGE}");
             return ExitCode::FAILURE;
         }
     };
-    if debug_program {
-        dbg!(&program);
-        return ExitCode::FAILURE;
+    let format: fn(&[TestResult]) -> String = match format {
+        "" => testing::report,
+        "tap" => testing::tap,
+        "junit" => testing::junit,
+        format => {
+            eprintln!("unknown test report format \"{format}\"");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let results = match testing::run_tests(Path::new(path)) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("error reading {path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    print!("{}", format(&results));
+    match results.iter().all(TestResult::passed) {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
     }
+}
 
-    let value = match interp_program(program, &mut Writer::new(Box::new(stdout()))) {
-        Ok(v) => v,
+/// Runs the Linger file named by the first element of `args` under an interactive debugger which
+/// reads commands from standard input. The program is paused before its first statement, and
+/// reads from an empty input stream.
+fn debug(args: &[String]) -> ExitCode {
+    let (linger_file_name, script_args) = match args {
+        [file_name, script_args @ ..] => (file_name.as_str(), script_args.to_vec()),
+        [] => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let linger_file_content = match fs::read_to_string(linger_file_name) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("error opening {linger_file_name}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let tokens = match tokenize(linger_file_content.as_str()) {
+        Ok(t) => t,
         Err(e) => {
             eprintln!("{e}");
             return ExitCode::FAILURE;
         }
     };
-    if debug_value {
-        dbg!(value);
-        return ExitCode::SUCCESS;
+
+    let program = match parse_program_recovering(tokens.as_slice()) {
+        Ok(p) => p,
+        Err(errors) => {
+            for e in errors {
+                eprintln!("{e}");
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let console = Console {
+        file_name: linger_file_name.to_string(),
+        lines: linger_file_content.lines().map(str::to_string).collect(),
+    };
+    // the debugger reads its commands from standard input, so the program reads from an empty
+    // input stream
+    let mut writer = Writer::stdio().with_stdin(Box::new(io::empty()));
+    let mut interpreter = Interpreter::new(&mut writer)
+        .with_capabilities(Capabilities::all())
+        .with_args(script_args)
+        .with_debugger(Debugger::new(console).with_stop_on_entry());
+    match interpreter.run(program) {
+        Ok(_) | Err(RuntimeError::Cancelled) => ExitCode::SUCCESS,
+        Err(RuntimeError::Exit(code)) => program_exit_code(code),
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
     }
+}
+
+/// A [DebugFrontend] which reads commands from standard input, and prints the state of the paused
+/// program to standard output.
+struct Console {
+    file_name: String,
+    lines: Vec<String>,
+}
+
+impl DebugFrontend for Console {
+    fn paused(&mut self, pause: &mut Pause) -> Resume {
+        let line = pause.span().line;
+        let name = pause
+            .stack()
+            .last()
+            .map_or("main", |frame| frame.name.as_str());
+        let location = format!("{}:{line} in {name}", self.file_name);
+        match pause.reason() {
+            PauseReason::Breakpoint => println!("breakpoint at {location}"),
+            PauseReason::Entry | PauseReason::Step => println!("{location}"),
+        }
+        let source = self.lines.get(line - 1).map_or("", |source| source.trim());
+        println!("{line}\t{source}");
+
+        loop {
+            print!("(linger) ");
+            let _ = io::stdout().flush();
+            let mut command = String::new();
+            match io::stdin().read_line(&mut command) {
+                Ok(0) | Err(_) => return Resume::Continue, // run to the end without a terminal
+                Ok(_) => (),
+            }
+            let (command, arg) = match command.trim().split_once(' ') {
+                Some((command, arg)) => (command, arg.trim()),
+                None => (command.trim(), ""),
+            };
 
-    return ExitCode::SUCCESS;
+            match command {
+                "continue" | "c" => return Resume::Continue,
+                "step" | "s" => return Resume::StepIn,
+                "next" | "n" => return Resume::StepOver,
+                "finish" | "f" => return Resume::StepOut,
+                "quit" | "q" => return Resume::Stop,
+                "break" | "b" => match arg.parse::<usize>() {
+                    Ok(line) => {
+                        pause.breakpoints().insert(line);
+                        println!("breakpoint set on line {line}");
+                    }
+                    Err(_) => println!("usage: break <LINE>"),
+                },
+                "delete" | "d" => match arg.parse::<usize>() {
+                    Ok(line) if pause.breakpoints().remove(&line) => {
+                        println!("breakpoint removed from line {line}")
+                    }
+                    Ok(line) => println!("no breakpoint on line {line}"),
+                    Err(_) => println!("usage: delete <LINE>"),
+                },
+                "locals" | "l" => {
+                    for (name, value) in pause.bindings() {
+                        println!("{name} = {}", value.to_quoted_string());
+                    }
+                }
+                "backtrace" | "bt" => {
+                    for (i, frame) in pause.stack().iter().rev().enumerate() {
+                        println!("#{i} {} at line {}", frame.name, frame.line);
+                    }
+                }
+                "print" | "p" => match pause.evaluate(arg) {
+                    Ok(value) => println!("{}", value.to_quoted_string()),
+                    Err(e) => println!("error: {e}"),
+                },
+                "help" | "h" => println!("{DEBUG_HELP}"),
+                "" => (),
+                command => {
+                    println!("unknown command \"{command}\", enter \"help\" for a list of commands")
+                }
+            }
+        }
+    }
 }
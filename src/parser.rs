@@ -1,26 +1,77 @@
-use crate::desugar::{desugar_statement, Procedure, Statement};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::desugar::{
+    desugar_expression, desugar_params, desugar_statement, Enum, Expr, Procedure, Statement,
+    Struct, Variant,
+};
 use crate::tokenizer::AssignOp;
 use crate::tokenizer::Operator;
+use crate::tokenizer::Trivia;
+use crate::typecheck::Type;
 use crate::{
-    error::ParseError::{self, *},
+    error::{
+        ParseError::{self, *},
+        RuntimeError,
+    },
     tokenizer::Token as T,
 };
 
-use self::procedures::parse_procs;
+use self::procedures::parse_items;
+use self::recovery::first_item_error;
 use self::utils::unexpected_token;
 
 mod expressions;
+mod patterns;
 mod procedures;
+mod recovery;
 mod statements;
+mod types;
 mod utils;
 
+/// The deepest that expressions, statements, patterns and types can be nested in a program, such
+/// as in parentheses, blocks, the arguments of calls and the operands of unary operators. A chain
+/// of binary operators such as `a + b + c` is not nested, however long it is. Deeper programs fail
+/// to parse with [NestedTooDeeply].
+pub const MAX_NESTING: usize = 128;
+
 /// A representation of a Linger program.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct Program {
+    /// The top-level struct declarations of the program.
+    pub structs: Vec<Struct>,
+    /// The top-level enum declarations of the program.
+    pub enums: Vec<Enum>,
     /// The top-level procedures of the program, excluding the main procedure.
     pub procedures: Vec<Procedure>,
     /// The body of the main procedure of the program.
     pub main: Statement,
+    /// The parameter of the main procedure, if it has one, which is bound to a list of the
+    /// command-line arguments of the program.
+    pub args_param: Option<String>,
+}
+
+/// Prints the program as Linger source, with the body of its main procedure last.
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let main = Procedure {
+            name: "main".to_string(),
+            params: (self.args_param.iter())
+                .map(|name| Param {
+                    name: name.to_string(),
+                    default: None,
+                    rest: false,
+                })
+                .collect(),
+            body: self.main.clone(),
+        };
+        let items: Vec<String> = (self.structs.iter().map(Struct::to_string))
+            .chain(self.enums.iter().map(Enum::to_string))
+            .chain(self.procedures.iter().chain([&main]).map(Procedure::to_string))
+            .collect();
+        write!(f, "{}", items.join("\n\n"))
+    }
 }
 
 /// A representation for a procedure in the Linger programming language.
@@ -29,25 +80,230 @@ pub struct Program {
 /// the user-facing syntax of the language. These statements are later
 /// ["desugared"](https://en.wikipedia.org/wiki/Syntactic_sugar) (converted) to
 /// a subset of the language which is then executed.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct SugaredProcedure {
+    /// The position of the `proc` keyword which begins the procedure.
+    pub span: Span,
+    /// The text of the doc comments directly before the procedure, with one line per comment.
+    pub doc: Option<String>,
+    pub name: String,
+    pub params: Vec<SugaredParam>,
+    /// The type annotation of each parameter, in the same order as `params`.
+    pub param_types: Vec<Option<Type>>,
+    /// The return type annotation after the parameters, if there is one.
+    pub return_type: Option<Type>,
+    pub body: SugaredStatement,
+}
+
+/// A representation of a struct declaration in the Linger programming language, such as
+/// `struct Point { x, y }`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SugaredStruct {
+    /// The position of the `struct` keyword which begins the declaration.
+    pub span: Span,
     pub name: String,
-    pub params: Vec<String>,
+    pub fields: Vec<String>,
+    /// The type annotation of each field, in the same order as `fields`.
+    pub field_types: Vec<Option<Type>>,
+}
+
+/// A representation of an enum declaration in the Linger programming language, such as
+/// `enum Shape { Circle(r), Rect(w, h) }`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SugaredEnum {
+    /// The position of the `enum` keyword which begins the declaration.
+    pub span: Span,
+    pub name: String,
+    pub variants: Vec<SugaredVariant>,
+}
+
+/// A single variant of an enum declaration, such as `Rect(w, h)`. A variant without fields is
+/// written without parentheses.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SugaredVariant {
+    pub name: String,
+    pub fields: Vec<String>,
+    /// The type annotation of each field, in the same order as `fields`.
+    pub field_types: Vec<Option<Type>>,
+}
+
+/// The top-level declarations of a program, without desugaring them.
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct Declarations {
+    pub structs: Vec<SugaredStruct>,
+    pub enums: Vec<SugaredEnum>,
+    pub procedures: Vec<SugaredProcedure>,
+}
+
+/// A pattern in an arm of a `match` expression.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum Pattern {
+    /// The `_` pattern, which matches any value without binding it.
+    Wildcard,
+    /// A name, which matches any value and binds it to the name. If a variant without fields is
+    /// declared with the same name, the pattern instead matches that variant.
+    Binding(String),
+    Nil,
+    Num(f64),
+    Bool(bool),
+    Str(String),
+    /// A list pattern such as `[a, b]` which matches lists of exactly the given length, or
+    /// `[head, ..rest]` which matches lists at least as long as its elements. The pattern after
+    /// `..` is matched against a list of the remaining elements, and is either a
+    /// [Binding](Pattern::Binding) or, when the name is omitted, a [Wildcard](Pattern::Wildcard).
+    List(Vec<Pattern>, Option<Box<Pattern>>),
+    /// A variant pattern such as `Rect(w, h)`, which matches the variant with the given name if
+    /// each of its fields matches the corresponding pattern.
+    Variant(String, Vec<Pattern>),
+}
+
+impl Pattern {
+    /// Returns the names bound by this pattern, in the order in which they appear. The names of
+    /// variants without fields are included, since they can only be told apart from bindings once
+    /// the declared enums are known.
+    pub fn bindings(&self) -> Vec<&str> {
+        match self {
+            Pattern::Binding(name) => vec![name.as_str()],
+            Pattern::List(elements, rest) => elements
+                .iter()
+                .chain(rest.as_deref())
+                .flat_map(Pattern::bindings)
+                .collect(),
+            Pattern::Variant(_, fields) => fields.iter().flat_map(Pattern::bindings).collect(),
+            Pattern::Wildcard
+            | Pattern::Nil
+            | Pattern::Num(_)
+            | Pattern::Bool(_)
+            | Pattern::Str(_) => vec![],
+        }
+    }
+}
+
+/// An arm of a `match` expression, such as `Circle(r) if (r > 0) -> r * r`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SugaredMatchArm {
+    pub pattern: Pattern,
+    /// The condition after `if`, which must also hold for the arm to be chosen.
+    pub guard: Option<SugaredExpr>,
     pub body: SugaredStatement,
 }
 
+/// A parameter of a procedure or lambda, such as `a`, `b = 2` or `...rest`. Like [Pattern], it is
+/// shared by the sugared and desugared syntax trees, with the expressions of each tree as default
+/// values.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Param<E> {
+    pub name: String,
+    /// The expression after `=`, which is evaluated each time a call leaves out the parameter.
+    pub default: Option<E>,
+    /// Whether the parameter is written `...name`, in which case it is bound to a list of the
+    /// positional arguments left over after the other parameters. Only the last parameter can be a
+    /// rest parameter.
+    pub rest: bool,
+}
+
+pub type SugaredParam = Param<SugaredExpr>;
+
+/// Where the value of a parameter comes from in a particular call, as decided by [match_args].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgSource {
+    /// The positional argument at the given index.
+    Positional(usize),
+    /// The named argument at the given index, counting only the named arguments.
+    Named(usize),
+    /// The default value of the parameter.
+    Default,
+    /// A list of the positional arguments from the index of the rest parameter onwards.
+    Rest,
+}
+
+/// Matches the arguments of a call to the procedure `proc_name` with its `params`, given the
+/// number of positional arguments and the names of the named arguments in the order in which they
+/// are passed. On success, returns where the value of each parameter comes from.
+///
+/// A call with more positional arguments than there are parameters, not counting a rest parameter,
+/// results in an [ArgMismatch](RuntimeError::ArgMismatch). So does a call which leaves out a
+/// parameter of a procedure whose parameters are all plain names, when it has no named arguments.
+/// Any other parameter without a default value which is left out results in a
+/// [MissingArg](RuntimeError::MissingArg).
+pub fn match_args<E>(
+    proc_name: &str,
+    params: &[Param<E>],
+    positional: usize,
+    named: &[&str],
+) -> Result<Vec<ArgSource>, RuntimeError> {
+    let fixed = params.iter().filter(|param| !param.rest).count();
+    let plain = fixed == params.len() && params.iter().all(|param| param.default.is_none());
+    if (positional > fixed && fixed == params.len())
+        || (plain && named.is_empty() && positional < fixed)
+    {
+        return Err(RuntimeError::ArgMismatch(
+            proc_name.to_string(),
+            fixed,
+            positional,
+        ));
+    }
+
+    for (i, name) in named.iter().enumerate() {
+        let index = params
+            .iter()
+            .position(|param| param.name == *name && !param.rest);
+        match index {
+            None => {
+                return Err(RuntimeError::UnknownParam(
+                    proc_name.to_string(),
+                    name.to_string(),
+                ))
+            }
+            Some(index) if index < positional || named[..i].contains(name) => {
+                return Err(RuntimeError::DuplicateArg(
+                    proc_name.to_string(),
+                    name.to_string(),
+                ))
+            }
+            Some(_) => (),
+        }
+    }
+
+    params
+        .iter()
+        .enumerate()
+        .map(|(index, param)| {
+            if param.rest {
+                return Ok(ArgSource::Rest);
+            }
+            if index < positional {
+                return Ok(ArgSource::Positional(index));
+            }
+            match named.iter().position(|name| *name == param.name) {
+                Some(i) => Ok(ArgSource::Named(i)),
+                None if param.default.is_some() => Ok(ArgSource::Default),
+                None => Err(RuntimeError::MissingArg(
+                    proc_name.to_string(),
+                    param.name.to_string(),
+                )),
+            }
+        })
+        .collect()
+}
+
 /// A representation of a statement in the Linger programming language.
 ///
 /// Structs beginning with the word "Sugared" mean that they are the part of
 /// the user-facing syntax of the language. These statements are later
 /// ["desugared"](https://en.wikipedia.org/wiki/Syntactic_sugar) (converted) to
 /// a subset of the language which is then executed.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum SugaredStatement {
     Expr(SugaredExpr),
-    Let(String, SugaredExpr),
-    Const(String, SugaredExpr),
+    /// A `let` statement along with the type annotation of its variable, if there is one.
+    Let(String, Option<Type>, SugaredExpr),
+    /// A `const` statement along with the type annotation of its variable, if there is one.
+    Const(String, Option<Type>, SugaredExpr),
     Assign(String, SugaredExpr),
+    /// An assignment to a field of the struct stored in a variable, such as `p.x = 1`. The fields
+    /// are listed from the outermost struct to the innermost.
+    FieldAssign(String, Vec<String>, SugaredExpr),
     OperatorAssignment(AssignOp, String, SugaredExpr),
     Block(Vec<SugaredStatement>),
     If(
@@ -66,6 +322,28 @@ pub enum SugaredStatement {
     Break,
     Continue,
     Return(Option<SugaredExpr>),
+    Trivia(Trivia),
+    /// A statement along with the position in the source at which it begins. Every statement in a
+    /// block is wrapped in a `Spanned` statement by the parser.
+    Spanned(Span, Box<SugaredStatement>),
+    /// A statement which could not be parsed. The parser skips to the next statement after an
+    /// error, so that every error in a program can be reported at once.
+    #[serde(skip)]
+    Error(ParseError),
+}
+
+/// A position in Linger source code. Lines and columns both start at 1, and columns count
+/// characters rather than bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
 }
 
 /// A representation of an expression in the Linger programming language.
@@ -74,7 +352,7 @@ pub enum SugaredStatement {
 /// the user-facing syntax of the language. These statements are later
 /// ["desugared"](https://en.wikipedia.org/wiki/Syntactic_sugar) (converted) to
 /// a subset of the language which is then executed.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum SugaredExpr {
     Nil,
     Num(f64),
@@ -85,46 +363,249 @@ pub enum SugaredExpr {
     Unary(Operator, Box<SugaredExpr>),
     PrimitiveCall(Builtin, Vec<SugaredExpr>),
     Call(Box<SugaredExpr>, Vec<SugaredExpr>),
-    Lambda(Vec<String>, Box<SugaredStatement>),
+    /// A lambda, along with the position of the parenthesis which begins it, which identifies the
+    /// lambda when it is profiled or traced.
+    Lambda(Span, Vec<SugaredParam>, Box<SugaredStatement>),
     Index(Box<SugaredExpr>, Box<SugaredExpr>),
+    /// A struct constructor, such as `Point { x: 1, y: 2 }`.
+    StructLiteral(String, Vec<(String, SugaredExpr)>),
+    /// An access of a field of a struct, such as `p.x`.
+    Field(Box<SugaredExpr>, String),
+    /// A call written with method syntax, such as `xs.map(f)`, which calls the builtin or
+    /// procedure with the given name with the value before the dot as its first argument, like
+    /// `map(xs, f)`.
+    MethodCall(Box<SugaredExpr>, String, Vec<SugaredExpr>),
+    /// A `match` expression, whose value is the value of the body of the first arm whose pattern
+    /// matches the value of the first expression.
+    Match(Box<SugaredExpr>, Vec<SugaredMatchArm>),
+    /// A named argument of a call, such as `b: 2` in `f(1, b: 2)`, which is passed to the
+    /// parameter with the given name. Named arguments only appear in the arguments of a call.
+    NamedArg(String, Box<SugaredExpr>),
+    /// Trivia found between the arguments of a call.
+    Trivia(Trivia),
+}
+
+/// A top-level item of a Linger source file. Items are only needed by tooling which must preserve
+/// the [Trivia] between procedures, such as the formatter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SugaredItem {
+    Proc(SugaredProcedure),
+    Struct(SugaredStruct),
+    Enum(SugaredEnum),
+    Trivia(Trivia),
 }
 
 /// A built in procedure in the Linger programming language.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize)]
 pub enum Builtin {
     Print,
+    Eprint,
     List,
     IsEmpty,
     IsNil,
     Head,
     Rest,
+    ReadLine,
+    ReadFile,
+    WriteFile,
+    AppendFile,
+    ListDir,
+    GetEnv,
+    Args,
+    Now,
+    MonotonicMs,
+    Exit,
+    Assert,
+    AssertEq,
+    AssertError,
 }
 
-/// Parses a program from a list of tokens.
-pub fn parse_program(tokens: &[T]) -> Result<Program, ParseError> {
-    let (procedures, rest) = parse_procs(tokens)?;
+impl Builtin {
+    /// Every builtin, in the order in which they are documented.
+    pub const ALL: [Builtin; 20] = [
+        Builtin::Print,
+        Builtin::Eprint,
+        Builtin::List,
+        Builtin::IsEmpty,
+        Builtin::IsNil,
+        Builtin::Head,
+        Builtin::Rest,
+        Builtin::ReadLine,
+        Builtin::ReadFile,
+        Builtin::WriteFile,
+        Builtin::AppendFile,
+        Builtin::ListDir,
+        Builtin::GetEnv,
+        Builtin::Args,
+        Builtin::Now,
+        Builtin::MonotonicMs,
+        Builtin::Exit,
+        Builtin::Assert,
+        Builtin::AssertEq,
+        Builtin::AssertError,
+    ];
+
+    /// Returns the builtin which is called `name`, if any.
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        Builtin::ALL
+            .into_iter()
+            .find(|builtin| builtin.to_string() == name)
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Builtin::Print => write!(f, "print"),
+            Builtin::Eprint => write!(f, "eprint"),
+            Builtin::List => write!(f, "list"),
+            Builtin::IsEmpty => write!(f, "is_empty"),
+            Builtin::IsNil => write!(f, "is_nil"),
+            Builtin::Head => write!(f, "head"),
+            Builtin::Rest => write!(f, "rest"),
+            Builtin::ReadLine => write!(f, "read_line"),
+            Builtin::ReadFile => write!(f, "read_file"),
+            Builtin::WriteFile => write!(f, "write_file"),
+            Builtin::AppendFile => write!(f, "append_file"),
+            Builtin::ListDir => write!(f, "list_dir"),
+            Builtin::GetEnv => write!(f, "getenv"),
+            Builtin::Args => write!(f, "args"),
+            Builtin::Now => write!(f, "now"),
+            Builtin::MonotonicMs => write!(f, "monotonic_ms"),
+            Builtin::Exit => write!(f, "exit"),
+            Builtin::Assert => write!(f, "assert"),
+            Builtin::AssertEq => write!(f, "assert_eq"),
+            Builtin::AssertError => write!(f, "assert_error"),
+        }
+    }
+}
+
+/// Parses the top-level items of a source file from a list of tokens which may contain
+/// [Trivia]. Unlike [parse_program], this function does not require a main procedure.
+pub fn parse_source_file(tokens: &[T]) -> Result<Vec<SugaredItem>, ParseError> {
+    let (items, rest) = parse_items(tokens)?;
+
+    if let Some(e) = first_item_error(&items) {
+        return Err(e);
+    }
 
     if !rest.is_empty() {
         return Err(unexpected_token(rest)); // extra tokens
     }
 
-    let desugared_procs = procedures.iter().map(|proc| Procedure {
+    Ok(items)
+}
+
+/// Parses the top-level procedures of a program from a list of tokens, without desugaring them.
+/// Unlike [parse_program], this function does not require a main procedure.
+pub fn parse_procedures(tokens: &[T]) -> Result<Vec<SugaredProcedure>, ParseError> {
+    parse_declarations(tokens).map(|declarations| declarations.procedures)
+}
+
+/// Parses the top-level procedures of a program from a list of tokens, recovering from errors.
+/// Each statement which could not be parsed is replaced by an [Error](SugaredStatement::Error)
+/// statement, and every error is returned in the order in which they appear in the source.
+pub fn parse_procedures_recovering(tokens: &[T]) -> (Vec<SugaredProcedure>, Vec<ParseError>) {
+    let (declarations, errors) = parse_declarations_recovering(tokens);
+    (declarations.procedures, errors)
+}
+
+/// Parses the top-level struct declarations, enum declarations and procedures of a program from a
+/// list of tokens, without desugaring them. Unlike [parse_program], this function does not require
+/// a main procedure.
+pub fn parse_declarations(tokens: &[T]) -> Result<Declarations, ParseError> {
+    match parse_declarations_recovering(tokens) {
+        (_, errors) if !errors.is_empty() => Err(errors[0].clone()),
+        (declarations, _) => Ok(declarations),
+    }
+}
+
+/// Parses the top-level declarations of a program from a list of tokens, recovering from errors in
+/// the same way as [parse_procedures_recovering].
+pub fn parse_declarations_recovering(tokens: &[T]) -> (Declarations, Vec<ParseError>) {
+    procedures::parse_declarations(tokens)
+}
+
+/// Parses a program from a list of tokens, returning the first error if the program cannot be
+/// parsed.
+pub fn parse_program(tokens: &[T]) -> Result<Program, ParseError> {
+    parse_program_recovering(tokens).map_err(|errors| errors[0].clone())
+}
+
+/// Parses a program from a list of tokens, returning every error if the program cannot be parsed.
+pub fn parse_program_recovering(tokens: &[T]) -> Result<Program, Vec<ParseError>> {
+    parse_program_with_entry(tokens, "main")
+}
+
+/// Parses a program from a list of tokens in the same way as [parse_program_recovering], except
+/// that the procedure called `entry` is run in place of the main procedure, such as a test
+/// procedure. The program fails to parse with [NoMain] if there is no such procedure, and with
+/// [TooManyMainParams] if it has more than one parameter.
+pub fn parse_program_with_entry(tokens: &[T], entry: &str) -> Result<Program, Vec<ParseError>> {
+    let (declarations, errors) = parse_declarations_recovering(tokens);
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let desugared_procs = declarations.procedures.iter().map(|proc| Procedure {
         name: proc.name.to_string(),
-        params: proc.params.clone(),
+        params: desugar_params(proc.params.clone()),
         body: desugar_statement(proc.body.clone()),
     });
 
     let (main_procs, procs): (Vec<Procedure>, Vec<Procedure>) = desugared_procs
         .into_iter()
-        .partition(|proc| proc.name == "main");
+        .partition(|proc| proc.name == entry);
 
     let main_proc = match main_procs.first() {
         Some(proc) => proc,
-        None => return Err(NoMain),
+        None => return Err(vec![NoMain]),
+    };
+    let args_param = match main_proc.params.as_slice() {
+        [] => None,
+        [param] => Some(param.name.to_string()),
+        _ => return Err(vec![TooManyMainParams(entry.to_string())]),
     };
 
     return Ok(Program {
+        structs: declarations
+            .structs
+            .into_iter()
+            .map(|strukt| Struct {
+                name: strukt.name,
+                fields: strukt.fields,
+            })
+            .collect(),
+        enums: declarations
+            .enums
+            .into_iter()
+            .map(|enumeration| Enum {
+                name: enumeration.name,
+                variants: enumeration
+                    .variants
+                    .into_iter()
+                    .map(|variant| Variant {
+                        name: variant.name,
+                        fields: variant.fields,
+                    })
+                    .collect(),
+            })
+            .collect(),
         procedures: procs,
         main: main_proc.body.clone(),
+        args_param,
     });
 }
+
+/// Parses a single expression from a list of tokens and desugars it, such as an expression which
+/// is evaluated by a debugger while a program is paused.
+pub fn parse_expression(tokens: &[T]) -> Result<Expr, ParseError> {
+    let (expr, rest) = expressions::parse_expr(tokens)?;
+
+    if !rest.is_empty() {
+        return Err(unexpected_token(rest)); // extra tokens
+    }
+
+    Ok(desugar_expression(expr))
+}
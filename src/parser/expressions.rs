@@ -4,14 +4,17 @@ use crate::{
     tokenizer::{Keyword::*, Token as T, TokenValue::*},
 };
 
+use super::patterns::parse_match;
 use super::procedures::parse_params;
 use super::statements::parse_statement;
 use super::utils::{
-    check_builtin, consume_token, match_operator, parse_binary_expr, unexpected_token,
+    check_args, check_builtin, consume_token, match_operator, nest, parse_binary_expr,
+    skip_trivia, unexpected_token,
 };
-use super::SugaredExpr;
+use super::{Builtin, Span, SugaredExpr};
 
 pub fn parse_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), ParseError> {
+    let _nested = nest(tokens)?;
     parse_logical_or_expr(tokens)
 }
 
@@ -41,8 +44,9 @@ pub fn parse_multiplicative_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), Pa
 
 pub fn parse_unary_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), ParseError> {
     match match_operator(vec![Minus, LogicNot].as_slice(), tokens) {
-        Some((operator, tokens)) => {
-            let (right, tokens) = parse_unary_expr(tokens)?;
+        Some((operator, rest)) => {
+            let _nested = nest(tokens)?;
+            let (right, tokens) = parse_unary_expr(rest)?;
             return Ok((SugaredExpr::Unary(operator, Box::new(right)), tokens));
         }
         None => {
@@ -80,7 +84,9 @@ pub fn parse_call_or_index_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), Par
         (expr, tokens) = match tokens {
             [T(LPAREN, ..), rest @ ..] => {
                 let (args, rest) = parse_args(rest)?;
-                let call_expr = match check_builtin(&expr) {
+                let builtin = check_builtin(&expr);
+                check_args(&args, builtin)?;
+                let call_expr = match builtin {
                     Some(builtin) => SugaredExpr::PrimitiveCall(builtin, args),
                     None => SugaredExpr::Call(Box::new(expr), args),
                 };
@@ -92,6 +98,15 @@ pub fn parse_call_or_index_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), Par
                 let index_expr = SugaredExpr::Index(Box::new(expr), Box::new(index_value_expr));
                 (index_expr, rest)
             }
+            [T(DOT, ..), T(ID(name), ..), T(LPAREN, ..), rest @ ..] => {
+                let (args, rest) = parse_args(rest)?;
+                check_args(&args, Builtin::from_name(name))?;
+                let method_call = SugaredExpr::MethodCall(Box::new(expr), name.to_string(), args);
+                (method_call, rest)
+            }
+            [T(DOT, ..), T(ID(field), ..), rest @ ..] => {
+                (SugaredExpr::Field(Box::new(expr), field.to_string()), rest)
+            }
             _ => break,
         }
     }
@@ -104,9 +119,28 @@ pub fn parse_terminal_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), ParseErr
         [T(KW(True), ..), tokens @ ..] => Ok((SugaredExpr::Bool(true), tokens)),
         [T(KW(False), ..), tokens @ ..] => Ok((SugaredExpr::Bool(false), tokens)),
         [T(KW(Nil), ..), tokens @ ..] => Ok((SugaredExpr::Nil, tokens)),
+        [T(KW(Match), ..), ..] => parse_match(tokens),
         [T(KW(kw), ..), ..] => Err(KeywordAsVar(kw.to_string())),
+        [T(ID(name), ..), T(L_CURLY_BRACKET, ..), tokens @ ..]
+            if matches!(
+                skip_trivia(tokens),
+                [T(R_CURLY_BRACKET, ..), ..] | [T(ID(_), ..), T(COLON, ..), ..]
+            ) =>
+        {
+            let (fields, tokens) = parse_field_values(tokens)?;
+            Ok((SugaredExpr::StructLiteral(name.to_string(), fields), tokens))
+        }
         [T(ID(id), ..), tokens @ ..] => Ok((SugaredExpr::Var(id.to_string()), tokens)),
-        [T(LPAREN, ..), tokens @ ..] => match parse_params(tokens) {
+        // a literal or a `match` expression cannot be a parameter, so this must be a
+        // parenthesized expression
+        [T(LPAREN, ..), tokens @ ..]
+            if matches!(tokens, [T(KW(True | False | Nil | Match), ..), ..]) =>
+        {
+            let (expr, tokens) = parse_expr(tokens)?;
+            let tokens = consume_token(RPAREN, tokens)?;
+            return Ok((expr, tokens));
+        }
+        [T(LPAREN, line, col, ..), tokens @ ..] => match parse_params(tokens) {
             // if the next sequence of tokens is a params list, then parse a lambda expression
             Ok((params, tokens)) => {
                 let tokens = consume_token(THIN_ARROW, tokens)?;
@@ -114,7 +148,12 @@ pub fn parse_terminal_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), ParseErr
                     (Some(statement), tokens) => (statement, tokens),
                     _ => return Err(ExpectedStatement),
                 };
-                return Ok((SugaredExpr::Lambda(params, Box::new(lambda_body)), tokens));
+                let span = Span {
+                    line: *line,
+                    col: *col,
+                };
+                let lambda = SugaredExpr::Lambda(span, params, Box::new(lambda_body));
+                return Ok((lambda, tokens));
             }
             // if the next sequence of tokens is a valid sequence of tokens, but not a params list,
             // then parse a parenthesized expression
@@ -132,25 +171,79 @@ pub fn parse_terminal_expr(tokens: &[T]) -> Result<(SugaredExpr, &[T]), ParseErr
     }
 }
 
+/// Parses the arguments of a call up to and including its closing parenthesis.
 pub fn parse_args(tokens: &[T]) -> Result<(Vec<SugaredExpr>, &[T]), ParseError> {
+    let mut args = vec![];
+    let mut tokens = tokens;
+    // whether the next token may begin an argument, rather than only end the last one
+    let mut expecting_arg = true;
+    loop {
+        tokens = match tokens {
+            [T(RPAREN, ..), tokens @ ..] => return Ok((args, tokens)),
+            // trivia which the last argument continues after is within that argument
+            [T(TRIVIA(trivia), ..), tokens @ ..]
+                if expecting_arg
+                    || matches!(skip_trivia(tokens), [T(COMMA | RPAREN, ..), ..]) =>
+            {
+                args.push(SugaredExpr::Trivia(trivia.clone()));
+                tokens
+            }
+            tokens if expecting_arg => {
+                let (arg, tokens) = parse_arg(tokens)?;
+                args.push(arg);
+                expecting_arg = false;
+                tokens
+            }
+            [T(COMMA, ..), T(RPAREN, ..), ..] => return Err(unexpected_token(tokens)),
+            [T(COMMA, ..), tokens @ ..] => {
+                expecting_arg = true;
+                tokens
+            }
+            tokens => return Err(unexpected_token(tokens)),
+        }
+    }
+}
+
+/// Parses a single argument of a call, which is either an expression or a named argument such as
+/// `b: 2`.
+fn parse_arg(tokens: &[T]) -> Result<(SugaredExpr, &[T]), ParseError> {
     match tokens {
-        [T(RPAREN, ..), tokens @ ..] => Ok((vec![], tokens)),
-        tokens => {
+        [T(ID(name), ..), T(COLON, ..), tokens @ ..] => {
             let (expr, tokens) = parse_expr(tokens)?;
-            let (mut rest_args, tokens) = parse_rest_args(tokens)?;
-
-            let mut vec = vec![expr];
-            vec.append(&mut rest_args);
-            return Ok((vec, tokens));
+            Ok((
+                SugaredExpr::NamedArg(name.to_string(), Box::new(expr)),
+                tokens,
+            ))
         }
+        tokens => parse_expr(tokens),
     }
 }
 
-pub fn parse_rest_args(tokens: &[T]) -> Result<(Vec<SugaredExpr>, &[T]), ParseError> {
-    match tokens {
-        [T(RPAREN, ..), tokens @ ..] => Ok((vec![], tokens)),
-        [T(COMMA, ..), T(RPAREN, ..), ..] => Err(unexpected_token(tokens)),
-        [T(COMMA, ..), tokens @ ..] => parse_args(tokens),
-        tokens => Err(unexpected_token(tokens)),
+/// The name of a field in a struct constructor along with the expression which initializes it.
+type FieldValue = (String, SugaredExpr);
+
+/// Parses the fields of a struct constructor up to and including its closing curly bracket.
+pub fn parse_field_values(tokens: &[T]) -> Result<(Vec<FieldValue>, &[T]), ParseError> {
+    if let [T(R_CURLY_BRACKET, ..), tokens @ ..] = tokens {
+        return Ok((vec![], tokens));
+    }
+    let mut fields = vec![];
+    let mut tokens = tokens;
+    loop {
+        let rest = match tokens {
+            [T(KW(kw), ..), ..] => return Err(KeywordAsField(kw.to_string())),
+            [T(ID(field), ..), T(COLON, ..), rest @ ..] => {
+                let (value, rest) = parse_expr(rest)?;
+                fields.push((field.to_string(), value));
+                rest
+            }
+            tokens => return Err(unexpected_token(tokens)),
+        };
+        match rest {
+            [T(COMMA, ..), T(R_CURLY_BRACKET, ..), ..] => return Err(unexpected_token(rest)),
+            [T(COMMA, ..), rest @ ..] => tokens = rest,
+            [T(R_CURLY_BRACKET, ..), rest @ ..] => return Ok((fields, rest)),
+            rest => return Err(unexpected_token(rest)),
+        }
     }
 }
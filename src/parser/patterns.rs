@@ -0,0 +1,143 @@
+use crate::tokenizer::Operator::*;
+use crate::{
+    error::ParseError::{self, *},
+    tokenizer::{Keyword::*, Token as T, TokenValue::*},
+};
+
+use super::expressions::parse_expr;
+use super::statements::parse_statement;
+use super::utils::{consume_token, nest, unexpected_token};
+use super::{Pattern, SugaredExpr, SugaredMatchArm};
+
+/// Parses a `match` expression such as `match (shape) { Circle(r) -> r * r, _ -> 0 }`, starting
+/// at the `match` keyword. The body of an arm is parsed like the body of a lambda, and arms are
+/// separated by commas, which may be left out after a body ending in `;` or `}`.
+pub fn parse_match(tokens: &[T]) -> Result<(SugaredExpr, &[T]), ParseError> {
+    let tokens = consume_token(KW(Match), tokens)?;
+    let tokens = consume_token(LPAREN, tokens)?;
+    let (scrutinee, tokens) = parse_expr(tokens)?;
+    let tokens = consume_token(RPAREN, tokens)?;
+    let mut tokens = consume_token(L_CURLY_BRACKET, tokens)?;
+
+    let mut arms = vec![];
+    if let [T(R_CURLY_BRACKET, ..), rest @ ..] = tokens {
+        return Ok((SugaredExpr::Match(Box::new(scrutinee), arms), rest));
+    }
+    loop {
+        let (pattern, rest) = parse_pattern(tokens)?;
+        let (guard, rest) = match rest {
+            [T(KW(If), ..), T(LPAREN, ..), rest @ ..] => {
+                let (guard, rest) = parse_expr(rest)?;
+                let rest = consume_token(RPAREN, rest)?;
+                (Some(guard), rest)
+            }
+            rest => (None, rest),
+        };
+        let body_tokens = consume_token(THIN_ARROW, rest)?;
+        let (body, rest) = match parse_statement(body_tokens, false)? {
+            (Some(statement), rest) => (statement, rest),
+            _ => return Err(ExpectedStatement),
+        };
+        let delimited = matches!(
+            body_tokens[..body_tokens.len() - rest.len()].last(),
+            Some(T(SEMICOLON | R_CURLY_BRACKET, ..))
+        );
+        arms.push(SugaredMatchArm {
+            pattern,
+            guard,
+            body,
+        });
+
+        match rest {
+            [T(COMMA, ..), T(R_CURLY_BRACKET, ..), ..] => return Err(unexpected_token(rest)),
+            [T(COMMA, ..), rest @ ..] => tokens = rest,
+            [T(R_CURLY_BRACKET, ..), rest @ ..] => {
+                return Ok((SugaredExpr::Match(Box::new(scrutinee), arms), rest))
+            }
+            rest if delimited => tokens = rest,
+            rest => return Err(unexpected_token(rest)),
+        }
+    }
+}
+
+pub fn parse_pattern(tokens: &[T]) -> Result<(Pattern, &[T]), ParseError> {
+    let _nested = nest(tokens)?;
+    match tokens {
+        [T(KW(Nil), ..), tokens @ ..] => Ok((Pattern::Nil, tokens)),
+        [T(KW(True), ..), tokens @ ..] => Ok((Pattern::Bool(true), tokens)),
+        [T(KW(False), ..), tokens @ ..] => Ok((Pattern::Bool(false), tokens)),
+        [T(KW(kw), ..), ..] => Err(KeywordAsVar(kw.to_string())),
+        [T(NUM(n), ..), tokens @ ..] => Ok((Pattern::Num(*n), tokens)),
+        [T(OP(Minus), ..), T(NUM(n), ..), tokens @ ..] => Ok((Pattern::Num(-n), tokens)),
+        [T(STR(s), ..), tokens @ ..] => Ok((Pattern::Str(s.to_string()), tokens)),
+        [T(ID(name), ..), T(LPAREN, ..), tokens @ ..] => {
+            let (fields, tokens) = parse_variant_patterns(tokens)?;
+            Ok((Pattern::Variant(name.to_string(), fields), tokens))
+        }
+        [T(ID(name), ..), tokens @ ..] => Ok((binding_or_wildcard(name), tokens)),
+        [T(L_SQUARE_BRACKET, ..), tokens @ ..] => parse_list_pattern(tokens),
+        tokens => Err(unexpected_token(tokens)),
+    }
+}
+
+fn binding_or_wildcard(name: &str) -> Pattern {
+    match name {
+        "_" => Pattern::Wildcard,
+        name => Pattern::Binding(name.to_string()),
+    }
+}
+
+/// Parses the patterns of the fields of a variant pattern up to and including the closing
+/// parenthesis.
+fn parse_variant_patterns(tokens: &[T]) -> Result<(Vec<Pattern>, &[T]), ParseError> {
+    if let [T(RPAREN, ..), tokens @ ..] = tokens {
+        return Ok((vec![], tokens));
+    }
+    let mut patterns = vec![];
+    let mut tokens = tokens;
+    loop {
+        let (pattern, rest) = parse_pattern(tokens)?;
+        patterns.push(pattern);
+        match rest {
+            [T(COMMA, ..), T(RPAREN, ..), ..] => return Err(unexpected_token(rest)),
+            [T(COMMA, ..), rest @ ..] => tokens = rest,
+            [T(RPAREN, ..), rest @ ..] => return Ok((patterns, rest)),
+            rest => return Err(unexpected_token(rest)),
+        }
+    }
+}
+
+/// Parses a list pattern up to and including its closing square bracket. Only the last element
+/// may be a `..rest` pattern.
+fn parse_list_pattern(tokens: &[T]) -> Result<(Pattern, &[T]), ParseError> {
+    if let [T(R_SQUARE_BRACKET, ..), tokens @ ..] = tokens {
+        return Ok((Pattern::List(vec![], None), tokens));
+    }
+    let mut elements = vec![];
+    let mut tokens = tokens;
+    loop {
+        let rest_pattern = match tokens {
+            [T(DOUBLE_DOT, ..), T(KW(kw), ..), ..] => return Err(KeywordAsVar(kw.to_string())),
+            [T(DOUBLE_DOT, ..), T(ID(name), ..), rest @ ..] => {
+                Some((binding_or_wildcard(name), rest))
+            }
+            [T(DOUBLE_DOT, ..), rest @ ..] => Some((Pattern::Wildcard, rest)),
+            _ => None,
+        };
+        if let Some((rest_pattern, rest)) = rest_pattern {
+            let rest = consume_token(R_SQUARE_BRACKET, rest)?;
+            return Ok((Pattern::List(elements, Some(Box::new(rest_pattern))), rest));
+        }
+
+        let (element, rest) = parse_pattern(tokens)?;
+        elements.push(element);
+        match rest {
+            [T(COMMA, ..), T(R_SQUARE_BRACKET, ..), ..] => return Err(unexpected_token(rest)),
+            [T(COMMA, ..), rest @ ..] => tokens = rest,
+            [T(R_SQUARE_BRACKET, ..), rest @ ..] => {
+                return Ok((Pattern::List(elements, None), rest))
+            }
+            rest => return Err(unexpected_token(rest)),
+        }
+    }
+}
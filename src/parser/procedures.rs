@@ -1,49 +1,308 @@
 use crate::{
     error::ParseError::{self, *},
-    tokenizer::{Keyword::*, Token as T, TokenValue::*},
+    tokenizer::{Keyword::*, Token as T, TokenValue::*, Trivia},
+    typecheck::Type,
 };
 
-use super::utils::{ensure_block, unexpected_token};
+use super::expressions::parse_expr;
+use super::recovery::{collect_proc_errors, synchronize_proc};
 use super::statements::parse_statement;
-use super::SugaredProcedure;
-
-pub fn parse_procs(tokens: &[T]) -> Result<(Vec<SugaredProcedure>, &[T]), ParseError> {
-    let (proc_option, tokens) = parse_proc(tokens)?;
-
-    match proc_option {
-        Some(proc) => {
-            let (mut rest_procs, tokens) = parse_procs(tokens)?;
-
-            if rest_procs
-                .clone()
-                .iter()
-                .find(|p| p.name == proc.name)
-                .is_some()
-            {
-                return Err(MultipleSameNamedProcs(proc.name.to_string()));
+use super::types::{
+    check_type_names, collect_proc_annotations, parse_annotation, parse_return_type,
+};
+use super::utils::{ensure_block, parse_with_comments, unexpected_token};
+use super::{
+    Declarations, Param, Span, SugaredEnum, SugaredItem, SugaredParam, SugaredProcedure,
+    SugaredStruct, SugaredVariant,
+};
+
+pub fn parse_items(tokens: &[T]) -> Result<(Vec<SugaredItem>, &[T]), ParseError> {
+    let mut items = vec![];
+    let mut tokens = tokens;
+    loop {
+        match tokens {
+            [T(TRIVIA(trivia), ..), rest @ ..] if !matches!(trivia, Trivia::DocComment(_)) => {
+                items.push(SugaredItem::Trivia(trivia.clone()));
+                tokens = rest;
+            }
+            [T(KW(Struct), ..), ..] => {
+                let (strukt, comments, rest) = parse_with_comments(tokens, parse_struct)?;
+                push_item(&mut items, SugaredItem::Struct(strukt), comments);
+                tokens = rest;
+            }
+            [T(KW(Enum), ..), ..] => {
+                let (enumeration, comments, rest) = parse_with_comments(tokens, parse_enum)?;
+                push_item(&mut items, SugaredItem::Enum(enumeration), comments);
+                tokens = rest;
+            }
+            _ => match parse_with_comments(tokens, parse_proc)? {
+                (Some(proc), comments, rest) => {
+                    push_item(&mut items, SugaredItem::Proc(proc), comments);
+                    tokens = rest;
+                }
+                // a doc comment which does not document a procedure
+                (None, _, [T(TRIVIA(trivia), ..), rest @ ..]) => {
+                    items.push(SugaredItem::Trivia(trivia.clone()));
+                    tokens = rest;
+                }
+                (None, _, rest) => return Ok((items, rest)),
+            },
+        }
+    }
+}
+
+/// Pushes `item`, preceded by the comments taken out of it, except for trailing comments which
+/// follow it instead.
+fn push_item(items: &mut Vec<SugaredItem>, item: SugaredItem, comments: Vec<Trivia>) {
+    let (trailing, leading): (Vec<_>, Vec<_>) = comments
+        .into_iter()
+        .partition(|comment| matches!(comment, Trivia::TrailingComment(_)));
+    items.extend(leading.into_iter().map(SugaredItem::Trivia));
+    items.push(item);
+    items.extend(trailing.into_iter().map(SugaredItem::Trivia));
+}
+
+/// Parses every top-level declaration in `tokens`, returning the ones which could be parsed along
+/// with every error in the order in which they appear in the source. After an error in the header
+/// of a procedure or in a struct or enum declaration, the parser skips to the next declaration.
+pub fn parse_declarations(tokens: &[T]) -> (Declarations, Vec<ParseError>) {
+    let mut structs = vec![];
+    let mut enums = vec![];
+    let mut procs = vec![];
+    let mut errors = vec![];
+    let mut tokens = tokens;
+    while !tokens.is_empty() {
+        let result = match tokens {
+            [T(KW(Struct), ..), ..] => parse_struct(tokens).map(|(strukt, rest)| {
+                structs.push(strukt);
+                rest
+            }),
+            [T(KW(Enum), ..), ..] => parse_enum(tokens).map(|(enumeration, rest)| {
+                enums.push(enumeration);
+                rest
+            }),
+            _ => match parse_proc(tokens) {
+                Ok((Some(proc), rest)) => {
+                    collect_proc_errors(&proc, &mut errors);
+                    procs.push(proc);
+                    Ok(rest)
+                }
+                Ok((None, _)) => Err(unexpected_token(tokens)), // extra tokens
+                Err(e) => Err(e),
+            },
+        };
+        tokens = match result {
+            Ok(rest) => rest,
+            Err(e) => {
+                errors.push(e);
+                synchronize_proc(tokens)
             }
+        };
+    }
+
+    for name in duplicate_names(procs.iter().map(|p| p.name.as_str())) {
+        errors.push(MultipleSameNamedProcs(name.to_string()));
+    }
+    for name in duplicate_names(structs.iter().map(|s| s.name.as_str())) {
+        errors.push(MultipleSameNamedStructs(name.to_string()));
+    }
+    for name in duplicate_names(enums.iter().map(|e| e.name.as_str())) {
+        errors.push(MultipleSameNamedEnums(name.to_string()));
+    }
+    let variants = enums.iter().flat_map(|e| e.variants.iter());
+    for name in duplicate_names(variants.map(|v| v.name.as_str())) {
+        errors.push(MultipleSameNamedVariants(name.to_string()));
+    }
 
-            let mut vec = vec![proc];
-            vec.append(&mut rest_procs);
-            return Ok((vec, tokens));
+    let type_names: Vec<&str> = (structs.iter().map(|s| s.name.as_str()))
+        .chain(enums.iter().map(|e| e.name.as_str()))
+        .collect();
+    let field_types = (structs.iter().flat_map(|s| s.field_types.iter())).chain(
+        enums
+            .iter()
+            .flat_map(|e| e.variants.iter())
+            .flat_map(|v| v.field_types.iter()),
+    );
+    for ty in field_types.flatten() {
+        check_type_names(ty, &type_names, &mut errors);
+    }
+    for proc in &procs {
+        for ty in collect_proc_annotations(proc) {
+            check_type_names(ty, &type_names, &mut errors);
         }
-        None => Ok((vec![], tokens)),
     }
+
+    // an unterminated block is reported by every block which encloses it
+    errors.dedup();
+    let declarations = Declarations {
+        structs,
+        enums,
+        procedures: procs,
+    };
+    (declarations, errors)
 }
 
-pub fn parse_proc(tokens: &[T]) -> Result<(Option<SugaredProcedure>, &[T]), ParseError> {
+/// Returns the names which appear more than once in `names`, in the order in which they first
+/// appear.
+fn duplicate_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let names: Vec<&str> = names.collect();
+    let mut duplicates = vec![];
+    for (index, name) in names.iter().enumerate() {
+        if names[index + 1..].contains(name) && !duplicates.contains(name) {
+            duplicates.push(*name);
+        }
+    }
+    duplicates
+}
+
+/// Parses a struct declaration such as `struct Point { x: num, y: num }`.
+pub fn parse_struct(tokens: &[T]) -> Result<(SugaredStruct, &[T]), ParseError> {
+    match tokens {
+        [T(KW(Struct), ..), T(KW(kw), ..), ..] => Err(KeywordAsStruct(kw.to_string())),
+        [T(KW(Struct), line, col, ..), T(ID(name), ..), T(L_CURLY_BRACKET, ..), rest @ ..] => {
+            let mut fields = vec![];
+            let mut field_types = vec![];
+            let mut tokens = rest;
+            if let [T(R_CURLY_BRACKET, ..), rest @ ..] = tokens {
+                tokens = rest;
+            } else {
+                loop {
+                    let rest = match tokens {
+                        [T(KW(kw), ..), ..] => return Err(KeywordAsField(kw.to_string())),
+                        [T(ID(field), ..), rest @ ..] => {
+                            let (field_type, rest) = parse_annotation(rest)?;
+                            fields.push(field.to_string());
+                            field_types.push(field_type);
+                            rest
+                        }
+                        tokens => return Err(unexpected_token(tokens)),
+                    };
+                    match rest {
+                        [T(COMMA, ..), T(R_CURLY_BRACKET, ..), ..] => {
+                            return Err(unexpected_token(rest))
+                        }
+                        [T(COMMA, ..), rest @ ..] => tokens = rest,
+                        [T(R_CURLY_BRACKET, ..), rest @ ..] => {
+                            tokens = rest;
+                            break;
+                        }
+                        rest => return Err(unexpected_token(rest)),
+                    }
+                }
+            }
+            Ok((
+                SugaredStruct {
+                    span: Span {
+                        line: *line,
+                        col: *col,
+                    },
+                    name: name.to_string(),
+                    fields,
+                    field_types,
+                },
+                tokens,
+            ))
+        }
+        tokens => Err(unexpected_token(tokens)),
+    }
+}
+
+/// Parses an enum declaration such as `enum Shape { Circle(r: num), Rect(w, h), Empty }`.
+pub fn parse_enum(tokens: &[T]) -> Result<(SugaredEnum, &[T]), ParseError> {
     match tokens {
+        [T(KW(Enum), ..), T(KW(kw), ..), ..] => Err(KeywordAsEnum(kw.to_string())),
+        [T(KW(Enum), line, col, ..), T(ID(name), ..), T(L_CURLY_BRACKET, ..), rest @ ..] => {
+            let mut variants = vec![];
+            let mut tokens = rest;
+            if let [T(R_CURLY_BRACKET, ..), rest @ ..] = tokens {
+                tokens = rest;
+            } else {
+                loop {
+                    let (variant, rest) = parse_variant(tokens)?;
+                    variants.push(variant);
+                    match rest {
+                        [T(COMMA, ..), T(R_CURLY_BRACKET, ..), ..] => {
+                            return Err(unexpected_token(rest))
+                        }
+                        [T(COMMA, ..), rest @ ..] => tokens = rest,
+                        [T(R_CURLY_BRACKET, ..), rest @ ..] => {
+                            tokens = rest;
+                            break;
+                        }
+                        rest => return Err(unexpected_token(rest)),
+                    }
+                }
+            }
+            Ok((
+                SugaredEnum {
+                    span: Span {
+                        line: *line,
+                        col: *col,
+                    },
+                    name: name.to_string(),
+                    variants,
+                },
+                tokens,
+            ))
+        }
+        tokens => Err(unexpected_token(tokens)),
+    }
+}
+
+/// Parses a single variant of an enum declaration, whose fields are written like the parameters
+/// of a procedure.
+fn parse_variant(tokens: &[T]) -> Result<(SugaredVariant, &[T]), ParseError> {
+    match tokens {
+        [T(KW(kw), ..), ..] => Err(KeywordAsVariant(kw.to_string())),
+        [T(ID(name), ..), T(LPAREN, ..), rest @ ..] => {
+            let (typed_fields, rest) = parse_typed_params(rest).map_err(|e| match e {
+                KeywordAsParam(kw) => KeywordAsField(kw),
+                e => e,
+            })?;
+            let (fields, field_types) = typed_fields.into_iter().unzip();
+            let variant = SugaredVariant {
+                name: name.to_string(),
+                fields,
+                field_types,
+            };
+            Ok((variant, rest))
+        }
+        [T(ID(name), ..), rest @ ..] => {
+            let variant = SugaredVariant {
+                name: name.to_string(),
+                fields: vec![],
+                field_types: vec![],
+            };
+            Ok((variant, rest))
+        }
+        tokens => Err(unexpected_token(tokens)),
+    }
+}
+
+/// Parses a procedure along with the doc comments before it.
+pub fn parse_proc(tokens: &[T]) -> Result<(Option<SugaredProcedure>, &[T]), ParseError> {
+    let (doc, rest) = parse_doc_comments(tokens);
+    match rest {
         [T(KW(Proc), ..), T(KW(kw), ..), T(LPAREN, ..), ..] => Err(KeywordAsProc(kw.to_string())),
-        [T(KW(Proc), ..), T(ID(name), ..), T(LPAREN, ..), rest @ ..] => {
-            let (params, tokens) = parse_params(rest)?;
+        [T(KW(Proc), line, col, ..), T(ID(name), ..), T(LPAREN, ..), rest @ ..] => {
+            let (annotated_params, tokens) = parse_param_list(rest, true)?;
+            let (params, param_types) = annotated_params.into_iter().unzip();
+            let (return_type, tokens) = parse_return_type(tokens)?;
 
             let (body_block_option, tokens) = parse_statement(tokens, true)?;
             let body_block = ensure_block(body_block_option)?;
 
             Ok((
                 Some(SugaredProcedure {
+                    span: Span {
+                        line: *line,
+                        col: *col,
+                    },
+                    doc,
                     name: name.to_string(),
                     params,
+                    param_types,
+                    return_type,
                     body: body_block,
                 }),
                 tokens,
@@ -53,13 +312,110 @@ pub fn parse_proc(tokens: &[T]) -> Result<(Option<SugaredProcedure>, &[T]), Pars
     }
 }
 
-pub fn parse_params(tokens: &[T]) -> Result<(Vec<String>, &[T]), ParseError> {
+/// Returns the text of the doc comments at the start of `tokens`, along with the tokens after them.
+fn parse_doc_comments(tokens: &[T]) -> (Option<String>, &[T]) {
+    let mut lines = vec![];
+    let mut tokens = tokens;
+    while let [T(TRIVIA(Trivia::DocComment(line)), ..), rest @ ..] = tokens {
+        lines.push(line.as_str());
+        tokens = rest;
+    }
+    match lines.is_empty() {
+        true => (None, tokens),
+        false => (Some(lines.join("\n")), tokens),
+    }
+}
+
+/// Parses the parameters of a lambda up to and including the closing parenthesis.
+pub fn parse_params(tokens: &[T]) -> Result<(Vec<SugaredParam>, &[T]), ParseError> {
+    let (annotated_params, tokens) = parse_param_list(tokens, false)?;
+    let params = annotated_params
+        .into_iter()
+        .map(|(param, _)| param)
+        .collect();
+    Ok((params, tokens))
+}
+
+/// A parameter along with its type annotation, if there is one.
+type AnnotatedParam = (SugaredParam, Option<Type>);
+
+/// Parses a list of parameters up to and including the closing parenthesis. Each parameter may
+/// have a type annotation if `typed` is true. Parameters with default values can only be followed
+/// by other parameters with default values, and a rest parameter must come last.
+fn parse_param_list(tokens: &[T], typed: bool) -> Result<(Vec<AnnotatedParam>, &[T]), ParseError> {
+    let mut params: Vec<AnnotatedParam> = vec![];
+    let mut tokens = match tokens {
+        [T(RPAREN, ..), tokens @ ..] => return Ok((params, tokens)),
+        tokens => tokens,
+    };
+    loop {
+        let ((param, ty), rest) = parse_param(tokens, typed)?;
+        if let Some((last, _)) = params.last().filter(|(last, _)| last.rest) {
+            return Err(RestParamNotLast(last.name.to_string()));
+        }
+        let after_default = params.iter().any(|(param, _)| param.default.is_some());
+        if after_default && param.default.is_none() && !param.rest {
+            return Err(RequiredParamAfterDefault(param.name));
+        }
+        params.push((param, ty));
+        match rest {
+            [T(COMMA, ..), T(RPAREN, ..), ..] => return Err(unexpected_token(rest)),
+            [T(COMMA, ..), rest @ ..] => tokens = rest,
+            [T(RPAREN, ..), rest @ ..] => return Ok((params, rest)),
+            rest => return Err(unexpected_token(rest)),
+        }
+    }
+}
+
+/// Parses a single parameter, which is either a name followed by an optional default value, such
+/// as `b = 2`, or a rest parameter such as `...rest`. A type annotation may follow the name if
+/// `typed` is true.
+fn parse_param(tokens: &[T], typed: bool) -> Result<(AnnotatedParam, &[T]), ParseError> {
+    let (rest, tokens) = match tokens {
+        [T(ELLIPSIS, ..), tokens @ ..] => (true, tokens),
+        tokens => (false, tokens),
+    };
+    let (name, tokens) = match tokens {
+        [T(KW(kw), ..), ..] => return Err(KeywordAsParam(kw.to_string())),
+        [T(ID(name), ..), tokens @ ..] => (name.to_string(), tokens),
+        tokens => return Err(unexpected_token(tokens)),
+    };
+    let (ty, tokens) = match typed {
+        true => parse_annotation(tokens)?,
+        false => (None, tokens),
+    };
+    let (default, tokens) = match tokens {
+        [T(ASSIGN, ..), tokens @ ..] if !rest => {
+            let (default, tokens) = parse_expr(tokens)?;
+            (Some(default), tokens)
+        }
+        tokens => (None, tokens),
+    };
+    Ok((
+        (
+            Param {
+                name,
+                default,
+                rest,
+            },
+            ty,
+        ),
+        tokens,
+    ))
+}
+
+/// The name of a parameter along with its type annotation, if there is one.
+type TypedParam = (String, Option<Type>);
+
+/// Parses the fields of a variant, each of which may have a type annotation.
+pub fn parse_typed_params(tokens: &[T]) -> Result<(Vec<TypedParam>, &[T]), ParseError> {
     match tokens {
         [T(RPAREN, ..), rest @ ..] => Ok((vec![], rest)),
         [T(KW(kw), ..), ..] => Err(KeywordAsParam(kw.to_string())),
         [T(ID(param_name), ..), rest_toks @ ..] => {
-            let (mut rest_params, rest_toks) = parse_rest_params(rest_toks)?;
-            let mut params = vec![param_name.to_string()];
+            let (param_type, rest_toks) = parse_annotation(rest_toks)?;
+            let (mut rest_params, rest_toks) = parse_rest_typed_params(rest_toks)?;
+            let mut params = vec![(param_name.to_string(), param_type)];
             params.append(&mut rest_params);
             Ok((params, rest_toks))
         }
@@ -67,11 +423,11 @@ pub fn parse_params(tokens: &[T]) -> Result<(Vec<String>, &[T]), ParseError> {
     }
 }
 
-pub fn parse_rest_params(tokens: &[T]) -> Result<(Vec<String>, &[T]), ParseError> {
+pub fn parse_rest_typed_params(tokens: &[T]) -> Result<(Vec<TypedParam>, &[T]), ParseError> {
     match tokens {
         [T(RPAREN, ..), tokens @ ..] => Ok((vec![], tokens)),
         [T(COMMA, ..), T(RPAREN, ..), ..] => Err(unexpected_token(tokens)),
-        [T(COMMA, ..), tokens @ ..] => parse_params(tokens),
+        [T(COMMA, ..), tokens @ ..] => parse_typed_params(tokens),
         tokens => Err(unexpected_token(tokens)),
     }
 }
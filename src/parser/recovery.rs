@@ -0,0 +1,166 @@
+use crate::{
+    error::ParseError,
+    tokenizer::{Keyword::*, Token as T, TokenValue::*},
+};
+
+use super::{SugaredExpr, SugaredItem, SugaredProcedure, SugaredStatement};
+
+/// Skips the rest of a statement which could not be parsed, starting from its first token. The
+/// parser resumes after the next `;` or balanced block, or before a `}` which closes the
+/// enclosing block or a `proc`, `struct` or `enum` keyword which begins the next declaration.
+pub fn synchronize(tokens: &[T]) -> &[T] {
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match token.0 {
+            KW(Proc) if index > 0 && !matches!(tokens.get(index + 1), Some(T(LPAREN, ..))) => {
+                return &tokens[index..]
+            }
+            KW(Struct | Enum) if index > 0 => return &tokens[index..],
+            SEMICOLON if depth == 0 => return &tokens[index + 1..],
+            L_CURLY_BRACKET => depth += 1,
+            R_CURLY_BRACKET if depth == 0 => return &tokens[index..],
+            R_CURLY_BRACKET => {
+                depth -= 1;
+                if depth == 0 {
+                    // a block may be followed by the semicolon that ends a `let` statement
+                    return match &tokens[index + 1..] {
+                        [T(SEMICOLON, ..), rest @ ..] => rest,
+                        rest => rest,
+                    };
+                }
+            }
+            _ => (),
+        }
+    }
+    &tokens[tokens.len()..]
+}
+
+/// Skips the rest of a top-level declaration which could not be parsed, resuming at the next
+/// `proc`, `struct` or `enum` keyword.
+pub fn synchronize_proc(tokens: &[T]) -> &[T] {
+    // a `proc` keyword followed by a left parenthesis is a procedure type rather than a procedure
+    let starts_declaration = |index: usize| match &tokens[index..] {
+        [T(KW(Proc), ..), T(LPAREN, ..), ..] => false,
+        [T(KW(Proc | Struct | Enum), ..), ..] => true,
+        _ => false,
+    };
+    match (1..tokens.len()).find(|index| starts_declaration(*index)) {
+        Some(index) => &tokens[index..],
+        None => &tokens[tokens.len()..],
+    }
+}
+
+/// Appends the errors of the [Error](SugaredStatement::Error) statements in `proc` to `errors`, in
+/// the order in which they appear in the source.
+pub fn collect_proc_errors(proc: &SugaredProcedure, errors: &mut Vec<ParseError>) {
+    for default in proc.params.iter().filter_map(|param| param.default.as_ref()) {
+        collect_expr_errors(default, errors);
+    }
+    collect_statement_errors(&proc.body, errors);
+}
+
+/// Returns the first error of the [Error](SugaredStatement::Error) statements in `items`.
+pub fn first_item_error(items: &[SugaredItem]) -> Option<ParseError> {
+    let mut errors = vec![];
+    for item in items {
+        if let SugaredItem::Proc(proc) = item {
+            collect_proc_errors(proc, &mut errors);
+        }
+    }
+    errors.into_iter().next()
+}
+
+fn collect_statement_errors(statement: &SugaredStatement, errors: &mut Vec<ParseError>) {
+    match statement {
+        SugaredStatement::Error(e) => errors.push(e.clone()),
+        SugaredStatement::Spanned(_, statement) => collect_statement_errors(statement, errors),
+        SugaredStatement::Expr(expr)
+        | SugaredStatement::Let(_, _, expr)
+        | SugaredStatement::Const(_, _, expr)
+        | SugaredStatement::Assign(_, expr)
+        | SugaredStatement::FieldAssign(_, _, expr)
+        | SugaredStatement::OperatorAssignment(_, _, expr)
+        | SugaredStatement::Return(Some(expr)) => collect_expr_errors(expr, errors),
+        SugaredStatement::Block(statements) => {
+            for statement in statements {
+                collect_statement_errors(statement, errors);
+            }
+        }
+        SugaredStatement::If(cond, then_block, else_ifs, else_block) => {
+            collect_expr_errors(cond, errors);
+            collect_statement_errors(then_block, errors);
+            for (else_if_cond, else_if_block) in else_ifs {
+                collect_expr_errors(else_if_cond, errors);
+                collect_statement_errors(else_if_block, errors);
+            }
+            if let Some(else_block) = else_block {
+                collect_statement_errors(else_block, errors);
+            }
+        }
+        SugaredStatement::While(cond, body) => {
+            collect_expr_errors(cond, errors);
+            collect_statement_errors(body, errors);
+        }
+        SugaredStatement::For(init, cond, update, body) => {
+            collect_statement_errors(init, errors);
+            collect_expr_errors(cond, errors);
+            collect_statement_errors(update, errors);
+            for statement in body {
+                collect_statement_errors(statement, errors);
+            }
+        }
+        SugaredStatement::Break
+        | SugaredStatement::Continue
+        | SugaredStatement::Return(None)
+        | SugaredStatement::Trivia(_) => (),
+    }
+}
+
+fn collect_expr_errors(expr: &SugaredExpr, errors: &mut Vec<ParseError>) {
+    match expr {
+        SugaredExpr::Binary(_, lhs, rhs) | SugaredExpr::Index(lhs, rhs) => {
+            collect_expr_errors(lhs, errors);
+            collect_expr_errors(rhs, errors);
+        }
+        SugaredExpr::Unary(_, operand)
+        | SugaredExpr::Field(operand, _)
+        | SugaredExpr::NamedArg(_, operand) => collect_expr_errors(operand, errors),
+        SugaredExpr::StructLiteral(_, fields) => {
+            for (_, value) in fields {
+                collect_expr_errors(value, errors);
+            }
+        }
+        SugaredExpr::PrimitiveCall(_, args) => {
+            for arg in args {
+                collect_expr_errors(arg, errors);
+            }
+        }
+        SugaredExpr::Call(proc_expr, args) | SugaredExpr::MethodCall(proc_expr, _, args) => {
+            collect_expr_errors(proc_expr, errors);
+            for arg in args {
+                collect_expr_errors(arg, errors);
+            }
+        }
+        SugaredExpr::Lambda(_, params, body) => {
+            for default in params.iter().filter_map(|param| param.default.as_ref()) {
+                collect_expr_errors(default, errors);
+            }
+            collect_statement_errors(body, errors);
+        }
+        SugaredExpr::Match(scrutinee, arms) => {
+            collect_expr_errors(scrutinee, errors);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    collect_expr_errors(guard, errors);
+                }
+                collect_statement_errors(&arm.body, errors);
+            }
+        }
+        SugaredExpr::Nil
+        | SugaredExpr::Num(_)
+        | SugaredExpr::Bool(_)
+        | SugaredExpr::Str(_)
+        | SugaredExpr::Var(_)
+        | SugaredExpr::Trivia(_) => (),
+    }
+}
@@ -1,60 +1,143 @@
 use crate::{
     error::ParseError::{self, *},
-    tokenizer::{Keyword::*, Token as T, TokenValue::*},
+    tokenizer::{Keyword::*, Token as T, TokenValue::*, Trivia},
 };
 
 use super::{
     expressions::parse_expr,
+    patterns::parse_match,
+    recovery::synchronize,
+    types::parse_annotation,
     utils::{
         conditionally_consume_semicolon, consume_token, ensure_block, is_assignment,
-        is_assignment_or_initialization,
+        is_assignment_or_initialization, nest, parse_with_comments, skip_trivia, unexpected_token,
     },
-    SugaredStatement,
+    Span, SugaredStatement,
 };
 
+/// Parses the statements of a block up to and including its closing curly bracket. A statement
+/// which cannot be parsed is replaced by an [Error](SugaredStatement::Error) statement, and parsing
+/// resumes at the next statement.
 pub fn parse_statements(tokens: &[T]) -> Result<(Vec<SugaredStatement>, &[T]), ParseError> {
-    let (statement_option, tokens) = parse_statement(tokens, true)?;
+    let mut statements = vec![];
+    let mut tokens = tokens;
+    loop {
+        if let [T(TRIVIA(trivia), ..), rest @ ..] = tokens {
+            statements.push(SugaredStatement::Trivia(trivia.clone()));
+            tokens = rest;
+            continue;
+        }
 
-    let statement = match statement_option {
-        Some(statement) => statement,
-        None => return Ok((vec![], tokens)),
-    };
+        // the block is unterminated, so end it here and let the caller continue with the next
+        // declaration or the end of the file
+        let span = match tokens {
+            [token @ T(KW(Proc | Struct | Enum), ..), ..] => {
+                let error = Expected(R_CURLY_BRACKET, token.clone());
+                statements.push(SugaredStatement::Error(error));
+                return Ok((statements, tokens));
+            }
+            [T(_, line, col, ..), ..] => Span {
+                line: *line,
+                col: *col,
+            },
+            [] => {
+                statements.push(SugaredStatement::Error(UnexpectedEOF));
+                return Ok((statements, tokens));
+            }
+        };
+
+        // comments within the statement go before it, unless they trail a line
+        let (statement_option, comments, rest) =
+            match parse_with_comments(tokens, |tokens| parse_statement(tokens, true)) {
+                Ok(result) => result,
+                Err(e) => (
+                    Some(SugaredStatement::Error(e)),
+                    vec![],
+                    synchronize(tokens),
+                ),
+            };
+        tokens = rest;
+        let (trailing, leading): (Vec<_>, Vec<_>) = comments
+            .into_iter()
+            .partition(|comment| matches!(comment, Trivia::TrailingComment(_)));
+        statements.extend(leading.into_iter().map(SugaredStatement::Trivia));
 
-    let (mut rest_statements, tokens) = parse_statements(tokens)?;
-    let mut vec = vec![statement];
-    vec.append(&mut rest_statements);
-    Ok((vec, tokens))
+        match statement_option {
+            Some(statement) => {
+                statements.push(SugaredStatement::Spanned(span, Box::new(statement)))
+            }
+            None => return Ok((statements, tokens)),
+        }
+        statements.extend(trailing.into_iter().map(SugaredStatement::Trivia));
+    }
 }
 
 pub fn parse_statement(
     tokens: &[T],
     parse_semicolon: bool,
 ) -> Result<(Option<SugaredStatement>, &[T]), ParseError> {
+    // the end of a block is not nested in it, so that recovering from an error which is reported
+    // here always skips past the first token
+    if let [T(R_CURLY_BRACKET, ..), tokens @ ..] = tokens {
+        return Ok((None, tokens));
+    }
+    let _nested = nest(tokens)?;
     match tokens {
-        [T(R_CURLY_BRACKET, ..), tokens @ ..] => Ok((None, tokens)),
         [T(KW(Let), ..), T(KW(kw), ..), ..] => Err(KeywordAsVar(kw.to_string())),
         [T(KW(Const), ..), T(KW(kw), ..), ..] => Err(KeywordAsVar(kw.to_string())),
-        [T(KW(Let), ..), T(ID(var_name), ..), T(ASSIGN, ..), tokens @ ..] => {
+        [T(KW(Let), ..), T(ID(var_name), ..), tokens @ ..]
+            if matches!(tokens, [T(ASSIGN | COLON, ..), ..]) =>
+        {
+            let (var_type, tokens) = parse_annotation(tokens)?;
+            let tokens = consume_token(ASSIGN, tokens)?;
             let (var_expr, tokens) = parse_expr(tokens)?;
 
             let tokens = conditionally_consume_semicolon(tokens, parse_semicolon)?;
 
             Ok((
-                Some(SugaredStatement::Let(var_name.to_string(), var_expr)),
+                Some(SugaredStatement::Let(
+                    var_name.to_string(),
+                    var_type,
+                    var_expr,
+                )),
                 tokens,
             ))
         }
-        [T(KW(Const), ..), T(ID(var_name), ..), T(ASSIGN, ..), tokens @ ..] => {
+        [T(KW(Const), ..), T(ID(var_name), ..), tokens @ ..]
+            if matches!(tokens, [T(ASSIGN | COLON, ..), ..]) =>
+        {
+            let (var_type, tokens) = parse_annotation(tokens)?;
+            let tokens = consume_token(ASSIGN, tokens)?;
             let (var_expr, tokens) = parse_expr(tokens)?;
 
             let tokens = conditionally_consume_semicolon(tokens, parse_semicolon)?;
 
             Ok((
-                Some(SugaredStatement::Const(var_name.to_string(), var_expr)),
+                Some(SugaredStatement::Const(
+                    var_name.to_string(),
+                    var_type,
+                    var_expr,
+                )),
                 tokens,
             ))
         }
         [T(KW(kw), ..), T(ASSIGN, ..), ..] => Err(KeywordAsVar(kw.to_string())),
+        [T(ID(var_name), ..), tokens @ ..] if is_field_assignment(tokens) => {
+            let (fields, tokens) = parse_field_path(tokens)?;
+            let tokens = consume_token(ASSIGN, tokens)?;
+            let (var_expr, tokens) = parse_expr(tokens)?;
+
+            let tokens = conditionally_consume_semicolon(tokens, parse_semicolon)?;
+
+            Ok((
+                Some(SugaredStatement::FieldAssign(
+                    var_name.to_string(),
+                    fields,
+                    var_expr,
+                )),
+                tokens,
+            ))
+        }
         [T(ID(var_name), ..), T(ASSIGN, ..), tokens @ ..] => {
             let (var_expr, tokens) = parse_expr(tokens)?;
 
@@ -88,6 +171,12 @@ pub fn parse_statement(
             let mut else_ifs = vec![];
             loop {
                 match tokens {
+                    // taken out by parse_with_comments, which moves it into the block
+                    [T(TRIVIA(_), ..), ..]
+                        if matches!(skip_trivia(tokens), [T(KW(Else), ..), ..]) =>
+                    {
+                        return Err(unexpected_token(tokens));
+                    }
                     [T(KW(Else), ..), T(KW(If), ..), T(LPAREN, ..), rest @ ..] => {
                         let (else_if_cond, rest) = parse_expr(rest)?;
                         let rest = consume_token(RPAREN, rest)?;
@@ -201,6 +290,11 @@ pub fn parse_statement(
             let (statements, tokens) = parse_statements(tokens)?;
             Ok((Some(SugaredStatement::Block(statements)), tokens))
         }
+        // like an `if` statement, a `match` used as a statement is not followed by a semicolon
+        [T(KW(Match), ..), ..] => {
+            let (match_expr, tokens) = parse_match(tokens)?;
+            Ok((Some(SugaredStatement::Expr(match_expr)), tokens))
+        }
         tokens => match parse_expr(tokens)? {
             (expr, tokens) => {
                 let tokens = conditionally_consume_semicolon(tokens, parse_semicolon)?;
@@ -209,3 +303,24 @@ pub fn parse_statement(
         },
     }
 }
+
+/// Returns whether `tokens` is a path of fields such as `.a.b` followed by an [ASSIGN] token, so
+/// that the variable before `tokens` is the target of a field assignment.
+fn is_field_assignment(tokens: &[T]) -> bool {
+    match tokens {
+        [T(DOT, ..), T(ID(_), ..), T(ASSIGN, ..), ..] => true,
+        [T(DOT, ..), T(ID(_), ..), rest @ ..] => is_field_assignment(rest),
+        _ => false,
+    }
+}
+
+/// Parses a path of fields such as `.a.b`, returning the names of the fields.
+fn parse_field_path(tokens: &[T]) -> Result<(Vec<String>, &[T]), ParseError> {
+    let mut fields = vec![];
+    let mut tokens = tokens;
+    while let [T(DOT, ..), T(ID(field), ..), rest @ ..] = tokens {
+        fields.push(field.to_string());
+        tokens = rest;
+    }
+    Ok((fields, tokens))
+}
@@ -0,0 +1,207 @@
+use crate::tokenizer::Operator::*;
+use crate::{
+    error::ParseError::{self, *},
+    tokenizer::{Keyword::*, Token as T, TokenValue::*},
+    typecheck::Type,
+};
+
+use super::utils::{consume_token, nest, unexpected_token};
+use super::{SugaredExpr, SugaredProcedure, SugaredStatement};
+
+/// Parses an optional type annotation, which is a [COLON] followed by a type. If `tokens` does not
+/// start with a [COLON], this function returns `None` along with `tokens` unmodified.
+pub fn parse_annotation(tokens: &[T]) -> Result<(Option<Type>, &[T]), ParseError> {
+    match tokens {
+        [T(COLON, ..), tokens @ ..] => {
+            let (ty, tokens) = parse_type(tokens)?;
+            Ok((Some(ty), tokens))
+        }
+        tokens => Ok((None, tokens)),
+    }
+}
+
+/// Parses an optional return type annotation, which is a [THIN_ARROW] followed by a type.
+pub fn parse_return_type(tokens: &[T]) -> Result<(Option<Type>, &[T]), ParseError> {
+    match tokens {
+        [T(THIN_ARROW, ..), tokens @ ..] => {
+            let (ty, tokens) = parse_type(tokens)?;
+            Ok((Some(ty), tokens))
+        }
+        tokens => Ok((None, tokens)),
+    }
+}
+
+pub fn parse_type(tokens: &[T]) -> Result<(Type, &[T]), ParseError> {
+    let _nested = nest(tokens)?;
+    match tokens {
+        [T(KW(Nil), ..), tokens @ ..] => Ok((Type::Nil, tokens)),
+        [T(ID(name), ..), T(OP(LT), ..), tokens @ ..] if name == "list" => {
+            let (element, tokens) = parse_type(tokens)?;
+            let tokens = consume_token(OP(GT), tokens)?;
+            Ok((Type::List(Box::new(element)), tokens))
+        }
+        [T(ID(name), ..), tokens @ ..] => match name.as_str() {
+            "any" => Ok((Type::Any, tokens)),
+            "num" => Ok((Type::Num, tokens)),
+            "bool" => Ok((Type::Bool, tokens)),
+            "str" => Ok((Type::Str, tokens)),
+            // struct and enum names are checked once every declaration has been parsed
+            name => Ok((Type::Named(name.to_string()), tokens)),
+        },
+        [T(KW(Proc), ..), T(LPAREN, ..), tokens @ ..] => {
+            let (params, tokens) = parse_type_list(tokens)?;
+            let tokens = consume_token(THIN_ARROW, tokens)?;
+            let (ret, tokens) = parse_type(tokens)?;
+            Ok((Type::Proc(params, Box::new(ret)), tokens))
+        }
+        tokens => Err(unexpected_token(tokens)),
+    }
+}
+
+/// Parses the parameter types of a procedure type up to and including the closing parenthesis.
+fn parse_type_list(tokens: &[T]) -> Result<(Vec<Type>, &[T]), ParseError> {
+    if let [T(RPAREN, ..), tokens @ ..] = tokens {
+        return Ok((vec![], tokens));
+    }
+    let mut types = vec![];
+    let mut tokens = tokens;
+    loop {
+        let (ty, rest) = parse_type(tokens)?;
+        types.push(ty);
+        match rest {
+            [T(COMMA, ..), rest @ ..] => tokens = rest,
+            [T(RPAREN, ..), rest @ ..] => return Ok((types, rest)),
+            rest => return Err(unexpected_token(rest)),
+        }
+    }
+}
+
+/// Appends an [UnknownType] error to `errors` for each named type in `ty` which is not one of the
+/// declared struct or enum names in `names`.
+pub fn check_type_names(ty: &Type, names: &[&str], errors: &mut Vec<ParseError>) {
+    match ty {
+        Type::Named(name) if !names.contains(&name.as_str()) => {
+            errors.push(UnknownType(name.to_string()))
+        }
+        Type::List(element) => check_type_names(element, names, errors),
+        Type::Proc(params, ret) => {
+            for param in params {
+                check_type_names(param, names, errors);
+            }
+            check_type_names(ret, names, errors);
+        }
+        _ => (),
+    }
+}
+
+/// Returns every type annotation in `proc`, in the order in which they appear in the source.
+pub fn collect_proc_annotations(proc: &SugaredProcedure) -> Vec<&Type> {
+    let mut types: Vec<&Type> = vec![];
+    for (param, ty) in proc.params.iter().zip(&proc.param_types) {
+        types.extend(ty.iter());
+        if let Some(default) = &param.default {
+            collect_expr_annotations(default, &mut types);
+        }
+    }
+    types.extend(proc.return_type.iter());
+    collect_statement_annotations(&proc.body, &mut types);
+    types
+}
+
+fn collect_statement_annotations<'a>(statement: &'a SugaredStatement, types: &mut Vec<&'a Type>) {
+    match statement {
+        SugaredStatement::Spanned(_, statement) => collect_statement_annotations(statement, types),
+        SugaredStatement::Let(_, ty, expr) | SugaredStatement::Const(_, ty, expr) => {
+            types.extend(ty.iter());
+            collect_expr_annotations(expr, types);
+        }
+        SugaredStatement::Expr(expr)
+        | SugaredStatement::Assign(_, expr)
+        | SugaredStatement::FieldAssign(_, _, expr)
+        | SugaredStatement::OperatorAssignment(_, _, expr)
+        | SugaredStatement::Return(Some(expr)) => collect_expr_annotations(expr, types),
+        SugaredStatement::Block(statements) => {
+            for statement in statements {
+                collect_statement_annotations(statement, types);
+            }
+        }
+        SugaredStatement::If(cond, then_block, else_ifs, else_block) => {
+            collect_expr_annotations(cond, types);
+            collect_statement_annotations(then_block, types);
+            for (else_if_cond, else_if_block) in else_ifs {
+                collect_expr_annotations(else_if_cond, types);
+                collect_statement_annotations(else_if_block, types);
+            }
+            if let Some(else_block) = else_block {
+                collect_statement_annotations(else_block, types);
+            }
+        }
+        SugaredStatement::While(cond, body) => {
+            collect_expr_annotations(cond, types);
+            collect_statement_annotations(body, types);
+        }
+        SugaredStatement::For(init, cond, update, body) => {
+            collect_statement_annotations(init, types);
+            collect_expr_annotations(cond, types);
+            collect_statement_annotations(update, types);
+            for statement in body {
+                collect_statement_annotations(statement, types);
+            }
+        }
+        SugaredStatement::Break
+        | SugaredStatement::Continue
+        | SugaredStatement::Return(None)
+        | SugaredStatement::Trivia(_)
+        | SugaredStatement::Error(_) => (),
+    }
+}
+
+/// Collects the annotations in the bodies of the lambdas in `expr`.
+fn collect_expr_annotations<'a>(expr: &'a SugaredExpr, types: &mut Vec<&'a Type>) {
+    match expr {
+        SugaredExpr::Binary(_, lhs, rhs) | SugaredExpr::Index(lhs, rhs) => {
+            collect_expr_annotations(lhs, types);
+            collect_expr_annotations(rhs, types);
+        }
+        SugaredExpr::Unary(_, operand)
+        | SugaredExpr::Field(operand, _)
+        | SugaredExpr::NamedArg(_, operand) => collect_expr_annotations(operand, types),
+        SugaredExpr::PrimitiveCall(_, args) => {
+            for arg in args {
+                collect_expr_annotations(arg, types);
+            }
+        }
+        SugaredExpr::Call(proc_expr, args) | SugaredExpr::MethodCall(proc_expr, _, args) => {
+            collect_expr_annotations(proc_expr, types);
+            for arg in args {
+                collect_expr_annotations(arg, types);
+            }
+        }
+        SugaredExpr::StructLiteral(_, fields) => {
+            for (_, value) in fields {
+                collect_expr_annotations(value, types);
+            }
+        }
+        SugaredExpr::Lambda(_, params, body) => {
+            for default in params.iter().filter_map(|param| param.default.as_ref()) {
+                collect_expr_annotations(default, types);
+            }
+            collect_statement_annotations(body, types);
+        }
+        SugaredExpr::Match(scrutinee, arms) => {
+            collect_expr_annotations(scrutinee, types);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    collect_expr_annotations(guard, types);
+                }
+                collect_statement_annotations(&arm.body, types);
+            }
+        }
+        SugaredExpr::Nil
+        | SugaredExpr::Num(_)
+        | SugaredExpr::Bool(_)
+        | SugaredExpr::Str(_)
+        | SugaredExpr::Var(_)
+        | SugaredExpr::Trivia(_) => (),
+    }
+}
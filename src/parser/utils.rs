@@ -1,4 +1,10 @@
-use crate::tokenizer::Operator::{self, *};
+use std::cell::Cell;
+
+use crate::tokenizer::{
+    Keyword::Else,
+    Operator::{self, *},
+    Trivia,
+};
 use crate::{
     error::ParseError::{self, *},
     tokenizer::{
@@ -7,7 +13,7 @@ use crate::{
     },
 };
 
-use super::{Builtin, SugaredExpr, SugaredStatement};
+use super::{Builtin, SugaredExpr, SugaredStatement, MAX_NESTING};
 
 /// A helper function to handle unexpected token patterns. This function returns an
 /// [UnexpectedToken Error](UnexpectedToken), or an [Unexpected End-of-File](UnexpectedEOF) if
@@ -19,22 +25,61 @@ pub fn unexpected_token(tokens: &[T]) -> ParseError {
     }
 }
 
+thread_local! {
+    /// How many expressions, statements, patterns and types the parser is nested inside of.
+    static NESTING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A level of nesting entered by [nest], which is left when this is dropped.
+pub struct Nested(());
+
+impl Drop for Nested {
+    fn drop(&mut self) {
+        NESTING.with(|nesting| nesting.set(nesting.get() - 1));
+    }
+}
+
+/// Enters the expression, statement, pattern or type at the start of `tokens` until the returned
+/// [Nested] is dropped, failing with [NestedTooDeeply] if the parser would then be nested more
+/// deeply than [MAX_NESTING]. This is called by each function of the parser which can recurse, so
+/// that the parser cannot overflow the stack.
+pub fn nest(tokens: &[T]) -> Result<Nested, ParseError> {
+    let depth = NESTING.with(Cell::get);
+    if depth >= MAX_NESTING {
+        return Err(match tokens {
+            [token, ..] => NestedTooDeeply(token.clone()),
+            [] => UnexpectedEOF,
+        });
+    }
+    NESTING.with(|nesting| nesting.set(depth + 1));
+    Ok(Nested(()))
+}
+
 /// A helper function to check if `s` matches one of the [Builtin] procedures.
 pub fn check_builtin(expr: &SugaredExpr) -> Option<Builtin> {
     match expr {
-        SugaredExpr::Var(name) => match name.as_str() {
-            "print" => Some(Builtin::Print),
-            "list" => Some(Builtin::List),
-            "is_empty" => Some(Builtin::IsEmpty),
-            "is_nil" => Some(Builtin::IsNil),
-            "head" => Some(Builtin::Head),
-            "rest" => Some(Builtin::Rest),
-            _ => None,
-        },
+        SugaredExpr::Var(name) => Builtin::from_name(name),
         _ => None,
     }
 }
 
+/// Checks that no positional argument in `args` follows a named argument, and that a call to the
+/// builtin `builtin`, if there is one, has no named arguments.
+pub fn check_args(args: &[SugaredExpr], builtin: Option<Builtin>) -> Result<(), ParseError> {
+    let mut named = false;
+    for arg in args.iter().filter(|arg| !matches!(arg, SugaredExpr::Trivia(_))) {
+        match (arg, builtin) {
+            (SugaredExpr::NamedArg(..), Some(builtin)) => {
+                return Err(NamedArgToBuiltin(builtin.to_string()))
+            }
+            (SugaredExpr::NamedArg(..), None) => named = true,
+            _ if named => return Err(PositionalArgAfterNamed),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
 /// Tries to consume a token with a [TokenValue] of `target` from the front of `tokens`. On success,
 /// this function returns `tokens` with the first element removed. On failure, this function returns
 /// an [Expected] error.
@@ -130,21 +175,72 @@ pub fn ensure_block(
 pub fn is_assignment(statement: &SugaredStatement) -> bool {
     match statement {
         SugaredStatement::Assign(_, _) => true,
+        SugaredStatement::FieldAssign(..) => true,
         SugaredStatement::OperatorAssignment(_, _, _) => true,
-        SugaredStatement::Expr(expr) => match expr {
-            SugaredExpr::Unary(op, _) => match op {
-                PreIncrement | PostIncrement | PreDecrement | PostDecrement => true,
-                _ => false,
-            },
-            _ => false,
-        },
+        SugaredStatement::Expr(expr) => matches!(
+            expr,
+            SugaredExpr::Unary(
+                PreIncrement | PostIncrement | PreDecrement | PostDecrement,
+                _
+            )
+        ),
         _ => false,
     }
 }
 
 pub fn is_assignment_or_initialization(statement: &SugaredStatement) -> bool {
     match statement {
-        SugaredStatement::Let(_, _) => true,
+        SugaredStatement::Let(..) => true,
         statement => is_assignment(statement),
     }
 }
+
+/// Parses `tokens` with `parse`, taking the comments which `parse` does not accept, such as the
+/// one in `1 + // c\n 2`, out of the parsed tokens. A trailing comment before the opening curly
+/// bracket of a block, as in `if (x) // c\n {`, is moved into the block so that it follows the
+/// bracket, a comment between a block and `else` is moved to the end of the block, and every other
+/// comment is returned so that the caller can place it next to the node.
+pub fn parse_with_comments<'a, N>(
+    tokens: &'a [T],
+    parse: impl for<'b> Fn(&'b [T]) -> Result<(N, &'b [T]), ParseError>,
+) -> Result<(N, Vec<Trivia>, &'a [T]), ParseError> {
+    let mut comments = vec![];
+    let mut edited: Option<Vec<T>> = None;
+    loop {
+        let error = match parse(edited.as_deref().unwrap_or(tokens)) {
+            // only the tokens before `rest` were edited
+            Ok((node, rest)) => return Ok((node, comments, &tokens[tokens.len() - rest.len()..])),
+            Err(error) => error,
+        };
+        let comment = match &error {
+            UnexpectedToken(token @ T(TRIVIA(_), ..)) | Expected(_, token @ T(TRIVIA(_), ..)) => {
+                token
+            }
+            _ => return Err(error),
+        };
+        let edited = edited.get_or_insert_with(|| tokens.to_vec());
+        let Some(index) = edited.iter().position(|token| token == comment) else {
+            return Err(error);
+        };
+        let comment = edited.remove(index);
+        let after_block = index > 0 && matches!(edited[index - 1], T(R_CURLY_BRACKET, ..));
+        if let (T(TRIVIA(Trivia::TrailingComment(_)), ..), Some(T(L_CURLY_BRACKET, ..))) =
+            (&comment, edited.get(index))
+        {
+            edited.insert(index + 1, comment);
+        } else if after_block && matches!(skip_trivia(&edited[index..]), [T(KW(Else), ..), ..]) {
+            edited.insert(index - 1, comment);
+        } else if let T(TRIVIA(trivia), ..) = comment {
+            comments.push(trivia);
+        }
+    }
+}
+
+/// Returns the tokens after the trivia at the start of `tokens`.
+pub fn skip_trivia(tokens: &[T]) -> &[T] {
+    let mut tokens = tokens;
+    while let [T(TRIVIA(_), ..), rest @ ..] = tokens {
+        tokens = rest;
+    }
+    tokens
+}
@@ -0,0 +1,337 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::RuntimeError,
+    interpreter::{Capabilities, Interpreter, Value},
+    parser::{parse_declarations_recovering, parse_program_with_entry},
+    tokenizer::tokenize,
+    Writer,
+};
+
+/// Whether a test passed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// The test failed with the given message, which ends with a diff of the values if the test
+    /// failed in `assert_eq`.
+    Failed(String),
+}
+
+/// The result of running a single test procedure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestResult {
+    /// The path of the file which declares the test, relative to the directory in which the tests
+    /// were found.
+    pub file: String,
+    /// The name of the test procedure, or `None` if the file could not be loaded, in which case
+    /// the whole file fails.
+    pub name: Option<String>,
+    pub outcome: Outcome,
+    pub duration: Duration,
+    /// Everything which the test printed to its output stream, followed by everything which it
+    /// printed to its error stream.
+    pub output: String,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == Outcome::Passed
+    }
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{}::{name}", self.file),
+            None => write!(f, "{}", self.file),
+        }
+    }
+}
+
+/// Runs every test in the Linger files in `path`, which is either a single file or a directory
+/// which is searched recursively. A test is a procedure whose name starts with `test_`, and it
+/// passes if it returns without an error. Each test runs in its own interpreter with every
+/// capability granted, reading from an empty input stream.
+pub fn run_tests(path: &Path) -> io::Result<Vec<TestResult>> {
+    let mut results = vec![];
+    for file in find_test_files(path)? {
+        let display = match file.strip_prefix(path) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative.display().to_string(),
+            _ => file.display().to_string(),
+        };
+        let source = fs::read_to_string(&file)?;
+        results.append(&mut run_test_file(&display, &source));
+    }
+    Ok(results)
+}
+
+/// Returns the `.ling` files in `path`, sorted by path, or `path` itself if it is a file.
+fn find_test_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = vec![];
+    for entry in fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        if entry_path.is_dir() {
+            files.append(&mut find_test_files(&entry_path)?);
+        } else if entry_path
+            .extension()
+            .is_some_and(|extension| extension == "ling")
+        {
+            files.push(entry_path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Runs each test declared in `source`, the contents of the file called `file`, in the order in
+/// which they are declared.
+fn run_test_file(file: &str, source: &str) -> Vec<TestResult> {
+    let file_failure = |message: String| TestResult {
+        file: file.to_string(),
+        name: None,
+        outcome: Outcome::Failed(message),
+        duration: Duration::ZERO,
+        output: String::new(),
+    };
+
+    let tokens = match tokenize(source) {
+        Ok(tokens) => tokens,
+        Err(e) => return vec![file_failure(e.to_string())],
+    };
+    let (declarations, errors) = parse_declarations_recovering(tokens.as_slice());
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        return vec![file_failure(errors.join("\n"))];
+    }
+
+    let mut results = vec![];
+    for procedure in &declarations.procedures {
+        if !procedure.name.starts_with("test_") {
+            continue;
+        }
+        let program = match parse_program_with_entry(tokens.as_slice(), &procedure.name) {
+            Ok(program) => program,
+            // such as a test which takes too many parameters, which fails on its own
+            Err(errors) => {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                results.push(TestResult {
+                    file: file.to_string(),
+                    name: Some(procedure.name.to_string()),
+                    outcome: Outcome::Failed(errors.join("\n")),
+                    duration: Duration::ZERO,
+                    output: String::new(),
+                });
+                continue;
+            }
+        };
+
+        let mut out = vec![];
+        let mut err = vec![];
+        let mut writer = Writer::new(Box::new(&mut out))
+            .with_stderr(Box::new(&mut err))
+            .with_stdin(Box::new(io::empty()));
+        let started = Instant::now();
+        let result = Interpreter::new(&mut writer)
+            .with_capabilities(Capabilities::all())
+            .run(program);
+        let duration = started.elapsed();
+        drop(writer);
+
+        let outcome = match result {
+            Ok(_) => Outcome::Passed,
+            Err(e) => Outcome::Failed(failure_message(&e)),
+        };
+        results.push(TestResult {
+            file: file.to_string(),
+            name: Some(procedure.name.to_string()),
+            outcome,
+            duration,
+            output: String::from_utf8_lossy(&out).to_string() + &String::from_utf8_lossy(&err),
+        });
+    }
+    results
+}
+
+/// Returns the message of a test which failed with `e`, along with a diff of the values which
+/// were not equal if it failed in `assert_eq`.
+fn failure_message(e: &RuntimeError) -> String {
+    match e {
+        RuntimeError::AssertEqFailed(left, right) => {
+            // strings are compared line by line, without quotes
+            let text = |value: &Value| match value {
+                Value::Str(s) => s.to_string(),
+                value => value.to_quoted_string(),
+            };
+            format!("{e}\n{}", diff(&text(left), &text(right)))
+        }
+        e => e.to_string(),
+    }
+}
+
+/// Returns a line by line diff of `left` and `right`, in which each line is prefixed by `-` if it
+/// is only in `left`, by `+` if it is only in `right`, and by a space if it is in both.
+fn diff(left: &str, right: &str) -> String {
+    let left: Vec<&str> = left.lines().collect();
+    let right: Vec<&str> = right.lines().collect();
+
+    // the length of the longest common subsequence of the lines after `left[i..]` and `right[j..]`
+    let mut common = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            common[i][j] = match left[i] == right[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() || j < right.len() {
+        if i < left.len() && j < right.len() && left[i] == right[j] {
+            diff += &format!("  {}\n", left[i]);
+            (i, j) = (i + 1, j + 1);
+        } else if j == right.len() || (i < left.len() && common[i + 1][j] >= common[i][j + 1]) {
+            diff += &format!("- {}\n", left[i]);
+            i += 1;
+        } else {
+            diff += &format!("+ {}\n", right[j]);
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// Returns a report of `results` for people to read, listing whether each test passed followed by
+/// the message and output of each test which failed.
+pub fn report(results: &[TestResult]) -> String {
+    let mut report = String::new();
+    for result in results {
+        let status = if result.passed() { "ok" } else { "FAILED" };
+        report += &format!("test {result} ... {status}\n");
+    }
+
+    let failures: Vec<&TestResult> = results.iter().filter(|result| !result.passed()).collect();
+    if !failures.is_empty() {
+        report += "\nfailures:\n";
+        for result in &failures {
+            report += &format!("\n---- {result} ----\n");
+            if !result.output.is_empty() {
+                report += &result.output;
+                if !result.output.ends_with('\n') {
+                    report += "\n";
+                }
+            }
+            if let Outcome::Failed(message) = &result.outcome {
+                report += message;
+                if !message.ends_with('\n') {
+                    report += "\n";
+                }
+            }
+        }
+    }
+
+    let status = if failures.is_empty() { "ok" } else { "FAILED" };
+    report += &format!(
+        "\ntest result: {status}. {} passed; {} failed\n",
+        results.len() - failures.len(),
+        failures.len()
+    );
+    report
+}
+
+/// Returns a report of `results` in the Test Anything Protocol, in which the message of each test
+/// which failed is written as comments after it.
+pub fn tap(results: &[TestResult]) -> String {
+    let mut tap = format!("TAP version 13\n1..{}\n", results.len());
+    for (i, result) in results.iter().enumerate() {
+        match &result.outcome {
+            Outcome::Passed => tap += &format!("ok {} - {result}\n", i + 1),
+            Outcome::Failed(message) => {
+                tap += &format!("not ok {} - {result}\n", i + 1);
+                for line in message.lines() {
+                    tap += &format!("# {line}\n");
+                }
+            }
+        }
+    }
+    tap
+}
+
+/// Returns a report of `results` in the JUnit XML format, with a test suite for each file.
+pub fn junit(results: &[TestResult]) -> String {
+    let failures = results.iter().filter(|result| !result.passed()).count();
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites tests=\"{}\" failures=\"{failures}\" time=\"{:.3}\">\n",
+        results.len(),
+        total_seconds(results.iter())
+    );
+
+    let mut files: Vec<&str> = results.iter().map(|result| result.file.as_str()).collect();
+    files.dedup();
+    for file in files {
+        let suite: Vec<&TestResult> = results
+            .iter()
+            .filter(|result| result.file == file)
+            .collect();
+        let suite_failures = suite.iter().filter(|result| !result.passed()).count();
+        xml += &format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{suite_failures}\" time=\"{:.3}\">\n",
+            escape_xml(file),
+            suite.len(),
+            total_seconds(suite.iter().copied())
+        );
+        for result in suite {
+            let name = result.name.as_deref().unwrap_or(file);
+            xml += &format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(name),
+                escape_xml(file),
+                result.duration.as_secs_f64()
+            );
+            if result.passed() && result.output.is_empty() {
+                xml += "/>\n";
+                continue;
+            }
+
+            xml += ">\n";
+            if let Outcome::Failed(message) = &result.outcome {
+                let summary = message.lines().next().unwrap_or_default();
+                xml += &format!(
+                    "      <failure message=\"{}\">{}</failure>\n",
+                    escape_xml(summary),
+                    escape_xml(message)
+                );
+            }
+            if !result.output.is_empty() {
+                xml += &format!(
+                    "      <system-out>{}</system-out>\n",
+                    escape_xml(&result.output)
+                );
+            }
+            xml += "    </testcase>\n";
+        }
+        xml += "  </testsuite>\n";
+    }
+    xml += "</testsuites>\n";
+    xml
+}
+
+fn total_seconds<'r>(results: impl Iterator<Item = &'r TestResult>) -> f64 {
+    results.map(|result| result.duration.as_secs_f64()).sum()
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,16 +1,18 @@
 use std::fmt;
 
-use regex::{Match, Regex};
+use serde::Serialize;
+use unicode_xid::UnicodeXID;
 
 use crate::error::TokenizerError::{self, *};
 
-/// A Linger token.
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub struct Token(pub TokenValue, pub usize, pub usize);
+/// A Linger token, along with the line and column at which it begins, both starting at 1, and its
+/// byte offset in the source. Columns count characters rather than bytes.
+#[derive(Debug, PartialEq, PartialOrd, Clone, Serialize)]
+pub struct Token(pub TokenValue, pub usize, pub usize, pub usize);
 
 /// A Linger token value. This is an enum which represents the type of the
 /// token along with any associated data with that type.
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Serialize)]
 #[allow(non_camel_case_types)]
 pub enum TokenValue {
     ID(String),
@@ -27,6 +29,7 @@ pub enum TokenValue {
     L_SQUARE_BRACKET,
     R_SQUARE_BRACKET,
     SEMICOLON,
+    COLON,
     QUOTE,
     COMMA,
     THIN_ARROW,
@@ -34,12 +37,33 @@ pub enum TokenValue {
     DOUBLE_PLUS,
     DOUBLE_MINUS,
     DOT,
+    DOUBLE_DOT,
+    ELLIPSIS,
+    TRIVIA(Trivia),
+}
+
+/// Source text which has no meaning to the interpreter but which tooling such as the formatter
+/// needs to preserve. Trivia tokens are only produced by [tokenize_with_trivia].
+#[derive(Debug, PartialEq, PartialOrd, Clone, Serialize)]
+pub enum Trivia {
+    /// A `//` comment on its own line, without the leading slashes.
+    Comment(String),
+    /// A `//` comment which follows other tokens on the same line, without the leading slashes.
+    TrailingComment(String),
+    /// A `///` comment on its own line, without the leading slashes and the space after them. Doc
+    /// comments directly before a procedure document it.
+    DocComment(String),
+    /// A `/* */` comment, without its delimiters. Block comments may span multiple lines and may
+    /// be nested.
+    BlockComment(String),
+    /// A line containing only whitespace.
+    BlankLine,
 }
 
 /// An operator. This enum represents all of the valid operators in the Linger
 /// programming language. The variants of this enum are the associated data for
 /// the [OP TokenValue](TokenValue::OP) variant.
-#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize)]
 pub enum Operator {
     Plus,
     Minus,
@@ -61,13 +85,13 @@ pub enum Operator {
     PostDecrement,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize)]
 pub enum AssignOp {
     Plus,
     Minus,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize)]
 pub enum Keyword {
     If,
     Else,
@@ -82,250 +106,435 @@ pub enum Keyword {
     Continue,
     For,
     Nil,
+    Struct,
+    Enum,
+    Match,
 }
 
-const WHITESPACE_REGEX: &str = r"[[:space:]]+";
-const ASSIGN_REGEX: &str = r"=";
-const THIN_ARROW_REGEX: &str = r"->";
-const EQ_REGEX: &str = r"==";
-const NE_REGEX: &str = r"!=";
-const LT_REGEX: &str = r"<";
-const GT_REGEX: &str = r">";
-const LTE_REGEX: &str = r"<=";
-const GTE_REGEX: &str = r">=";
-const ID_REGEX: &str = r"([a-zA-Z][a-zA-Z0-9_]*)\b";
-const NUM_REGEX: &str = r"\d*\.?\d+";
-const PLUS_REGEX: &str = r"\+";
-const MINUS_REGEX: &str = r"\-";
-const STAR_REGEX: &str = r"\*";
-const SLASH_REGEX: &str = r"/";
-const DOUBLE_SLASH_REGEX: &str = r"//";
-const DOUBLE_PLUS_REGEX: &str = r"\+\+";
-const DOUBLE_MINUS_REGEX: &str = r"\-\-";
-const MOD_REGEX: &str = "%";
-const LPAREN_REGEX: &str = r"\(";
-const RPAREN_REGEX: &str = r"\)";
-const L_CURLY_BRACKET_REGEX: &str = r"\{";
-const R_CURLY_BRACKET_REGEX: &str = r"\}";
-const L_SQUARE_BRACKET_REGEX: &str = r"\[";
-const R_SQUARE_BRACKET_REGEX: &str = r"\]";
-const SEMICOLON_REGEX: &str = ";";
-const COMMA_REGEX: &str = ",";
-const QUOTE_REGEX: &str = "\"";
-const LOGIC_OR_REGEX: &str = r"\|\|";
-const LOGIC_AND_REGEX: &str = "&&";
-const LOGIC_NOT_REGEX: &str = "!";
-const ASSIGNMENT_PLUS_REGEX: &str = r"\+=";
-const ASSIGNMENT_MINUS_REGEX: &str = r"\-=";
-const DOT_REGEX: &str = r"\.";
-
-/// Returns the [Tokens](Token) which make up the program `s`.
+/// Returns the [Tokens](Token) which make up the program `s`. The only [Trivia] tokens kept are
+/// the doc comments which document a procedure.
 pub fn tokenize(s: &str) -> Result<Vec<Token>, TokenizerError> {
-    let enumerated_lines = s.split("\n").enumerate();
-    let mut tokens: Vec<Token> = vec![];
-    for (line_num, line) in enumerated_lines {
-        let mut tokenized_line = tokenize_helper(line, line_num + 1, 1)?;
-        tokens.append(&mut tokenized_line)
-    }
+    let tokens = Lexer::new(s).tokenize()?;
+
+    // walk backwards so that each doc comment knows whether a procedure follows it
+    let mut documents_proc = false;
+    let mut tokens: Vec<Token> = tokens
+        .into_iter()
+        .rev()
+        .filter(|token| match &token.0 {
+            TokenValue::TRIVIA(Trivia::DocComment(_)) => documents_proc,
+            TokenValue::TRIVIA(_) => {
+                documents_proc = false;
+                false
+            }
+            value => {
+                documents_proc = *value == TokenValue::KW(Keyword::Proc);
+                true
+            }
+        })
+        .collect();
+    tokens.reverse();
     Ok(tokens)
 }
 
-/// Returns the [Tokens](Token) which make up the program `s`. This is a helper function which is
-/// wrapped by [tokenize]. This function also takes a line and column number which are passed to
-/// created token structures.
-fn tokenize_helper(s: &str, line_num: usize, col_num: usize) -> Result<Vec<Token>, TokenizerError> {
-    if s.len() == 0 {
-        return Ok(vec![]);
+/// Returns the [Tokens](Token) which make up the program `s`, along with [Trivia] tokens for its
+/// comments and blank lines.
+pub fn tokenize_with_trivia(s: &str) -> Result<Vec<Token>, TokenizerError> {
+    Lexer::new(s).tokenize()
+}
+
+/// Returns the line and column at which the token that cannot be tokenized begins, or `None` if
+/// `s` can be tokenized.
+pub fn error_position(s: &str) -> Option<(usize, usize)> {
+    let mut lexer = Lexer::new(s);
+    match lexer.run() {
+        Ok(()) => None,
+        Err(_) => Some((lexer.token_start.0, lexer.token_start.1)),
     }
+}
 
-    let (token_value_option, token_length) = get_token_value(s)?;
-    let token_value = match token_value_option {
-        Some(token) => token,
-        None => return tokenize_helper(&s[token_length..], line_num, col_num + token_length),
-    };
+/// A line, column and byte offset, in the order in which they appear in a [Token].
+type Position = (usize, usize, usize);
 
-    match token_value {
-        TokenValue::QUOTE => {
-            let s = &s[token_length..];
-            let mut string_token_content = String::new();
-            let mut enumerated_character_iter = s.chars().enumerate();
-            while let Some((index, char)) = enumerated_character_iter.next() {
-                match char {
-                    '"' => {
-                        let mut tokens = vec![Token(
-                            TokenValue::STR(string_token_content.to_string()),
-                            line_num,
-                            col_num,
-                        )];
-                        let mut rest_tokens = tokenize_helper(
-                            &s[index + 1..],
-                            line_num,
-                            // the "plus 2" is to account for the opening and closing quotes for the string literal
-                            col_num + string_token_content.len() + 2,
-                        )?;
-                        tokens.append(&mut rest_tokens);
-                        return Ok(tokens);
-                    }
-                    '\\' => match enumerated_character_iter.nth(0) {
-                        Some((_, escaped_char)) => match escaped_char {
-                            'n' => string_token_content.push('\n'),
-                            'r' => string_token_content.push('\r'),
-                            't' => string_token_content.push('\t'),
-                            '\\' => string_token_content.push('\\'),
-                            '0' => string_token_content.push('\0'),
-                            '"' => string_token_content.push('"'),
-                            '\'' => string_token_content.push('\''),
-                            c => return Err(InvalidEscapeSequence(c)),
-                        },
-                        None => return Err(UnterminatedStringLiteral),
-                    },
-                    _ => string_token_content.push(char),
+/// A single-pass lexer over the characters of a program, which always produces [Trivia] tokens.
+struct Lexer<'a> {
+    source: &'a str,
+    /// The byte offset of the next character.
+    offset: usize,
+    line: usize,
+    /// The column of the next character, counted in characters.
+    col: usize,
+    /// The byte offset of the first character of the current line.
+    line_start: usize,
+    /// Whether a token has ended on the current line.
+    line_has_tokens: bool,
+    /// The position of the token being lexed.
+    token_start: Position,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            offset: 0,
+            line: 1,
+            col: 1,
+            line_start: 0,
+            line_has_tokens: false,
+            token_start: (1, 1, 0),
+            tokens: vec![],
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>, TokenizerError> {
+        self.run()?;
+        Ok(self.tokens)
+    }
+
+    fn run(&mut self) -> Result<(), TokenizerError> {
+        while let Some(c) = self.peek() {
+            self.token_start = self.position();
+            match c {
+                '\n' => {
+                    self.end_line();
+                    self.bump();
+                }
+                c if is_whitespace(c) => {
+                    self.bump();
+                }
+                '"' if self.source[self.offset..].starts_with(TRIPLE_QUOTE) => {
+                    let s = self.multi_line_string()?;
+                    self.push(TokenValue::STR(s));
+                }
+                '"' => {
+                    let s = self.string()?;
+                    self.push(TokenValue::STR(s));
+                }
+                '/' if self.peek_second() == Some('/') => self.comment(),
+                '/' if self.peek_second() == Some('*') => self.block_comment()?,
+                c if c.is_xid_start() || c == '_' => {
+                    let value = self.identifier_or_keyword();
+                    self.push(value);
+                }
+                '.' if self.source[self.offset..].starts_with("...") => {
+                    self.advance(3);
+                    self.push(TokenValue::ELLIPSIS);
+                }
+                c if c.is_ascii_digit()
+                    || (c == '.' && self.peek_second().is_some_and(|c| c.is_ascii_digit())) =>
+                {
+                    let value = self.number();
+                    self.push(value);
                 }
+                c => match punctuation(c, self.peek_second()) {
+                    Some((value, len)) => {
+                        self.advance(len);
+                        self.push(value);
+                    }
+                    None => {
+                        let unknown_token = self.source[self.offset..]
+                            .split(is_whitespace)
+                            .next()
+                            .unwrap_or_default();
+                        return Err(UnknownToken(unknown_token.to_string()));
+                    }
+                },
             }
-            return Err(UnterminatedStringLiteral);
         }
-        TokenValue::DOUBLE_SLASH => return Ok(vec![]),
-        token_value => {
-            let mut tokens = vec![Token(token_value, line_num, col_num)];
-            let mut rest_tokens =
-                tokenize_helper(&s[token_length..], line_num, col_num + token_length)?;
-            tokens.append(&mut rest_tokens);
-            return Ok(tokens);
+        self.end_line();
+        Ok(())
+    }
+
+    fn position(&self) -> Position {
+        (self.line, self.col, self.offset)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.source[self.offset..].chars().nth(1)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        self.col += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+            self.line_start = self.offset;
+            self.line_has_tokens = false;
+        }
+        Some(c)
+    }
+
+    /// Consumes `len` bytes of ASCII characters other than line terminators.
+    fn advance(&mut self, len: usize) {
+        self.offset += len;
+        self.col += len;
+    }
+
+    /// Consumes characters for as long as `predicate` holds, returning them.
+    fn bump_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.offset;
+        while self.peek().is_some_and(&predicate) {
+            self.bump();
+        }
+        &self.source[start..self.offset]
+    }
+
+    /// Adds a token which begins at the start of the token being lexed.
+    fn push(&mut self, value: TokenValue) {
+        let (line, col, offset) = self.token_start;
+        self.tokens.push(Token(value, line, col, offset));
+        self.line_has_tokens = true;
+    }
+
+    /// Finishes the current line before its line terminator is consumed, or at the end of the
+    /// source. A line on which no token ends is a blank line.
+    fn end_line(&mut self) {
+        if !self.line_has_tokens {
+            self.token_start = (self.line, 1, self.line_start);
+            self.push(TokenValue::TRIVIA(Trivia::BlankLine));
+        }
+    }
+
+    /// Consumes a `//` or `///` comment up to the end of its line.
+    fn comment(&mut self) {
+        self.advance("//".len());
+        let comment = self.bump_while(|c| c != '\n').trim_end().to_string();
+        let trivia = match comment.strip_prefix('/') {
+            _ if self.line_has_tokens => Trivia::TrailingComment(comment),
+            // `////` begins an ordinary comment
+            Some(doc) if !doc.starts_with('/') => {
+                Trivia::DocComment(doc.strip_prefix(' ').unwrap_or(doc).to_string())
+            }
+            _ => Trivia::Comment(comment),
+        };
+        self.push(TokenValue::TRIVIA(trivia));
+    }
+
+    /// Consumes a `/* */` comment, which may span multiple lines and contain other block comments.
+    fn block_comment(&mut self) -> Result<(), TokenizerError> {
+        self.advance("/*".len());
+        let start = self.offset;
+        let mut depth = 1;
+        while depth > 0 {
+            if self.source[self.offset..].starts_with("/*") {
+                self.advance("/*".len());
+                depth += 1;
+            } else if self.source[self.offset..].starts_with("*/") {
+                self.advance("*/".len());
+                depth -= 1;
+            } else if self.bump().is_none() {
+                return Err(UnterminatedBlockComment);
+            }
+        }
+        let comment = self.source[start..self.offset - "*/".len()].to_string();
+        self.push(TokenValue::TRIVIA(Trivia::BlockComment(comment)));
+        Ok(())
+    }
+
+    /// Consumes a string literal, including its quotes, and returns its unescaped contents. String
+    /// literals in double quotes cannot span multiple lines.
+    fn string(&mut self) -> Result<String, TokenizerError> {
+        self.bump();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.escape_sequence()?),
+                Some('\n') | None => return Err(UnterminatedStringLiteral),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    /// Consumes a string literal in triple quotes, which may span multiple lines and contain
+    /// unescaped double quotes, and returns its unescaped contents. A line break just after the
+    /// opening quotes is not part of the string.
+    fn multi_line_string(&mut self) -> Result<String, TokenizerError> {
+        self.advance(TRIPLE_QUOTE.len());
+        if self.source[self.offset..].starts_with("\r\n") {
+            self.bump();
+        }
+        if self.peek() == Some('\n') {
+            self.bump();
+        }
+        let mut s = String::new();
+        loop {
+            if self.source[self.offset..].starts_with(TRIPLE_QUOTE) {
+                self.advance(TRIPLE_QUOTE.len());
+                return Ok(s);
+            }
+            match self.bump() {
+                Some('\\') => s.push(self.escape_sequence()?),
+                Some(c) => s.push(c),
+                None => return Err(UnterminatedStringLiteral),
+            }
+        }
+    }
+
+    /// Consumes the character after a backslash in a string literal, returning the character which
+    /// the escape sequence stands for.
+    fn escape_sequence(&mut self) -> Result<char, TokenizerError> {
+        match self.bump() {
+            Some('x') => self.ascii_escape(),
+            Some('u') => self.unicode_escape(),
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('\\') => Ok('\\'),
+            Some('0') => Ok('\0'),
+            Some('"') => Ok('"'),
+            Some('\'') => Ok('\''),
+            Some('\n') | None => Err(UnterminatedStringLiteral),
+            Some(c) => Err(InvalidEscapeSequence(c)),
         }
     }
-}
 
-/// Tries to get a token beginning at the start of `s`. On success, this function returns an option
-/// of a [Token] that is None in the case of whitespace, or Some(Token) in all other cases. If the
-/// beginning of `s` is not a known token, this function returns a [TokenizerError].
-fn get_token_value(s: &str) -> Result<(Option<TokenValue>, usize), TokenizerError> {
-    // WHITESPACE TOKEN
-    if let Some(mat) = find(WHITESPACE_REGEX, s) {
-        Ok((None, mat.end()))
-
-    // KEYWORDS
-    } else if let Some(mat) = find("if", s) {
-        Ok((Some(TokenValue::KW(Keyword::If)), mat.end()))
-    } else if let Some(mat) = find("else", s) {
-        Ok((Some(TokenValue::KW(Keyword::Else)), mat.end()))
-    } else if let Some(mat) = find("proc", s) {
-        Ok((Some(TokenValue::KW(Keyword::Proc)), mat.end()))
-    } else if let Some(mat) = find("let", s) {
-        Ok((Some(TokenValue::KW(Keyword::Let)), mat.end()))
-    } else if let Some(mat) = find("true", s) {
-        Ok((Some(TokenValue::KW(Keyword::True)), mat.end()))
-    } else if let Some(mat) = find("false", s) {
-        Ok((Some(TokenValue::KW(Keyword::False)), mat.end()))
-    } else if let Some(mat) = find("return", s) {
-        Ok((Some(TokenValue::KW(Keyword::Return)), mat.end()))
-    } else if let Some(mat) = find("while", s) {
-        Ok((Some(TokenValue::KW(Keyword::While)), mat.end()))
-    } else if let Some(mat) = find("break", s) {
-        Ok((Some(TokenValue::KW(Keyword::Break)), mat.end()))
-    } else if let Some(mat) = find("continue", s) {
-        Ok((Some(TokenValue::KW(Keyword::Continue)), mat.end()))
-    } else if let Some(mat) = find("for", s) {
-        Ok((Some(TokenValue::KW(Keyword::For)), mat.end()))
-    } else if let Some(mat) = find("const", s) {
-        Ok((Some(TokenValue::KW(Keyword::Const)), mat.end()))
-    } else if let Some(mat) = find("nil", s) {
-        Ok((Some(TokenValue::KW(Keyword::Nil)), mat.end()))
-
-    // TWO-CHARACTER TOKENS
-    } else if let Some(mat) = find(NE_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::Ne)), mat.end()))
-    } else if let Some(mat) = find(EQ_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::Eq)), mat.end()))
-    } else if let Some(mat) = find(LTE_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::LTE)), mat.end()))
-    } else if let Some(mat) = find(GTE_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::GTE)), mat.end()))
-    } else if let Some(mat) = find(LOGIC_AND_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::LogicAnd)), mat.end()))
-    } else if let Some(mat) = find(LOGIC_OR_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::LogicOr)), mat.end()))
-    } else if let Some(mat) = find(DOUBLE_SLASH_REGEX, s) {
-        Ok((Some(TokenValue::DOUBLE_SLASH), mat.end()))
-    } else if let Some(mat) = find(THIN_ARROW_REGEX, s) {
-        Ok((Some(TokenValue::THIN_ARROW), mat.end()))
-    } else if let Some(mat) = find(DOUBLE_PLUS_REGEX, s) {
-        Ok((Some(TokenValue::DOUBLE_PLUS), mat.end()))
-    } else if let Some(mat) = find(DOUBLE_MINUS_REGEX, s) {
-        Ok((Some(TokenValue::DOUBLE_MINUS), mat.end()))
-    } else if let Some(mat) = find(ASSIGNMENT_PLUS_REGEX, s) {
-        Ok((Some(TokenValue::ASSIGN_OP(AssignOp::Plus)), mat.end()))
-    } else if let Some(mat) = find(ASSIGNMENT_MINUS_REGEX, s) {
-        Ok((Some(TokenValue::ASSIGN_OP(AssignOp::Minus)), mat.end()))
-
-    // ONE-CHARACTER TOKENS
-    } else if let Some(mat) = find(ASSIGN_REGEX, s) {
-        Ok((Some(TokenValue::ASSIGN), mat.end()))
-    } else if let Some(mat) = find(LT_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::LT)), mat.end()))
-    } else if let Some(mat) = find(GT_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::GT)), mat.end()))
-    } else if let Some(mat) = find(STAR_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::Times)), mat.end()))
-    } else if let Some(mat) = find(MOD_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::Mod)), mat.end()))
-    } else if let Some(mat) = find(SLASH_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::Div)), mat.end()))
-    } else if let Some(mat) = find(PLUS_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::Plus)), mat.end()))
-    } else if let Some(mat) = find(MINUS_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::Minus)), mat.end()))
-    } else if let Some(mat) = find(LPAREN_REGEX, s) {
-        Ok((Some(TokenValue::LPAREN), mat.end()))
-    } else if let Some(mat) = find(RPAREN_REGEX, s) {
-        Ok((Some(TokenValue::RPAREN), mat.end()))
-    } else if let Some(mat) = find(L_CURLY_BRACKET_REGEX, s) {
-        Ok((Some(TokenValue::L_CURLY_BRACKET), mat.end()))
-    } else if let Some(mat) = find(R_CURLY_BRACKET_REGEX, s) {
-        Ok((Some(TokenValue::R_CURLY_BRACKET), mat.end()))
-    } else if let Some(mat) = find(L_SQUARE_BRACKET_REGEX, s) {
-        Ok((Some(TokenValue::L_SQUARE_BRACKET), mat.end()))
-    } else if let Some(mat) = find(R_SQUARE_BRACKET_REGEX, s) {
-        Ok((Some(TokenValue::R_SQUARE_BRACKET), mat.end()))
-    } else if let Some(mat) = find(SEMICOLON_REGEX, s) {
-        Ok((Some(TokenValue::SEMICOLON), mat.end()))
-    } else if let Some(mat) = find(COMMA_REGEX, s) {
-        Ok((Some(TokenValue::COMMA), mat.end()))
-    } else if let Some(mat) = find(QUOTE_REGEX, s) {
-        Ok((Some(TokenValue::QUOTE), mat.end()))
-    } else if let Some(mat) = find(LOGIC_NOT_REGEX, s) {
-        Ok((Some(TokenValue::OP(Operator::LogicNot)), mat.end()))
-
-    // VARIABLE-LENGTH TOKENS
-    } else if let Some(mat) = find(ID_REGEX, s) {
-        Ok((Some(TokenValue::ID(mat.as_str().to_string())), mat.end()))
-    } else if let Some(mat) = find(NUM_REGEX, s) {
-        Ok((
-            Some(TokenValue::NUM(mat.as_str().parse::<f64>().expect("a match with the NUM_REGEX should imply that the string slice can be parsed into am i64"))),
-            mat.end(),
-        ))
-    } else if let Some(mat) = find(DOT_REGEX, s) {
-        Ok((Some(TokenValue::DOT), mat.end()))
-
-    // THE ERROR CASE
-    } else {
-        Err(UnknownToken({
-            let mut split =
-                s.split(|c: char| str_to_regex(WHITESPACE_REGEX).is_match(c.to_string().as_str()));
-            let unknown_token = split.nth(0).expect("some non-whitespace text since whitespace would have been matched on the first branch of the if statement");
-            format!("{}", unknown_token).to_string()
-        }))
+    /// Consumes the two hexadecimal digits of a `\x` escape, which stands for an ASCII character.
+    fn ascii_escape(&mut self) -> Result<char, TokenizerError> {
+        let digits: String = self.source[self.offset..].chars().take(2).collect();
+        match u8::from_str_radix(digits.as_str(), 16) {
+            Ok(byte) if is_hex(digits.as_str()) && digits.len() == 2 && byte.is_ascii() => {
+                self.advance(2);
+                Ok(byte as char)
+            }
+            _ => Err(InvalidCharacterEscape(format!("\\x{digits}"))),
+        }
     }
+
+    /// Consumes the one to six bracketed hexadecimal digits of a `\u{...}` escape, which stands
+    /// for a Unicode scalar value.
+    fn unicode_escape(&mut self) -> Result<char, TokenizerError> {
+        let rest = &self.source[self.offset..];
+        let escape = match rest.find('}') {
+            Some(end) if rest.starts_with('{') && end <= 7 => &rest[..=end],
+            _ => return Err(InvalidCharacterEscape("\\u".to_string())),
+        };
+        let digits = &escape[1..escape.len() - 1];
+        match u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(c) if is_hex(digits) => {
+                self.advance(escape.len());
+                Ok(c)
+            }
+            _ => Err(InvalidCharacterEscape(format!("\\u{escape}"))),
+        }
+    }
+
+    /// Consumes an identifier, which is a keyword only if the whole identifier is one, so that
+    /// `iffy` is an identifier rather than `if` followed by `fy`. Identifiers follow the Unicode
+    /// XID rules, so `naïve` and `名前` are identifiers, except that they may also begin with an
+    /// underscore.
+    fn identifier_or_keyword(&mut self) -> TokenValue {
+        let name = self.bump_while(|c| c.is_xid_continue());
+        match keyword(name) {
+            Some(kw) => TokenValue::KW(kw),
+            None => TokenValue::ID(name.to_string()),
+        }
+    }
+
+    /// Consumes a number, which has an optional fractional part and may begin with the decimal
+    /// point. A trailing decimal point is not part of the number, so `1.` is `1` followed by `.`.
+    fn number(&mut self) -> TokenValue {
+        let start = self.offset;
+        self.bump_while(|c| c.is_ascii_digit());
+        if self.peek() == Some('.') && self.peek_second().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+            self.bump_while(|c| c.is_ascii_digit());
+        }
+        let n = self.source[start..self.offset]
+            .parse::<f64>()
+            .expect("digits with an optional fractional part should parse into an f64");
+        TokenValue::NUM(n)
+    }
+}
+
+const TRIPLE_QUOTE: &str = "\"\"\"";
+
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\x0B' | '\x0C' | '\r')
 }
 
-/// Takes a string and returns the corresponding [Regex].
-fn str_to_regex(s: &str) -> Regex {
-    return Regex::new(format!("^({s})").as_str())
-        .expect("strings to be valid regular expressions");
+fn is_hex(digits: &str) -> bool {
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit())
 }
 
-/// Checks if `s` starts with the regular expression represented by `re`.
-fn find<'a>(re: &'a str, s: &'a str) -> Option<Match<'a>> {
-    return str_to_regex(re).find(s);
+fn keyword(name: &str) -> Option<Keyword> {
+    let kw = match name {
+        "if" => Keyword::If,
+        "else" => Keyword::Else,
+        "proc" => Keyword::Proc,
+        "let" => Keyword::Let,
+        "const" => Keyword::Const,
+        "true" => Keyword::True,
+        "false" => Keyword::False,
+        "return" => Keyword::Return,
+        "while" => Keyword::While,
+        "break" => Keyword::Break,
+        "continue" => Keyword::Continue,
+        "for" => Keyword::For,
+        "nil" => Keyword::Nil,
+        "struct" => Keyword::Struct,
+        "enum" => Keyword::Enum,
+        "match" => Keyword::Match,
+        _ => return None,
+    };
+    Some(kw)
+}
+
+/// Returns the operator or punctuation token which begins with `c`, followed by `next`, along with
+/// its length in bytes.
+fn punctuation(c: char, next: Option<char>) -> Option<(TokenValue, usize)> {
+    let two_chars = match (c, next) {
+        ('!', Some('=')) => Some(TokenValue::OP(Operator::Ne)),
+        ('=', Some('=')) => Some(TokenValue::OP(Operator::Eq)),
+        ('<', Some('=')) => Some(TokenValue::OP(Operator::LTE)),
+        ('>', Some('=')) => Some(TokenValue::OP(Operator::GTE)),
+        ('&', Some('&')) => Some(TokenValue::OP(Operator::LogicAnd)),
+        ('|', Some('|')) => Some(TokenValue::OP(Operator::LogicOr)),
+        ('-', Some('>')) => Some(TokenValue::THIN_ARROW),
+        ('.', Some('.')) => Some(TokenValue::DOUBLE_DOT),
+        ('+', Some('+')) => Some(TokenValue::DOUBLE_PLUS),
+        ('-', Some('-')) => Some(TokenValue::DOUBLE_MINUS),
+        ('+', Some('=')) => Some(TokenValue::ASSIGN_OP(AssignOp::Plus)),
+        ('-', Some('=')) => Some(TokenValue::ASSIGN_OP(AssignOp::Minus)),
+        _ => None,
+    };
+    if let Some(value) = two_chars {
+        return Some((value, 2));
+    }
+
+    let value = match c {
+        '=' => TokenValue::ASSIGN,
+        '<' => TokenValue::OP(Operator::LT),
+        '>' => TokenValue::OP(Operator::GT),
+        '*' => TokenValue::OP(Operator::Times),
+        '%' => TokenValue::OP(Operator::Mod),
+        '/' => TokenValue::OP(Operator::Div),
+        '+' => TokenValue::OP(Operator::Plus),
+        '-' => TokenValue::OP(Operator::Minus),
+        '!' => TokenValue::OP(Operator::LogicNot),
+        '(' => TokenValue::LPAREN,
+        ')' => TokenValue::RPAREN,
+        '{' => TokenValue::L_CURLY_BRACKET,
+        '}' => TokenValue::R_CURLY_BRACKET,
+        '[' => TokenValue::L_SQUARE_BRACKET,
+        ']' => TokenValue::R_SQUARE_BRACKET,
+        ';' => TokenValue::SEMICOLON,
+        ':' => TokenValue::COLON,
+        ',' => TokenValue::COMMA,
+        '.' => TokenValue::DOT,
+        _ => return None,
+    };
+    Some((value, 1))
 }
 
 impl fmt::Display for AssignOp {
@@ -378,6 +587,9 @@ impl fmt::Display for Keyword {
             Keyword::For => write!(f, "for"),
             Keyword::Const => write!(f, "const"),
             Keyword::Nil => write!(f, "nil"),
+            Keyword::Struct => write!(f, "struct"),
+            Keyword::Enum => write!(f, "enum"),
+            Keyword::Match => write!(f, "match"),
         }
     }
 }
@@ -393,6 +605,7 @@ impl fmt::Display for TokenValue {
             TokenValue::L_CURLY_BRACKET => write!(f, "{{"),
             TokenValue::R_CURLY_BRACKET => write!(f, "}}"),
             TokenValue::SEMICOLON => write!(f, ";"),
+            TokenValue::COLON => write!(f, ":"),
             TokenValue::COMMA => write!(f, ","),
             TokenValue::OP(op) => write!(f, "{op}"),
             TokenValue::QUOTE => write!(f, "\""),
@@ -404,8 +617,24 @@ impl fmt::Display for TokenValue {
             TokenValue::DOUBLE_MINUS => write!(f, "--"),
             TokenValue::ASSIGN_OP(op) => write!(f, "{op}"),
             TokenValue::DOT => write!(f, "."),
+            TokenValue::DOUBLE_DOT => write!(f, ".."),
+            TokenValue::ELLIPSIS => write!(f, "..."),
             TokenValue::L_SQUARE_BRACKET => write!(f, "["),
             TokenValue::R_SQUARE_BRACKET => write!(f, "]"),
+            TokenValue::TRIVIA(trivia) => write!(f, "{trivia}"),
+        }
+    }
+}
+
+impl fmt::Display for Trivia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trivia::Comment(comment) => write!(f, "//{comment}"),
+            Trivia::TrailingComment(comment) => write!(f, "//{comment}"),
+            Trivia::DocComment(comment) if comment.is_empty() => write!(f, "///"),
+            Trivia::DocComment(comment) => write!(f, "/// {comment}"),
+            Trivia::BlockComment(comment) => write!(f, "/*{comment}*/"),
+            Trivia::BlankLine => write!(f, ""),
         }
     }
 }
@@ -0,0 +1,1147 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use serde::Serialize;
+
+use crate::{
+    error::TypeError,
+    lint::diverges,
+    parser::{
+        match_args, ArgSource, Builtin, Declarations, Pattern, Span, SugaredExpr, SugaredMatchArm,
+        SugaredParam, SugaredProcedure, SugaredStatement,
+    },
+    tokenizer::{AssignOp, Operator},
+};
+
+/// The type of a Linger value. Types are only known where a program is annotated or where they
+/// can be inferred, and everything else has the type [Any](Type::Any), which is compatible with
+/// every other type.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum Type {
+    Any,
+    Nil,
+    Num,
+    Bool,
+    Str,
+    /// A list whose elements all have the given type, written `list<T>`.
+    List(Box<Type>),
+    /// A procedure or lambda with the given parameter types and return type, written
+    /// `proc(T, U) -> R`.
+    Proc(Vec<Type>, Box<Type>),
+    /// An instance of the struct or enum with the given name.
+    Named(String),
+}
+
+impl Type {
+    /// Returns whether a value of type `other` can be used where a value of this type is expected.
+    pub fn accepts(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::List(element), Type::List(other_element)) => element.accepts(other_element),
+            (Type::Proc(params, ret), Type::Proc(other_params, other_ret)) => {
+                params.len() == other_params.len()
+                    && params
+                        .iter()
+                        .zip(other_params)
+                        .all(|(param, other_param)| other_param.accepts(param))
+                    && ret.accepts(other_ret)
+            }
+            (ty, other) => ty == other,
+        }
+    }
+
+    /// Returns the type of a value which is either of type `self` or of type `other`.
+    fn join(self, other: Type) -> Type {
+        match self == other {
+            true => self,
+            false => Type::Any,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Any => write!(f, "any"),
+            Type::Nil => write!(f, "nil"),
+            Type::Num => write!(f, "num"),
+            Type::Bool => write!(f, "bool"),
+            Type::Str => write!(f, "str"),
+            Type::List(element) => write!(f, "list<{element}>"),
+            Type::Proc(params, ret) => {
+                let params: Vec<String> = params.iter().map(|param| param.to_string()).collect();
+                write!(f, "proc({}) -> {ret}", params.join(", "))
+            }
+            Type::Named(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Checks the types of the procedures in `declarations` without running them, returning every
+/// mismatch and every `match` which does not cover all values, along with the position of the
+/// statement in which it was found, in the order in which they appear in the source.
+///
+/// Unannotated parameters and fields have the type [Any](Type::Any). The type of an
+/// unannotated variable is inferred from its initializer unless it is reassigned, and the return
+/// type of an unannotated procedure is inferred from its `return` statements when every path
+/// through it returns.
+pub fn typecheck(declarations: &Declarations) -> Vec<(Span, TypeError)> {
+    let Declarations {
+        structs,
+        enums,
+        procedures,
+    } = declarations;
+    let mut checker = Checker {
+        structs: structs
+            .iter()
+            .map(|strukt| {
+                let fields = strukt
+                    .fields
+                    .iter()
+                    .zip(&strukt.field_types)
+                    .map(|(name, ty)| (name.as_str(), ty.clone().unwrap_or(Type::Any)))
+                    .collect();
+                (strukt.name.as_str(), fields)
+            })
+            .collect(),
+        variants: enums
+            .iter()
+            .flat_map(|enumeration| {
+                enumeration.variants.iter().map(|variant| {
+                    let fields = variant
+                        .field_types
+                        .iter()
+                        .map(|ty| ty.clone().unwrap_or(Type::Any))
+                        .collect();
+                    (variant.name.as_str(), (enumeration.name.as_str(), fields))
+                })
+            })
+            .collect(),
+        enums: enums
+            .iter()
+            .map(|enumeration| {
+                let variants = enumeration
+                    .variants
+                    .iter()
+                    .map(|variant| variant.name.as_str())
+                    .collect();
+                (enumeration.name.as_str(), variants)
+            })
+            .collect(),
+        procedures: procedures
+            .iter()
+            .map(|proc| {
+                let params = proc
+                    .params
+                    .iter()
+                    .zip(&proc.param_types)
+                    .map(|(param, ty)| param_type(param, ty))
+                    .collect();
+                let ret = proc.return_type.clone().unwrap_or(Type::Any);
+                (proc.name.as_str(), Type::Proc(params, Box::new(ret)))
+            })
+            .collect(),
+        params: procedures
+            .iter()
+            .map(|proc| (proc.name.as_str(), proc.params.as_slice()))
+            .collect(),
+        scopes: vec![],
+        reassigned: HashSet::new(),
+        return_type: None,
+        returns: vec![],
+        span: Span { line: 1, col: 1 },
+        errors: vec![],
+    };
+
+    // the return types of unannotated procedures are inferred before any procedure is checked,
+    // so that calls to procedures defined later in the source are checked as well
+    for proc in procedures.iter().filter(|proc| proc.return_type.is_none()) {
+        checker.check_proc(proc);
+        let returns = std::mem::take(&mut checker.returns);
+        if diverges(&proc.body) {
+            if let Some(Type::Proc(_, ret)) = checker.procedures.get_mut(proc.name.as_str()) {
+                **ret = join_all(returns);
+            }
+        }
+    }
+    checker.errors.clear();
+
+    for proc in procedures {
+        checker.check_proc(proc);
+    }
+
+    let mut errors = checker.errors;
+    errors.sort_by_key(|(span, _)| *span);
+    errors
+}
+
+#[derive(Clone, Debug)]
+struct Binding {
+    name: String,
+    ty: Type,
+    /// Whether the type of the binding was written in the source rather than inferred.
+    annotated: bool,
+}
+
+struct Checker<'a> {
+    /// The name and type of each field of each struct.
+    structs: HashMap<&'a str, Vec<(&'a str, Type)>>,
+    /// The name of the enum and the type of each field of each variant.
+    variants: HashMap<&'a str, (&'a str, Vec<Type>)>,
+    /// The names of the variants of each enum, in the order in which they are declared.
+    enums: HashMap<&'a str, Vec<&'a str>>,
+    /// The type of each top-level procedure.
+    procedures: HashMap<&'a str, Type>,
+    /// The parameters of each top-level procedure.
+    params: HashMap<&'a str, &'a [SugaredParam]>,
+    /// The variables bound in each enclosing block, innermost last.
+    scopes: Vec<Vec<Binding>>,
+    /// The names of the variables which are assigned to anywhere in the current procedure.
+    reassigned: HashSet<String>,
+    /// The annotated return type of the innermost procedure being checked.
+    return_type: Option<Type>,
+    /// The types of the values returned by the innermost procedure or lambda being checked.
+    returns: Vec<Type>,
+    /// The position of the innermost statement being checked.
+    span: Span,
+    errors: Vec<(Span, TypeError)>,
+}
+
+impl<'a> Checker<'a> {
+    fn report(&mut self, error: TypeError) {
+        self.errors.push((self.span, error));
+    }
+
+    /// Reports a [Mismatch](TypeError::Mismatch) unless `actual` is accepted by `expected`.
+    fn expect(&mut self, expected: &Type, actual: &Type) {
+        if !expected.accepts(actual) {
+            self.report(TypeError::Mismatch(expected.clone(), actual.clone()));
+        }
+    }
+
+    fn check_proc(&mut self, proc: &SugaredProcedure) {
+        self.span = proc.span;
+        self.reassigned.clear();
+        collect_reassigned(&proc.body, &mut self.reassigned);
+        self.return_type = proc.return_type.clone();
+        self.returns.clear();
+        self.scopes.push(vec![]);
+        for (param, ty) in proc.params.iter().zip(&proc.param_types) {
+            self.check_param(param, ty);
+        }
+        self.check_statement(&proc.body);
+        self.scopes.pop();
+    }
+
+    /// Checks the default value of the parameter `param` with the annotation `ty` in the scope of
+    /// the parameters before it, and then adds the parameter to that scope.
+    fn check_param(&mut self, param: &SugaredParam, ty: &Option<Type>) {
+        if let Some(default) = &param.default {
+            let default_ty = self.check_expr(default);
+            if let Some(ty) = ty {
+                self.expect(ty, &default_ty);
+            }
+        }
+        if let (true, Some(ty)) = (param.rest, ty) {
+            self.expect(&Type::List(Box::new(Type::Any)), ty);
+        }
+        let binding = Binding {
+            name: param.name.to_string(),
+            ty: param_type(param, ty),
+            annotated: ty.is_some(),
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(binding);
+        }
+    }
+
+    fn declare(&mut self, name: &str, annotation: &Option<Type>, ty: Type) {
+        let binding = match annotation {
+            Some(annotation) => Binding {
+                name: name.to_string(),
+                ty: annotation.clone(),
+                annotated: true,
+            },
+            // the value of a variable which is reassigned may have a different type later on
+            None if self.reassigned.contains(name) => Binding {
+                name: name.to_string(),
+                ty: Type::Any,
+                annotated: false,
+            },
+            None => Binding {
+                name: name.to_string(),
+                ty,
+                annotated: false,
+            },
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(binding);
+        }
+    }
+
+    /// Returns the innermost binding of `name`, or `None` if `name` is not a local variable.
+    fn resolve(&self, name: &str) -> Option<&Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|binding| binding.name == name)
+    }
+
+    /// Checks that a value of type `ty` can be assigned to the variable `name`.
+    fn check_assignment(&mut self, name: &str, ty: Type) {
+        if let Some(Binding {
+            ty: expected,
+            annotated: true,
+            ..
+        }) = self.resolve(name)
+        {
+            let expected = expected.clone();
+            self.expect(&expected, &ty);
+        }
+    }
+
+    fn check_statement(&mut self, statement: &SugaredStatement) {
+        match statement {
+            SugaredStatement::Spanned(span, statement) => {
+                self.span = *span;
+                self.check_statement(statement);
+            }
+            SugaredStatement::Expr(expr) => {
+                self.check_expr(expr);
+            }
+            SugaredStatement::Let(name, annotation, expr)
+            | SugaredStatement::Const(name, annotation, expr) => {
+                let ty = self.check_expr(expr);
+                if let Some(annotation) = annotation {
+                    self.expect(annotation, &ty);
+                }
+                self.declare(name, annotation, ty);
+            }
+            SugaredStatement::Assign(name, expr) => {
+                let ty = self.check_expr(expr);
+                self.check_assignment(name, ty);
+            }
+            SugaredStatement::FieldAssign(name, fields, expr) => {
+                let ty = self.check_expr(expr);
+                let field_ty = fields
+                    .iter()
+                    .fold(self.var_type(name), |strukt, field| {
+                        self.field_type(strukt, field)
+                    });
+                self.expect(&field_ty, &ty);
+            }
+            SugaredStatement::OperatorAssignment(op, name, expr) => {
+                let operator = match op {
+                    AssignOp::Plus => Operator::Plus,
+                    AssignOp::Minus => Operator::Minus,
+                };
+                let var_ty = self.var_type(name);
+                let expr_ty = self.check_expr(expr);
+                let ty = self.check_binary(operator, var_ty, expr_ty);
+                self.check_assignment(name, ty);
+            }
+            SugaredStatement::Block(statements) => {
+                self.scopes.push(vec![]);
+                for statement in statements {
+                    self.check_statement(statement);
+                }
+                self.scopes.pop();
+            }
+            SugaredStatement::If(cond, then_block, else_ifs, else_block) => {
+                self.check_condition(cond);
+                self.check_statement(then_block);
+                for (else_if_cond, else_if_block) in else_ifs {
+                    self.check_condition(else_if_cond);
+                    self.check_statement(else_if_block);
+                }
+                if let Some(else_block) = else_block {
+                    self.check_statement(else_block);
+                }
+            }
+            SugaredStatement::While(cond, body) => {
+                self.check_condition(cond);
+                self.check_statement(body);
+            }
+            SugaredStatement::For(init, cond, update, body) => {
+                let span = self.span;
+                self.scopes.push(vec![]);
+                self.check_statement(init);
+                self.check_condition(cond);
+                self.check_statement(&SugaredStatement::Block(body.clone()));
+                self.span = span;
+                self.check_statement(update);
+                self.scopes.pop();
+            }
+            SugaredStatement::Return(expr) => {
+                let ty = match expr {
+                    Some(expr) => self.check_expr(expr),
+                    None => Type::Nil,
+                };
+                if let Some(return_type) = self.return_type.clone() {
+                    self.expect(&return_type, &ty);
+                }
+                self.returns.push(ty);
+            }
+            SugaredStatement::Break
+            | SugaredStatement::Continue
+            | SugaredStatement::Trivia(_)
+            | SugaredStatement::Error(_) => (),
+        }
+    }
+
+    fn check_condition(&mut self, cond: &SugaredExpr) {
+        let ty = self.check_expr(cond);
+        self.expect(&Type::Bool, &ty);
+    }
+
+    /// Returns the type of the variable `name`, which is either a local variable, the constructor
+    /// of a variant or a top-level procedure.
+    fn var_type(&self, name: &str) -> Type {
+        if let Some(binding) = self.resolve(name) {
+            return binding.ty.clone();
+        }
+        match (self.variants.get(name), self.procedures.get(name)) {
+            (Some((enumeration, fields)), _) if fields.is_empty() => {
+                Type::Named(enumeration.to_string())
+            }
+            (Some((enumeration, fields)), _) => Type::Proc(
+                fields.clone(),
+                Box::new(Type::Named(enumeration.to_string())),
+            ),
+            (None, Some(ty)) => ty.clone(),
+            (None, None) => Type::Any,
+        }
+    }
+
+    /// Returns the parameters of the top-level procedure `name`, unless `name` is a local variable
+    /// or the constructor of a variant.
+    fn proc_params(&self, name: &str) -> Option<&'a [SugaredParam]> {
+        if self.resolve(name).is_some() || self.variants.contains_key(name) {
+            return None;
+        }
+        self.params.get(name).copied()
+    }
+
+    /// Returns the type of the field `field` of a value of type `strukt`.
+    fn field_type(&mut self, strukt: Type, field: &str) -> Type {
+        let field_ty = match &strukt {
+            Type::Any => return Type::Any,
+            Type::Named(name) => self.structs.get(name.as_str()).and_then(|fields| {
+                fields
+                    .iter()
+                    .find(|(name, _)| *name == field)
+                    .map(|(_, ty)| ty.clone())
+            }),
+            _ => None,
+        };
+        match field_ty {
+            Some(ty) => ty,
+            None => {
+                self.report(TypeError::NoField(strukt, field.to_string()));
+                Type::Any
+            }
+        }
+    }
+
+    /// Returns the type of `expr`. An expression with a type error has the type
+    /// [Any](Type::Any), so that a single mistake is only reported once.
+    fn check_expr(&mut self, expr: &SugaredExpr) -> Type {
+        match expr {
+            SugaredExpr::Nil => Type::Nil,
+            SugaredExpr::Num(_) => Type::Num,
+            SugaredExpr::Bool(_) => Type::Bool,
+            SugaredExpr::Str(_) => Type::Str,
+            SugaredExpr::Var(name) => self.var_type(name),
+            SugaredExpr::Binary(op, lhs, rhs) => {
+                let lhs = self.check_expr(lhs);
+                let rhs = self.check_expr(rhs);
+                self.check_binary(*op, lhs, rhs)
+            }
+            SugaredExpr::Unary(op, operand) => {
+                let ty = self.check_expr(operand);
+                let expected = match op {
+                    Operator::LogicNot => Type::Bool,
+                    _ => Type::Num,
+                };
+                match expected.accepts(&ty) {
+                    true => expected,
+                    false => {
+                        self.report(TypeError::BadOperand(*op, ty));
+                        Type::Any
+                    }
+                }
+            }
+            SugaredExpr::PrimitiveCall(builtin, args) => {
+                let args: Vec<Type> = args
+                    .iter()
+                    .filter(|arg| !matches!(arg, SugaredExpr::Trivia(_)))
+                    .map(|arg| self.check_expr(arg))
+                    .collect();
+                self.check_builtin(*builtin, args)
+            }
+            SugaredExpr::Call(proc_expr, args) => {
+                let proc_ty = self.check_expr(proc_expr);
+                let params = match proc_expr.as_ref() {
+                    SugaredExpr::Var(name) => self.proc_params(name),
+                    _ => None,
+                };
+                let args = self.check_args(args);
+                self.check_call(proc_ty, params, args)
+            }
+            SugaredExpr::MethodCall(receiver, name, args) => {
+                let receiver = self.check_expr(receiver);
+                let mut args = self.check_args(args);
+                args.insert(0, (None, receiver));
+                match Builtin::from_name(name) {
+                    Some(builtin) => {
+                        let args = args.into_iter().map(|(_, ty)| ty).collect();
+                        self.check_builtin(builtin, args)
+                    }
+                    None => self.check_call(self.var_type(name), self.proc_params(name), args),
+                }
+            }
+            SugaredExpr::Lambda(_, params, body) => self.check_lambda(params, body),
+            SugaredExpr::Index(indexable, index) => {
+                let indexable = self.check_expr(indexable);
+                let index = self.check_expr(index);
+                self.expect(&Type::Num, &index);
+                match indexable {
+                    Type::List(element) => *element,
+                    Type::Str => Type::Str,
+                    Type::Any => Type::Any,
+                    ty => {
+                        self.report(TypeError::NotIndexable(ty));
+                        Type::Any
+                    }
+                }
+            }
+            SugaredExpr::StructLiteral(name, fields) => {
+                let values: Vec<(&str, Type)> = fields
+                    .iter()
+                    .map(|(field, value)| (field.as_str(), self.check_expr(value)))
+                    .collect();
+                let Some(declared) = self.structs.get(name.as_str()).cloned() else {
+                    return Type::Any; // an unknown struct is reported when the program is run
+                };
+                for (field, ty) in &values {
+                    match declared.iter().find(|(name, _)| name == field) {
+                        Some((_, expected)) => self.expect(expected, ty),
+                        None => self.report(TypeError::NoField(
+                            Type::Named(name.to_string()),
+                            field.to_string(),
+                        )),
+                    }
+                }
+                for (field, _) in &declared {
+                    if !values.iter().any(|(name, _)| name == field) {
+                        self.report(TypeError::MissingField(
+                            Type::Named(name.to_string()),
+                            field.to_string(),
+                        ));
+                    }
+                }
+                Type::Named(name.to_string())
+            }
+            SugaredExpr::Field(strukt, field) => {
+                let ty = self.check_expr(strukt);
+                self.field_type(ty, field)
+            }
+            SugaredExpr::Match(scrutinee, arms) => self.check_match(scrutinee, arms),
+            SugaredExpr::NamedArg(_, value) => self.check_expr(value),
+            SugaredExpr::Trivia(_) => Type::Any,
+        }
+    }
+
+    /// Returns the type of a `match`, which is the type of the values of its arms, and reports the
+    /// match if its patterns do not cover every value. Arms with a guard are not counted as
+    /// covering any value.
+    fn check_match(&mut self, scrutinee: &SugaredExpr, arms: &[SugaredMatchArm]) -> Type {
+        let ty = self.check_expr(scrutinee);
+        let span = self.span;
+        let mut types = vec![];
+        for arm in arms {
+            self.scopes.push(vec![]);
+            self.check_pattern(&arm.pattern, &ty);
+            if let Some(guard) = &arm.guard {
+                self.check_condition(guard);
+            }
+            let arm_ty = match &arm.body {
+                SugaredStatement::Expr(expr) => self.check_expr(expr),
+                body => {
+                    self.check_statement(body);
+                    Type::Any
+                }
+            };
+            types.push(arm_ty);
+            self.scopes.pop();
+            self.span = span;
+        }
+
+        let rows: Vec<Vec<Pat>> = arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| vec![self.resolve_pattern(&arm.pattern)])
+            .collect();
+        if let Some(mut witness) = self.uncovered(&rows, 1) {
+            self.report(TypeError::NonExhaustiveMatch(ty, witness.remove(0)));
+        }
+        join_all(types)
+    }
+
+    /// Checks that `pattern` can match a value of type `ty`, declaring the variables it binds.
+    fn check_pattern(&mut self, pattern: &Pattern, ty: &Type) {
+        match pattern {
+            Pattern::Wildcard => (),
+            Pattern::Binding(name) => match self.variants.get(name.as_str()) {
+                Some((enumeration, _)) => {
+                    let variant_ty = Type::Named(enumeration.to_string());
+                    self.expect(ty, &variant_ty);
+                }
+                None => self.declare(name, &None, ty.clone()),
+            },
+            Pattern::Nil => self.expect(ty, &Type::Nil),
+            Pattern::Num(_) => self.expect(ty, &Type::Num),
+            Pattern::Bool(_) => self.expect(ty, &Type::Bool),
+            Pattern::Str(_) => self.expect(ty, &Type::Str),
+            Pattern::List(elements, rest) => {
+                let element = match ty {
+                    Type::List(element) => (**element).clone(),
+                    _ => {
+                        self.expect(ty, &Type::List(Box::new(Type::Any)));
+                        Type::Any
+                    }
+                };
+                for pattern in elements {
+                    self.check_pattern(pattern, &element);
+                }
+                if let Some(rest) = rest {
+                    self.check_pattern(rest, &Type::List(Box::new(element)));
+                }
+            }
+            Pattern::Variant(name, patterns) => {
+                // an unknown variant or a pattern with the wrong number of fields is reported when
+                // the program is run
+                let field_types = match self.variants.get(name.as_str()).cloned() {
+                    Some((enumeration, fields)) => {
+                        self.expect(ty, &Type::Named(enumeration.to_string()));
+                        fields
+                    }
+                    None => vec![],
+                };
+                for (index, pattern) in patterns.iter().enumerate() {
+                    let field_ty = match field_types.len() == patterns.len() {
+                        true => field_types[index].clone(),
+                        false => Type::Any,
+                    };
+                    self.check_pattern(pattern, &field_ty);
+                }
+            }
+        }
+    }
+
+    /// Returns `pattern` with the names of variants without fields told apart from bindings.
+    fn resolve_pattern(&self, pattern: &Pattern) -> Pat {
+        match pattern {
+            Pattern::Wildcard => Pat::Wildcard,
+            Pattern::Binding(name) => match self.variants.contains_key(name.as_str()) {
+                true => Pat::Variant(name.to_string(), vec![]),
+                false => Pat::Wildcard,
+            },
+            Pattern::Bool(b) => Pat::Bool(*b),
+            Pattern::Nil | Pattern::Num(_) | Pattern::Str(_) => Pat::Literal,
+            Pattern::List(elements, rest) => Pat::List(
+                elements.iter().map(|p| self.resolve_pattern(p)).collect(),
+                rest.is_some(),
+            ),
+            Pattern::Variant(name, patterns) => Pat::Variant(
+                name.to_string(),
+                patterns.iter().map(|p| self.resolve_pattern(p)).collect(),
+            ),
+        }
+    }
+
+    /// Returns an example of a row of `width` values which is matched by none of `rows`, or `None`
+    /// if every row of values is matched by one of them.
+    ///
+    /// This is the usefulness algorithm used to check the exhaustiveness of pattern matching in
+    /// ML-like languages: the first column is split by the constructors which can appear in it,
+    /// and the rows which match each constructor are checked recursively. The values in a column
+    /// are assumed to have the type of its patterns, so that patterns for every variant of an
+    /// enum cover any value, even one of type [Any](Type::Any).
+    fn uncovered(&self, rows: &[Vec<Pat>], width: usize) -> Option<Vec<String>> {
+        if width == 0 {
+            return rows.is_empty().then(Vec::new);
+        }
+        let heads: Vec<&Pat> = rows
+            .iter()
+            .map(|row| &row[0])
+            .filter(|head| !matches!(head, Pat::Wildcard))
+            .collect();
+        let constructors = self.constructors(&heads);
+        let missing = constructors
+            .iter()
+            .find(|constructor| !heads.iter().any(|head| constructor.covered_by(head)));
+
+        match missing {
+            None if !constructors.is_empty() => constructors.iter().find_map(|constructor| {
+                let arity = constructor.arity();
+                let rows: Vec<Vec<Pat>> = rows
+                    .iter()
+                    .filter_map(|row| constructor.specialize(row))
+                    .collect();
+                let mut witness = self.uncovered(&rows, arity + width - 1)?;
+                let fields: Vec<String> = witness.drain(..arity).collect();
+                witness.insert(0, constructor.show(fields));
+                Some(witness)
+            }),
+            missing => {
+                // only the rows whose first pattern matches anything can match a value built with
+                // a missing constructor
+                let rows: Vec<Vec<Pat>> = rows
+                    .iter()
+                    .filter(|row| matches!(row[0], Pat::Wildcard))
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+                let mut witness = self.uncovered(&rows, width - 1)?;
+                let head = match missing {
+                    Some(constructor) => {
+                        constructor.show(vec!["_".to_string(); constructor.arity()])
+                    }
+                    None => "_".to_string(),
+                };
+                witness.insert(0, head);
+                Some(witness)
+            }
+        }
+    }
+
+    /// Returns every constructor of the values matched by `heads`, or no constructors if they
+    /// can be anything, such as numbers or a mix of lists and variants.
+    fn constructors(&self, heads: &[&Pat]) -> Vec<Constructor> {
+        match heads.first() {
+            Some(Pat::Bool(_)) if heads.iter().all(|head| matches!(head, Pat::Bool(_))) => {
+                vec![Constructor::Bool(true), Constructor::Bool(false)]
+            }
+            Some(Pat::Variant(name, _)) => {
+                let Some((enumeration, _)) = self.variants.get(name.as_str()) else {
+                    return vec![];
+                };
+                let same_enum = |head: &&Pat| match head {
+                    Pat::Variant(name, _) => matches!(
+                        self.variants.get(name.as_str()),
+                        Some((other, _)) if other == enumeration
+                    ),
+                    _ => false,
+                };
+                if !heads.iter().all(same_enum) {
+                    return vec![];
+                }
+                self.enums[enumeration]
+                    .iter()
+                    .map(|variant| {
+                        let arity = self.variants[variant].1.len();
+                        Constructor::Variant(variant.to_string(), arity)
+                    })
+                    .collect()
+            }
+            Some(Pat::List(..)) if heads.iter().all(|head| matches!(head, Pat::List(..))) => {
+                // lists longer than every pattern are only matched by patterns with a rest, so
+                // they can all be checked as a single open-ended length
+                let longest = heads
+                    .iter()
+                    .map(|head| match head {
+                        Pat::List(elements, _) => elements.len(),
+                        _ => 0,
+                    })
+                    .max()
+                    .unwrap_or(0);
+                (0..=longest)
+                    .map(|len| Constructor::List(len, false))
+                    .chain([Constructor::List(longest + 1, true)])
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Returns the name, if it is a named argument, and the type of each argument in `args`.
+    fn check_args<'e>(&mut self, args: &'e [SugaredExpr]) -> Vec<(Option<&'e str>, Type)> {
+        args.iter()
+            .filter(|arg| !matches!(arg, SugaredExpr::Trivia(_)))
+            .map(|arg| match arg {
+                SugaredExpr::NamedArg(name, value) => (Some(name.as_str()), self.check_expr(value)),
+                arg => (None, self.check_expr(arg)),
+            })
+            .collect()
+    }
+
+    /// Returns the type of the value returned by calling a value of type `proc_ty` with arguments
+    /// of the names and types in `args`. The arguments are matched to `params` if the value is a
+    /// top-level procedure, and are otherwise only checked if they are all positional.
+    fn check_call(
+        &mut self,
+        proc_ty: Type,
+        params: Option<&[SugaredParam]>,
+        args: Vec<(Option<&str>, Type)>,
+    ) -> Type {
+        match proc_ty {
+            Type::Proc(param_types, ret) => {
+                let (named, positional): (Vec<_>, Vec<_>) =
+                    args.into_iter().partition(|(name, _)| name.is_some());
+                let names: Vec<&str> = named.iter().filter_map(|(name, _)| *name).collect();
+                // a call whose arguments do not match the parameters is reported by the linter
+                let sources = match params {
+                    Some(params) => {
+                        match_args("", params, positional.len(), &names).unwrap_or_default()
+                    }
+                    None if names.is_empty() && positional.len() == param_types.len() => {
+                        (0..positional.len()).map(ArgSource::Positional).collect()
+                    }
+                    None => vec![],
+                };
+                for ((index, source), expected) in sources.into_iter().enumerate().zip(&param_types)
+                {
+                    match source {
+                        ArgSource::Positional(i) => self.expect(expected, &positional[i].1),
+                        ArgSource::Named(i) => self.expect(expected, &named[i].1),
+                        ArgSource::Rest => {
+                            if let Type::List(element) = expected {
+                                for (_, ty) in positional.iter().skip(index) {
+                                    self.expect(element, ty);
+                                }
+                            }
+                        }
+                        ArgSource::Default => (),
+                    }
+                }
+                *ret
+            }
+            Type::Any => Type::Any,
+            ty => {
+                self.report(TypeError::NotCallable(ty));
+                Type::Any
+            }
+        }
+    }
+
+    fn check_binary(&mut self, op: Operator, lhs: Type, rhs: Type) -> Type {
+        let both = |ty: &Type| ty.accepts(&lhs) && ty.accepts(&rhs);
+        let result = match op {
+            Operator::Plus => match (&lhs, &rhs) {
+                (Type::Any, Type::Any) => Some(Type::Any),
+                (Type::List(l), Type::List(r)) => {
+                    Some(Type::List(Box::new((**l).clone().join((**r).clone()))))
+                }
+                (Type::List(_), Type::Any) | (Type::Any, Type::List(_)) => {
+                    Some(Type::List(Box::new(Type::Any)))
+                }
+                (Type::Num | Type::Str, Type::Any) => Some(lhs.clone()),
+                (Type::Any, Type::Num | Type::Str) => Some(rhs.clone()),
+                (Type::Num, Type::Num) | (Type::Str, Type::Str) => Some(lhs.clone()),
+                _ => None,
+            },
+            Operator::Minus | Operator::Times | Operator::Div | Operator::Mod => {
+                both(&Type::Num).then_some(Type::Num)
+            }
+            Operator::LT | Operator::GT | Operator::LTE | Operator::GTE => {
+                both(&Type::Num).then_some(Type::Bool)
+            }
+            Operator::Eq | Operator::Ne => {
+                let named = matches!((&lhs, &rhs), (Type::Named(_), _) | (_, Type::Named(_)))
+                    && lhs.accepts(&rhs);
+                (both(&Type::Num) || both(&Type::Bool) || named).then_some(Type::Bool)
+            }
+            Operator::LogicAnd | Operator::LogicOr => both(&Type::Bool).then_some(Type::Bool),
+            _ => Some(Type::Any),
+        };
+        match result {
+            Some(ty) => ty,
+            None => {
+                self.report(TypeError::BadOperands(op, lhs, rhs));
+                Type::Any
+            }
+        }
+    }
+
+    fn check_builtin(&mut self, builtin: Builtin, args: Vec<Type>) -> Type {
+        let any_list = Type::List(Box::new(Type::Any));
+        let (params, ret) = match builtin {
+            Builtin::Print | Builtin::Eprint => return Type::Nil,
+            Builtin::List => {
+                let element = args.into_iter().reduce(Type::join).unwrap_or(Type::Any);
+                return Type::List(Box::new(element));
+            }
+            Builtin::IsEmpty => (vec![any_list], Type::Bool),
+            Builtin::IsNil => (vec![Type::Any], Type::Bool),
+            Builtin::Head => match args.as_slice() {
+                [Type::List(element)] => return (**element).clone(),
+                _ => (vec![any_list], Type::Any),
+            },
+            Builtin::Rest => match args.as_slice() {
+                [ty @ Type::List(_)] => return ty.clone(),
+                _ => (vec![any_list.clone()], any_list),
+            },
+            // these return `nil` when there is no line or variable
+            Builtin::ReadLine => (vec![], Type::Any),
+            Builtin::GetEnv => (vec![Type::Str], Type::Any),
+            Builtin::ReadFile => (vec![Type::Str], Type::Str),
+            Builtin::WriteFile | Builtin::AppendFile => (vec![Type::Str, Type::Str], Type::Nil),
+            Builtin::ListDir => (vec![Type::Str], Type::List(Box::new(Type::Str))),
+            Builtin::Args => (vec![], Type::List(Box::new(Type::Str))),
+            Builtin::Now | Builtin::MonotonicMs => (vec![], Type::Num),
+            Builtin::Exit => (vec![Type::Num], Type::Any),
+            Builtin::Assert => match args.len() {
+                2 => (vec![Type::Bool, Type::Str], Type::Nil),
+                _ => (vec![Type::Bool], Type::Nil),
+            },
+            Builtin::AssertEq => (vec![Type::Any, Type::Any], Type::Nil),
+            // the message of the error raised by the procedure
+            Builtin::AssertError => (vec![Type::Any], Type::Str),
+        };
+        if params.len() == args.len() {
+            for (param, arg) in params.iter().zip(&args) {
+                self.expect(param, arg);
+            }
+        }
+        ret
+    }
+
+    /// Returns the type of a lambda. The return type of a lambda whose body is an expression is
+    /// the type of the expression, and is otherwise inferred like that of a procedure.
+    fn check_lambda(&mut self, params: &[SugaredParam], body: &SugaredStatement) -> Type {
+        let return_type = self.return_type.take();
+        let returns = std::mem::take(&mut self.returns);
+        let span = self.span;
+        self.scopes.push(vec![]);
+        for param in params {
+            self.check_param(param, &None);
+        }
+
+        let ret = match body {
+            SugaredStatement::Expr(expr) => self.check_expr(expr),
+            body => {
+                self.check_statement(body);
+                match diverges(body) {
+                    true => join_all(std::mem::take(&mut self.returns)),
+                    false => Type::Any,
+                }
+            }
+        };
+
+        self.scopes.pop();
+        self.span = span;
+        self.returns = returns;
+        self.return_type = return_type;
+        let params = params.iter().map(|param| param_type(param, &None)).collect();
+        Type::Proc(params, Box::new(ret))
+    }
+}
+
+/// Returns the type of the parameter `param` with the annotation `ty`. A rest parameter without an
+/// annotation is a list of values of any type.
+fn param_type(param: &SugaredParam, ty: &Option<Type>) -> Type {
+    match ty {
+        Some(ty) => ty.clone(),
+        None if param.rest => Type::List(Box::new(Type::Any)),
+        None => Type::Any,
+    }
+}
+
+/// A [Pattern] as seen by the exhaustiveness check of a `match`, in which bindings and wildcards
+/// are the same, and literals other than booleans never cover all of their values.
+#[derive(Clone, Debug)]
+enum Pat {
+    Wildcard,
+    Literal,
+    Bool(bool),
+    /// A list pattern and whether it ends with a rest pattern.
+    List(Vec<Pat>, bool),
+    Variant(String, Vec<Pat>),
+}
+
+/// One of the ways of building a value which is told apart by patterns.
+#[derive(Debug)]
+enum Constructor {
+    Bool(bool),
+    /// A variant and the number of its fields.
+    Variant(String, usize),
+    /// A list of the given length, or of at least the given length if it is open-ended.
+    List(usize, bool),
+}
+
+impl Constructor {
+    fn arity(&self) -> usize {
+        match self {
+            Constructor::Bool(_) => 0,
+            Constructor::Variant(_, arity) | Constructor::List(arity, _) => *arity,
+        }
+    }
+
+    /// Returns whether the pattern `head` matches some values built with this constructor.
+    fn covered_by(&self, head: &Pat) -> bool {
+        match (self, head) {
+            (_, Pat::Wildcard) => true,
+            (Constructor::Bool(b), Pat::Bool(other)) => b == other,
+            (Constructor::Variant(name, _), Pat::Variant(other, _)) => name == other,
+            (Constructor::List(..), Pat::List(..)) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `row` with its first pattern replaced by the patterns of the fields of this
+    /// constructor, or `None` if the first pattern does not match values built with it.
+    fn specialize(&self, row: &[Pat]) -> Option<Vec<Pat>> {
+        let arity = self.arity();
+        let mut fields = match (self, &row[0]) {
+            (_, Pat::Wildcard) => vec![Pat::Wildcard; arity],
+            (Constructor::Bool(b), Pat::Bool(other)) if b == other => vec![],
+            // a pattern with the wrong number of fields is reported when the program is run
+            (Constructor::Variant(name, _), Pat::Variant(other, fields)) if name == other => {
+                match fields.len() == arity {
+                    true => fields.clone(),
+                    false => vec![Pat::Wildcard; arity],
+                }
+            }
+            (Constructor::List(len, _), Pat::List(elements, false)) if elements.len() == *len => {
+                elements.clone()
+            }
+            (Constructor::List(len, _), Pat::List(elements, true)) if elements.len() <= *len => {
+                let mut fields = elements.clone();
+                fields.resize(*len, Pat::Wildcard);
+                fields
+            }
+            _ => return None,
+        };
+        fields.extend_from_slice(&row[1..]);
+        Some(fields)
+    }
+
+    /// Returns how a value built with this constructor from `fields` is written.
+    fn show(&self, fields: Vec<String>) -> String {
+        match self {
+            Constructor::Bool(b) => b.to_string(),
+            Constructor::Variant(name, 0) => name.to_string(),
+            Constructor::Variant(name, _) => format!("{name}({})", fields.join(", ")),
+            Constructor::List(_, false) => format!("[{}]", fields.join(", ")),
+            Constructor::List(_, true) => {
+                let mut fields = fields;
+                fields.push("..".to_string());
+                format!("[{}]", fields.join(", "))
+            }
+        }
+    }
+}
+
+/// Returns the type of a value which has one of `types`.
+fn join_all(types: Vec<Type>) -> Type {
+    types.into_iter().reduce(Type::join).unwrap_or(Type::Any)
+}
+
+/// Adds the name of every variable which is assigned to in `statement` to `names`.
+fn collect_reassigned(statement: &SugaredStatement, names: &mut HashSet<String>) {
+    match statement {
+        SugaredStatement::Spanned(_, statement) => collect_reassigned(statement, names),
+        SugaredStatement::Assign(name, expr)
+        | SugaredStatement::OperatorAssignment(_, name, expr) => {
+            names.insert(name.to_string());
+            collect_reassigned_in_expr(expr, names);
+        }
+        SugaredStatement::Expr(expr)
+        | SugaredStatement::FieldAssign(_, _, expr)
+        | SugaredStatement::Let(_, _, expr)
+        | SugaredStatement::Const(_, _, expr)
+        | SugaredStatement::Return(Some(expr)) => collect_reassigned_in_expr(expr, names),
+        SugaredStatement::Block(statements) => {
+            for statement in statements {
+                collect_reassigned(statement, names);
+            }
+        }
+        SugaredStatement::If(cond, then_block, else_ifs, else_block) => {
+            collect_reassigned_in_expr(cond, names);
+            collect_reassigned(then_block, names);
+            for (else_if_cond, else_if_block) in else_ifs {
+                collect_reassigned_in_expr(else_if_cond, names);
+                collect_reassigned(else_if_block, names);
+            }
+            if let Some(else_block) = else_block {
+                collect_reassigned(else_block, names);
+            }
+        }
+        SugaredStatement::While(cond, body) => {
+            collect_reassigned_in_expr(cond, names);
+            collect_reassigned(body, names);
+        }
+        SugaredStatement::For(init, cond, update, body) => {
+            collect_reassigned(init, names);
+            collect_reassigned_in_expr(cond, names);
+            collect_reassigned(update, names);
+            for statement in body {
+                collect_reassigned(statement, names);
+            }
+        }
+        SugaredStatement::Break
+        | SugaredStatement::Continue
+        | SugaredStatement::Return(None)
+        | SugaredStatement::Trivia(_)
+        | SugaredStatement::Error(_) => (),
+    }
+}
+
+fn collect_reassigned_in_expr(expr: &SugaredExpr, names: &mut HashSet<String>) {
+    match expr {
+        SugaredExpr::Unary(
+            Operator::PreIncrement
+            | Operator::PostIncrement
+            | Operator::PreDecrement
+            | Operator::PostDecrement,
+            operand,
+        ) => match operand.as_ref() {
+            SugaredExpr::Var(name) => {
+                names.insert(name.to_string());
+            }
+            operand => collect_reassigned_in_expr(operand, names),
+        },
+        SugaredExpr::Unary(_, operand)
+        | SugaredExpr::Field(operand, _)
+        | SugaredExpr::NamedArg(_, operand) => collect_reassigned_in_expr(operand, names),
+        SugaredExpr::Binary(_, lhs, rhs) | SugaredExpr::Index(lhs, rhs) => {
+            collect_reassigned_in_expr(lhs, names);
+            collect_reassigned_in_expr(rhs, names);
+        }
+        SugaredExpr::PrimitiveCall(_, args) => {
+            for arg in args {
+                collect_reassigned_in_expr(arg, names);
+            }
+        }
+        SugaredExpr::Call(proc_expr, args) | SugaredExpr::MethodCall(proc_expr, _, args) => {
+            collect_reassigned_in_expr(proc_expr, names);
+            for arg in args {
+                collect_reassigned_in_expr(arg, names);
+            }
+        }
+        SugaredExpr::StructLiteral(_, fields) => {
+            for (_, value) in fields {
+                collect_reassigned_in_expr(value, names);
+            }
+        }
+        SugaredExpr::Lambda(_, _, body) => collect_reassigned(body, names),
+        SugaredExpr::Match(scrutinee, arms) => {
+            collect_reassigned_in_expr(scrutinee, names);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    collect_reassigned_in_expr(guard, names);
+                }
+                collect_reassigned(&arm.body, names);
+            }
+        }
+        SugaredExpr::Nil
+        | SugaredExpr::Num(_)
+        | SugaredExpr::Bool(_)
+        | SugaredExpr::Str(_)
+        | SugaredExpr::Var(_)
+        | SugaredExpr::Trivia(_) => (),
+    }
+}
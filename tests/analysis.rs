@@ -0,0 +1,258 @@
+use std::fs;
+
+use linger::{
+    analysis::{CompletionKind, Definition, DefinitionKind, Document, Severity, Symbol},
+    lint::Rule,
+    parser::Span,
+};
+
+fn file_name_to_document(s: &str) -> Document {
+    let source = fs::read_to_string(format!("test_programs/analysis/{}.ling", s))
+        .expect("test program should exist");
+    Document::new(source.as_str())
+}
+
+fn span(line: usize, col: usize) -> Span {
+    Span { line, col }
+}
+
+fn definition_span(document: &Document, line: usize, col: usize) -> Option<Span> {
+    document
+        .definition(span(line, col))
+        .map(|definition| definition.span)
+}
+
+#[test]
+fn no_problems() {
+    let document = file_name_to_document("definitions");
+
+    assert_eq!(document.problems(), []);
+}
+
+#[test]
+fn arity_warning() {
+    let document = file_name_to_document("arity");
+
+    let problems: Vec<_> = document
+        .problems()
+        .iter()
+        .map(|problem| (problem.span, problem.severity, problem.rule))
+        .collect();
+    assert_eq!(
+        problems,
+        vec![(span(2, 3), Severity::Warning, Some(Rule::ArgMismatch))]
+    );
+}
+
+#[test]
+fn parse_error() {
+    let document = file_name_to_document("parse_error");
+
+    let problems: Vec<_> = document
+        .problems()
+        .iter()
+        .map(|problem| (problem.span, problem.severity, problem.rule))
+        .collect();
+    assert_eq!(problems, vec![(span(2, 11), Severity::Error, None)]);
+}
+
+#[test]
+fn tokenizer_error() {
+    let document = file_name_to_document("tokenizer_error");
+
+    let problems: Vec<_> = document
+        .problems()
+        .iter()
+        .map(|problem| {
+            (
+                problem.span.line,
+                problem.severity,
+                problem.message.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        problems,
+        vec![(3, Severity::Error, "invalid escape sequence \"\\q\"")]
+    );
+}
+
+#[test]
+fn definition_of_proc() {
+    let document = file_name_to_document("definitions");
+
+    assert_eq!(
+        document.definition(span(3, 13)),
+        Some(Definition {
+            name: "add".to_string(),
+            kind: DefinitionKind::Proc(vec!["a".to_string(), "b".to_string()]),
+            span: span(16, 6),
+            doc: None,
+        })
+    );
+}
+
+#[test]
+fn definition_of_variables() {
+    let document = file_name_to_document("definitions");
+
+    assert_eq!(definition_span(&document, 3, 17), Some(span(2, 7)));
+    assert_eq!(definition_span(&document, 17, 10), Some(span(16, 10)));
+    // the name in a binding is its own definition
+    assert_eq!(definition_span(&document, 2, 7), Some(span(2, 7)));
+    // the end of a name is part of the name
+    assert_eq!(definition_span(&document, 3, 18), Some(span(2, 7)));
+    assert_eq!(definition_span(&document, 3, 3), None);
+}
+
+#[test]
+fn definition_respects_scopes() {
+    let document = file_name_to_document("definitions");
+
+    // shadowed in a block
+    assert_eq!(definition_span(&document, 6, 11), Some(span(5, 9)));
+    // the loop variable, and the outer variable after the block ends
+    assert_eq!(definition_span(&document, 9, 11), Some(span(8, 12)));
+    assert_eq!(definition_span(&document, 9, 15), Some(span(2, 7)));
+    // a lambda parameter, and a variable captured by the lambda
+    assert_eq!(definition_span(&document, 11, 18), Some(span(11, 12)));
+    assert_eq!(definition_span(&document, 11, 22), Some(span(3, 9)));
+    // a binding is not in scope in its own initializer
+    assert_eq!(definition_span(&document, 12, 11), Some(span(2, 7)));
+    assert_eq!(definition_span(&document, 13, 11), Some(span(12, 7)));
+    assert_eq!(definition_span(&document, 13, 9), Some(span(11, 7)));
+}
+
+#[test]
+fn hover() {
+    let document = file_name_to_document("definitions");
+
+    assert_eq!(
+        document.hover(span(3, 13)),
+        Some("proc add(a, b)".to_string())
+    );
+    assert_eq!(document.hover(span(3, 9)), Some("const y".to_string()));
+    assert_eq!(
+        document.hover(span(17, 10)),
+        Some("(parameter) a".to_string())
+    );
+    assert_eq!(
+        document.hover(span(6, 5)),
+        Some("(builtin) print".to_string())
+    );
+    assert_eq!(document.hover(span(1, 1)), None);
+}
+
+#[test]
+fn completions() {
+    let document = file_name_to_document("definitions");
+
+    let completions = document.completions(span(9, 5));
+    let variables: Vec<_> = completions
+        .iter()
+        .filter(|completion| completion.kind == CompletionKind::Variable)
+        .map(|completion| completion.label.as_str())
+        .collect();
+    assert_eq!(variables, vec!["x", "y", "i"]);
+    assert!(completions
+        .iter()
+        .any(|completion| completion.label == "add"
+            && completion.kind == CompletionKind::Proc
+            && completion.detail == "proc add(a, b)"));
+    assert!(completions
+        .iter()
+        .any(|completion| completion.label == "monotonic_ms"
+            && completion.kind == CompletionKind::Builtin));
+
+    let completions = document.completions(span(13, 3));
+    let variables: Vec<_> = completions
+        .iter()
+        .filter(|completion| completion.kind == CompletionKind::Variable)
+        .map(|completion| (completion.label.as_str(), completion.detail.as_str()))
+        .collect();
+    assert_eq!(
+        variables,
+        vec![("y", "const y"), ("f", "let f"), ("x", "let x")]
+    );
+}
+
+#[test]
+fn symbols() {
+    let document = file_name_to_document("definitions");
+
+    assert_eq!(
+        document.symbols(),
+        vec![
+            Symbol {
+                name: "main".to_string(),
+                detail: "proc main()".to_string(),
+                start: span(1, 1),
+                end: span(14, 2),
+                name_span: span(1, 6),
+            },
+            Symbol {
+                name: "add".to_string(),
+                detail: "proc add(a, b)".to_string(),
+                start: span(16, 1),
+                end: span(18, 2),
+                name_span: span(16, 6),
+            },
+        ]
+    );
+}
+
+#[test]
+fn every_parse_error() {
+    let document = file_name_to_document("parse_errors");
+
+    let problems: Vec<_> = document
+        .problems()
+        .iter()
+        .map(|problem| (problem.span, problem.severity))
+        .collect();
+    assert_eq!(
+        problems,
+        vec![
+            (span(2, 11), Severity::Error),
+            (span(7, 13), Severity::Error)
+        ]
+    );
+}
+
+#[test]
+fn definition_doc() {
+    let document = file_name_to_document("doc_comments");
+
+    let doc = |line, col| {
+        document
+            .definition(span(line, col))
+            .and_then(|definition| definition.doc)
+    };
+    assert_eq!(doc(7, 11), Some("Doubles `x`.".to_string()));
+    assert_eq!(doc(2, 6), Some("Doubles `x`.".to_string()));
+    assert_eq!(doc(6, 6), None);
+}
+
+#[test]
+fn unicode_columns() {
+    let document = file_name_to_document("unicode");
+
+    assert_eq!(definition_span(&document, 3, 16), Some(span(2, 9)));
+    assert_eq!(document.hover(span(3, 19)), Some("let café".to_string()));
+}
+
+#[test]
+fn type_annotations() {
+    let document = file_name_to_document("annotations");
+
+    assert_eq!(definition_span(&document, 2, 14), Some(span(1, 18)));
+    assert_eq!(definition_span(&document, 6, 40), Some(span(6, 30)));
+    assert_eq!(document.hover(span(6, 37)), Some("proc add(a, b)".to_string()));
+
+    let problems: Vec<_> = document
+        .problems()
+        .iter()
+        .map(|problem| (problem.span, problem.severity, problem.rule))
+        .collect();
+    assert_eq!(problems, vec![(span(7, 3), Severity::Error, None)]);
+}
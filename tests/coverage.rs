@@ -0,0 +1,85 @@
+use std::{env, fs};
+
+use assert_cmd::Command;
+use predicates::prelude::predicate::str::contains;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/coverage/{}.ling", s);
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn lcov() -> TestResult {
+    let path = env::temp_dir().join("linger-coverage-branches.info");
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(format!("--coverage={}", path.display()))
+        .arg(file_name_to_path("branches"));
+    cmd.assert()
+        .success()
+        .stdout("60")
+        .stderr("lines: 10/16 (62.5%)\nbranches: 6/12 (50.0%)\n");
+
+    let lcov = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    assert_eq!(
+        lcov,
+        "TN:\n\
+        SF:test_programs/coverage/branches.ling\n\
+        DA:2,1\n\
+        DA:3,1\n\
+        DA:4,3\n\
+        DA:6,1\n\
+        DA:7,0\n\
+        DA:9,1\n\
+        DA:10,0\n\
+        DA:12,1\n\
+        DA:16,3\n\
+        DA:17,1\n\
+        DA:19,1\n\
+        DA:21,0\n\
+        DA:23,1\n\
+        DA:28,0\n\
+        DA:29,0\n\
+        DA:31,0\n\
+        BRDA:3,0,0,1\n\
+        BRDA:3,0,1,0\n\
+        BRDA:6,0,0,0\n\
+        BRDA:6,0,1,1\n\
+        BRDA:9,0,0,0\n\
+        BRDA:9,0,1,1\n\
+        BRDA:16,0,0,1\n\
+        BRDA:16,0,1,1\n\
+        BRDA:16,0,2,0\n\
+        BRDA:16,0,3,1\n\
+        BRDA:28,0,0,-\n\
+        BRDA:28,0,1,-\n\
+        BRF:12\n\
+        BRH:6\n\
+        LF:16\n\
+        LH:10\n\
+        end_of_record\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn failing_program() -> TestResult {
+    let path = env::temp_dir().join("linger-coverage-error.info");
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(format!("--coverage={}", path.display()))
+        .arg(file_name_to_path("error"));
+    cmd.assert()
+        .failure()
+        .stderr(contains("lines: 3/4 (75.0%)\nbranches: 1/2 (50.0%)\n"));
+
+    let lcov = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    assert!(lcov.contains("DA:4,1\nDA:6,0\n"));
+    assert!(lcov.contains("BRDA:3,0,0,1\nBRDA:3,0,1,0\n"));
+
+    Ok(())
+}
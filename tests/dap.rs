@@ -0,0 +1,155 @@
+use assert_cmd::Command;
+use predicates::prelude::{predicate::str::contains, PredicateBooleanExt};
+use serde_json::{json, Value};
+
+const PROGRAM: &str = "test_programs/debugger/sum.ling";
+
+fn frame(message: Value) -> String {
+    let content = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{content}", content.len())
+}
+
+fn request(seq: u64, command: &str, arguments: Value) -> Value {
+    json!({ "seq": seq, "type": "request", "command": command, "arguments": arguments })
+}
+
+fn set_breakpoints(seq: u64, lines: &[u64]) -> Value {
+    let breakpoints: Vec<Value> = lines.iter().map(|line| json!({ "line": line })).collect();
+    request(
+        seq,
+        "setBreakpoints",
+        json!({ "source": { "path": PROGRAM }, "breakpoints": breakpoints }),
+    )
+}
+
+/// Returns the framed messages of a session which launches the `sum` debugger test program with
+/// `launch` arguments and breakpoints on `lines`, sends `requests` once the program has started,
+/// and then disconnects.
+fn session(launch: Value, lines: &[u64], requests: Vec<Value>) -> String {
+    let mut arguments = json!({ "program": PROGRAM });
+    if let (Some(arguments), Some(launch)) = (arguments.as_object_mut(), launch.as_object()) {
+        arguments.extend(launch.clone());
+    }
+    let mut messages = vec![
+        request(1, "initialize", json!({ "adapterID": "linger" })),
+        request(2, "launch", arguments),
+        set_breakpoints(3, lines),
+        request(4, "configurationDone", json!({})),
+    ];
+    messages.extend(requests);
+    messages.push(request(99, "disconnect", json!({})));
+    messages.into_iter().map(frame).collect()
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn run_to_end() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-dap")?;
+
+    cmd.write_stdin(session(json!({}), &[], vec![]));
+    cmd.assert()
+        .success()
+        .stdout(contains(r#""supportsConfigurationDoneRequest":true"#))
+        .stdout(contains(r#""event":"initialized""#))
+        .stdout(contains(r#""body":{"category":"stdout","output":"6"},"event":"output""#))
+        .stdout(contains(r#""body":{"exitCode":0},"event":"exited""#))
+        .stdout(contains(r#""event":"terminated""#))
+        .stdout(contains(r#""command":"disconnect","request_seq":99"#));
+
+    Ok(())
+}
+
+#[test]
+fn breakpoint() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-dap")?;
+
+    cmd.write_stdin(session(
+        json!({}),
+        &[10],
+        vec![
+            request(5, "stackTrace", json!({ "threadId": 1 })),
+            request(6, "scopes", json!({ "frameId": 0 })),
+            request(7, "variables", json!({ "variablesReference": 1 })),
+            set_breakpoints(8, &[]),
+            request(9, "continue", json!({ "threadId": 1 })),
+        ],
+    ));
+    cmd.assert()
+        .success()
+        .stdout(contains(r#""breakpoints":[{"line":10,"verified":true}]"#))
+        .stdout(contains(r#""reason":"breakpoint","threadId":1},"event":"stopped""#))
+        .stdout(contains(r#"{"column":1,"id":0,"line":10,"name":"add","#))
+        .stdout(contains(r#"{"column":1,"id":1,"line":4,"name":"main","#))
+        .stdout(contains(r#""scopes":[{"expensive":false,"name":"Locals","variablesReference":1}]"#))
+        .stdout(contains(r#"{"name":"a","value":"0","variablesReference":0}"#))
+        .stdout(contains(r#""output":"6""#));
+
+    Ok(())
+}
+
+#[test]
+fn step() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-dap")?;
+
+    cmd.write_stdin(session(
+        json!({ "stopOnEntry": true }),
+        &[],
+        vec![
+            request(5, "next", json!({ "threadId": 1 })),
+            request(6, "next", json!({ "threadId": 1 })),
+            request(7, "stepIn", json!({ "threadId": 1 })),
+            request(8, "stackTrace", json!({ "threadId": 1 })),
+            request(9, "stepOut", json!({ "threadId": 1 })),
+            request(10, "stackTrace", json!({ "threadId": 1 })),
+        ],
+    ));
+    cmd.assert()
+        .success()
+        .stdout(contains(r#""reason":"entry""#))
+        .stdout(contains(r#""reason":"step""#))
+        .stdout(contains(r#""stackFrames":[{"column":1,"id":0,"line":10,"name":"add","#))
+        .stdout(contains(r#""stackFrames":[{"column":1,"id":0,"line":4,"name":"main","#))
+        .stdout(contains(r#""event":"exited""#).not());
+
+    Ok(())
+}
+
+#[test]
+fn evaluate() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-dap")?;
+
+    cmd.write_stdin(session(
+        json!({}),
+        &[11],
+        vec![
+            request(5, "evaluate", json!({ "expression": "sum * 10", "frameId": 0 })),
+            request(6, "evaluate", json!({ "expression": "nope", "frameId": 0 })),
+        ],
+    ));
+    cmd.assert()
+        .success()
+        .stdout(contains(r#""body":{"result":"10","variablesReference":0}"#))
+        .stdout(contains(r#""message":"unknown variable \"nope\"""#))
+        .stdout(contains(r#""success":false"#));
+
+    Ok(())
+}
+
+#[test]
+fn launch_missing_program() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-dap")?;
+
+    let messages = [
+        request(1, "initialize", json!({})),
+        request(2, "launch", json!({ "program": "test_programs/debugger/missing.ling" })),
+        request(3, "disconnect", json!({})),
+    ];
+    cmd.write_stdin(messages.into_iter().map(frame).collect::<String>());
+    cmd.assert()
+        .success()
+        .stdout(contains(r#""command":"launch","message":"error opening"#))
+        .stdout(contains(r#""success":false"#));
+
+    Ok(())
+}
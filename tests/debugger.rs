@@ -0,0 +1,106 @@
+use assert_cmd::Command;
+use predicates::prelude::{predicate::str::contains, PredicateBooleanExt};
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/debugger/{}.ling", s);
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn pause_on_entry() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("debug").arg(file_name_to_path("sum"));
+    cmd.write_stdin("continue\n");
+    cmd.assert()
+        .success()
+        .stdout(contains("test_programs/debugger/sum.ling:2 in main\n2\tlet total = 0;\n"))
+        .stdout(contains("(linger) 6"));
+
+    Ok(())
+}
+
+#[test]
+fn breakpoint() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("debug").arg(file_name_to_path("sum"));
+    cmd.write_stdin("break 10\ncontinue\nlocals\nbacktrace\ndelete 10\ncontinue\n");
+    cmd.assert()
+        .success()
+        .stdout(contains("breakpoint set on line 10"))
+        .stdout(contains("breakpoint at test_programs/debugger/sum.ling:10 in add"))
+        .stdout(contains("a = 0\nb = 1\n"))
+        .stdout(contains("#0 add at line 10\n#1 main at line 4\n"))
+        .stdout(contains("breakpoint removed from line 10"))
+        .stdout(contains("(linger) 6"));
+
+    Ok(())
+}
+
+#[test]
+fn step() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("debug").arg(file_name_to_path("sum"));
+    cmd.write_stdin("next\nnext\nstep\nstep\nfinish\nquit\n");
+    cmd.assert().success().stdout(
+        "test_programs/debugger/sum.ling:2 in main\n\
+        2\tlet total = 0;\n\
+        (linger) test_programs/debugger/sum.ling:3 in main\n\
+        3\tfor (let i = 1; i < 4; i += 1) {\n\
+        (linger) test_programs/debugger/sum.ling:4 in main\n\
+        4\ttotal = add(total, i);\n\
+        (linger) test_programs/debugger/sum.ling:10 in add\n\
+        10\tconst sum = a + b;\n\
+        (linger) test_programs/debugger/sum.ling:11 in add\n\
+        11\treturn sum;\n\
+        (linger) test_programs/debugger/sum.ling:4 in main\n\
+        4\ttotal = add(total, i);\n\
+        (linger) ",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn step_over_call() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("debug").arg(file_name_to_path("sum"));
+    cmd.write_stdin("break 4\ncontinue\nnext\nprint total\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(contains("in add").not())
+        .stdout(contains("(linger) 1\n"));
+
+    Ok(())
+}
+
+#[test]
+fn evaluate() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("debug").arg(file_name_to_path("sum"));
+    cmd.write_stdin("break 11\ncontinue\nprint sum * 10\nprint \"a\" + \"b\"\nprint nope\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(contains("(linger) 10\n"))
+        .stdout(contains("(linger) \"ab\"\n"))
+        .stdout(contains("(linger) error: unknown variable \"nope\"\n"));
+
+    Ok(())
+}
+
+#[test]
+fn missing_file() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("debug");
+    cmd.assert()
+        .code(1)
+        .stderr(contains("usage:").and(contains("error opening").not()));
+
+    Ok(())
+}
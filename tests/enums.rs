@@ -0,0 +1,206 @@
+use std::{fs, process::Command};
+
+use assert_cmd::prelude::*;
+use linger::{
+    error::{ParseError, RuntimeError, TypeError},
+    interpreter::Value,
+    parser::{parse_declarations, Span},
+    tokenizer::tokenize,
+    typecheck::{typecheck, Type},
+};
+use predicates::prelude::predicate::str::starts_with;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/enums/{}.ling", s);
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn construct() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("construct"));
+    cmd.assert()
+        .success()
+        .stdout("Circle(2) Rect(3, 4) Empty Label(\"ada\")");
+
+    Ok(())
+}
+
+#[test]
+fn equality() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("equality"));
+    cmd.assert().success().stdout("true false true true");
+
+    Ok(())
+}
+
+#[test]
+fn literal_patterns() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("literal_patterns"));
+    cmd.assert()
+        .success()
+        .stdout("zero minus one a string yes nothing something else");
+
+    Ok(())
+}
+
+#[test]
+fn variable_patterns() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("variable_patterns"));
+    cmd.assert().success().stdout("42 ignored");
+
+    Ok(())
+}
+
+#[test]
+fn list_patterns() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("list_patterns"));
+    cmd.assert().success().stdout("10 empty one two many");
+
+    Ok(())
+}
+
+#[test]
+fn variant_patterns() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("variant_patterns"));
+    cmd.assert().success().stdout("12 12 0 1");
+
+    Ok(())
+}
+
+#[test]
+fn guards() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("guards"));
+    cmd.assert().success().stdout("negative zero even odd");
+
+    Ok(())
+}
+
+#[test]
+fn statement_match() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("statement_match"));
+    cmd.assert().success().stdout("3 negative");
+
+    Ok(())
+}
+
+#[test]
+fn formatted() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("fmt")
+        .arg("--check")
+        .arg(file_name_to_path("formatted"));
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn err_no_match() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-no_match"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        RuntimeError::NoMatch(Box::new(Value::Num(3.0))).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_unknown_variant() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-unknown_variant"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        RuntimeError::UnknownVariant("Circle".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_variant_arity() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-variant_arity"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        RuntimeError::VariantArity("Rect".to_string(), 2, 1).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_multiple_same_named_variants() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-multiple_same_named_variants"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        ParseError::MultipleSameNamedVariants("Square".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn typed() {
+    let source = fs::read_to_string(file_name_to_path("typed")).expect("test program should exist");
+    let tokens = tokenize(source.as_str()).expect("test program should tokenize");
+    let declarations = parse_declarations(tokens.as_slice()).expect("test program should parse");
+    let shape = Type::Named("Shape".to_string());
+    let non_exhaustive =
+        |ty: Type, missing: &str| TypeError::NonExhaustiveMatch(ty, missing.to_string());
+
+    assert_eq!(
+        typecheck(&declarations),
+        vec![
+            (
+                Span { line: 7, col: 3 },
+                TypeError::Mismatch(Type::Str, Type::Num)
+            ),
+            (
+                Span { line: 8, col: 3 },
+                non_exhaustive(shape.clone(), "Rect(_, _)")
+            ),
+            (
+                Span { line: 9, col: 3 },
+                TypeError::Mismatch(Type::Num, Type::Str)
+            ),
+            (
+                Span { line: 10, col: 3 },
+                non_exhaustive(Type::Named("Answer".to_string()), "No")
+            ),
+            (
+                Span { line: 12, col: 3 },
+                non_exhaustive(Type::Bool, "false")
+            ),
+            (
+                Span { line: 14, col: 3 },
+                non_exhaustive(Type::List(Box::new(Type::Num)), "[_, _, ..]")
+            ),
+            (Span { line: 15, col: 3 }, non_exhaustive(Type::Num, "_")),
+            (
+                Span { line: 16, col: 3 },
+                TypeError::Mismatch(shape, Type::Num)
+            ),
+        ]
+    );
+}
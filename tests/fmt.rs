@@ -0,0 +1,144 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+};
+
+use assert_cmd::prelude::*;
+use linger::{
+    formatter::format_source,
+    parser::{parse_program, Program},
+    tokenizer::tokenize,
+};
+use predicates::prelude::predicate::str::contains;
+use regex::Regex;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/fmt/{}.ling", s);
+}
+
+fn linger_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir).expect("directory should be readable") {
+        let path = entry.expect("directory entry should be readable").path();
+        if path.is_dir() {
+            files.append(&mut linger_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "ling") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    return files;
+}
+
+/// Returns the parsed form of `source` without the positions of its statements, which are
+/// changed by formatting, or `None` if `source` is not a valid program.
+fn meaning(source: &str) -> Option<String> {
+    let program: Program = tokenize(source)
+        .ok()
+        .and_then(|tokens| parse_program(tokens.as_slice()).ok())?;
+    let span = Regex::new(r"Span \{ line: \d+, col: \d+ \}").unwrap();
+    Some(span.replace_all(&format!("{program:?}"), "Span").to_string())
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+/// The size of the stack of the main thread on Linux, which is where the `linger` binary runs.
+const MAIN_THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+#[test]
+fn format_unformatted() -> TestResult {
+    let unformatted = fs::read_to_string(file_name_to_path("unformatted"))?;
+    let formatted = fs::read_to_string(file_name_to_path("formatted"))?;
+
+    assert_eq!(format_source(unformatted.as_str()).unwrap(), formatted);
+
+    Ok(())
+}
+
+#[test]
+fn format_comments_within_statements() -> TestResult {
+    let unformatted = fs::read_to_string(file_name_to_path("unformatted_comments"))?;
+    let formatted = fs::read_to_string(file_name_to_path("formatted_comments"))?;
+
+    assert_eq!(format_source(unformatted.as_str()).unwrap(), formatted);
+
+    Ok(())
+}
+
+#[test]
+fn idempotent_over_test_programs() {
+    // some of the programs are nested as deeply as the parser allows, which needs the stack of a
+    // main thread rather than the smaller one of a test thread
+    let test = thread::Builder::new()
+        .stack_size(MAIN_THREAD_STACK_SIZE)
+        .spawn(|| {
+            for path in linger_files(Path::new("test_programs")) {
+                let source = fs::read_to_string(&path).expect("test program should be readable");
+                let formatted = match format_source(source.as_str()) {
+                    Ok(formatted) => formatted,
+                    Err(_) => continue, // programs with syntax errors cannot be formatted
+                };
+
+                assert_eq!(
+                    format_source(formatted.as_str()).unwrap(),
+                    formatted,
+                    "formatting {} is not idempotent",
+                    path.display()
+                );
+
+                assert_eq!(
+                    meaning(source.as_str()),
+                    meaning(formatted.as_str()),
+                    "formatting {} changed its meaning",
+                    path.display()
+                );
+            }
+        })
+        .expect("test thread should start");
+
+    test.join().expect("test thread should not panic");
+}
+
+#[test]
+fn examples_are_formatted() -> TestResult {
+    for path in linger_files(Path::new("examples")) {
+        let mut cmd = Command::cargo_bin("linger-core")?;
+
+        cmd.arg("fmt").arg("--check").arg(&path);
+        cmd.assert().success();
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fmt_in_place() -> TestResult {
+    let path = env::temp_dir().join(format!("linger-fmt-{}.ling", std::process::id()));
+    fs::copy(file_name_to_path("unformatted"), &path)?;
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("fmt").arg(&path);
+    cmd.assert().success();
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        fs::read_to_string(file_name_to_path("formatted"))?
+    );
+    fs::remove_file(path)?;
+
+    Ok(())
+}
+
+#[test]
+fn fmt_check() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+    cmd.arg("fmt").arg("--check").arg(file_name_to_path("formatted"));
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("linger-core")?;
+    cmd.arg("fmt").arg("--check").arg(file_name_to_path("unformatted"));
+    cmd.assert().failure().stderr(contains("is not formatted"));
+
+    Ok(())
+}
@@ -0,0 +1,303 @@
+//! Runs every Linger program in `test_programs` which declares its expected output, and compares
+//! its output and exit code with the expected ones exactly.
+//!
+//! A program declares what it expects either in sidecar files next to it or in comments at the
+//! top of the file. `prog.out` and `prog.err` hold the expected output and error streams, and
+//! `prog.code` holds the expected exit code. At the top of the file, before any other line:
+//!
+//! ```text
+//! // args: check               arguments passed before the path of the program
+//! // stdin: a line of input    a line written to the input stream of the program
+//! // exit: 1                   the expected exit code, which is 0 if it is left out
+//! // stdout: a line of output  a line of the expected output stream
+//! // stderr: a line of error   a line of the expected error stream
+//! ```
+//!
+//! The lines of an expected stream are joined with newlines, so a stream which ends with a
+//! newline ends with an empty `stdout:` or `stderr:` comment. A stream which is neither in a
+//! sidecar file nor in the comments is expected to be empty.
+//!
+//! Set `LINGER_BLESS=1` to write the actual output of each program as its expected output
+//! instead, keeping it in the comments or sidecar files which the program already uses.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use assert_cmd::Command;
+
+const BLESS_VAR: &str = "LINGER_BLESS";
+
+/// The keys of the comments which declare what a program expects.
+const EXPECTATION_KEYS: [&str; 3] = ["exit", "stdout", "stderr"];
+
+/// A program along with how to run it and what it is expected to do.
+struct Golden {
+    path: PathBuf,
+    source: String,
+    /// The `key: value` comments at the top of the file which declare how to run the program and
+    /// what it expects, in order.
+    header: Vec<(String, String)>,
+}
+
+/// The output and exit code of a run.
+#[derive(PartialEq, Eq)]
+struct Output {
+    stdout: String,
+    stderr: String,
+    code: i32,
+}
+
+impl Golden {
+    fn load(path: PathBuf) -> io::Result<Self> {
+        let source = fs::read_to_string(&path)?;
+        let header = source
+            .lines()
+            .take_while(|line| line.starts_with("//"))
+            .filter_map(directive)
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        Ok(Self {
+            path,
+            source,
+            header,
+        })
+    }
+
+    fn sidecar(&self, extension: &str) -> PathBuf {
+        self.path.with_extension(extension)
+    }
+
+    fn values<'g>(&'g self, key: &'g str) -> impl Iterator<Item = &'g str> {
+        self.header
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the program declares any expectation, so that it should be checked.
+    fn is_golden(&self) -> bool {
+        ["out", "err", "code"]
+            .iter()
+            .any(|extension| self.sidecar(extension).exists())
+            || EXPECTATION_KEYS
+                .iter()
+                .any(|key| self.values(key).next().is_some())
+    }
+
+    fn expected(&self) -> io::Result<Output> {
+        let stream = |extension: &str, key: &str| -> io::Result<String> {
+            match fs::read_to_string(self.sidecar(extension)) {
+                Ok(expected) => Ok(expected),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    Ok(self.values(key).collect::<Vec<&str>>().join("\n"))
+                }
+                Err(e) => Err(e),
+            }
+        };
+        let code = match fs::read_to_string(self.sidecar("code")) {
+            Ok(code) => Some(code),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let code = code
+            .as_deref()
+            .or(self.values("exit").last())
+            .unwrap_or("0");
+        Ok(Output {
+            stdout: stream("out", "stdout")?,
+            stderr: stream("err", "stderr")?,
+            code: code.trim().parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("bad exit code {code:?} in {}", self.path.display()),
+                )
+            })?,
+        })
+    }
+
+    fn run(&self) -> Result<Output, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("linger-core")?
+            .args(self.values("args").flat_map(|args| args.split_whitespace()))
+            .arg(&self.path)
+            .write_stdin(
+                self.values("stdin")
+                    .map(|line| format!("{line}\n"))
+                    .collect::<String>(),
+            )
+            .output()?;
+        Ok(Output {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            code: output.status.code().unwrap_or(-1),
+        })
+    }
+
+    /// Writes `actual` as the expected output, in the sidecar files if the program has any and in
+    /// the comments at the top of the file otherwise. Returns whether anything changed.
+    fn bless(&self, actual: &Output) -> io::Result<bool> {
+        let uses_sidecars = ["out", "err", "code"]
+            .iter()
+            .any(|extension| self.sidecar(extension).exists());
+        let code_in_header = !uses_sidecars || self.values("exit").next().is_some();
+
+        // the expectations replace the old ones at the end of the comments at the top of the file
+        let header_len = self
+            .source
+            .lines()
+            .take_while(|line| line.starts_with("//"))
+            .count();
+        let mut source: Vec<String> = self
+            .source
+            .lines()
+            .take(header_len)
+            .filter(|line| !is_expectation(line))
+            .map(str::to_string)
+            .collect();
+        if code_in_header {
+            source.push(format!("// exit: {}", actual.code));
+        }
+        for (stream, key) in [(&actual.stdout, "stdout"), (&actual.stderr, "stderr")] {
+            if !uses_sidecars && !stream.is_empty() {
+                let lines = stream.split('\n');
+                source.extend(lines.map(|line| format!("// {key}: {line}").trim_end().to_string()));
+            }
+        }
+        source.extend(self.source.lines().skip(header_len).map(str::to_string));
+        let mut source = source.join("\n");
+        if self.source.ends_with('\n') {
+            source.push('\n');
+        }
+        let mut changed = write_if_changed(&self.path, Some(&source))?;
+
+        if uses_sidecars {
+            // the output stream always has a sidecar file so that the program is still checked,
+            // but an empty error stream and a successful exit do not need one
+            let code = format!("{}\n", actual.code);
+            let sidecars = [
+                ("out", &actual.stdout, true),
+                ("err", &actual.stderr, !actual.stderr.is_empty()),
+                ("code", &code, !code_in_header && actual.code != 0),
+            ];
+            for (extension, contents, needed) in sidecars {
+                let contents = Some(contents.as_str()).filter(|_| needed);
+                changed |= write_if_changed(&self.sidecar(extension), contents)?;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Returns the key and value of `line` if it is a comment which declares how to run a program or
+/// what it expects.
+fn directive(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.strip_prefix("//")?.trim_start().split_once(':')?;
+    match ["args", "stdin"].contains(&key) || EXPECTATION_KEYS.contains(&key) {
+        true => Some((key, value.strip_prefix(' ').unwrap_or(value))),
+        false => None,
+    }
+}
+
+fn is_expectation(line: &str) -> bool {
+    directive(line).is_some_and(|(key, _)| EXPECTATION_KEYS.contains(&key))
+}
+
+/// Writes `contents` to the file at `path`, or removes it if `contents` is `None`. Returns whether
+/// the file changed.
+fn write_if_changed(path: &Path, contents: Option<&str>) -> io::Result<bool> {
+    let old = fs::read_to_string(path).ok();
+    if old.as_deref() == contents {
+        return Ok(false);
+    }
+    match contents {
+        Some(contents) => fs::write(path, contents)?,
+        None => fs::remove_file(path)?,
+    }
+    Ok(true)
+}
+
+/// Returns the `.ling` files in `dir` and its subdirectories, sorted by path.
+fn find_programs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut programs = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            programs.append(&mut find_programs(&path)?);
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "ling")
+        {
+            programs.push(path);
+        }
+    }
+    programs.sort();
+    Ok(programs)
+}
+
+fn describe(expected: &Output, actual: &Output) -> String {
+    let mut description = String::new();
+    if expected.code != actual.code {
+        description += &format!(
+            "  exit code: expected {}, found {}\n",
+            expected.code, actual.code
+        );
+    }
+    for (name, expected, actual) in [
+        ("stdout", &expected.stdout, &actual.stdout),
+        ("stderr", &expected.stderr, &actual.stderr),
+    ] {
+        if expected != actual {
+            description +=
+                &format!("  {name}:\n    expected: {expected:?}\n    found:    {actual:?}\n");
+        }
+    }
+    description
+}
+
+#[test]
+fn golden_files() -> Result<(), Box<dyn std::error::Error>> {
+    let bless = env::var_os(BLESS_VAR).is_some_and(|value| value != "0");
+
+    let mut checked = 0;
+    let mut failures = vec![];
+    for path in find_programs(Path::new("test_programs"))? {
+        let golden = Golden::load(path)?;
+        if !golden.is_golden() {
+            continue;
+        }
+        checked += 1;
+
+        if bless {
+            // the comments at the top of the file can move the lines which errors point to, so
+            // rerun the program until its expected output no longer changes
+            let mut golden = golden;
+            for _ in 0..3 {
+                if !golden.bless(&golden.run()?)? {
+                    break;
+                }
+                golden = Golden::load(golden.path)?;
+            }
+            continue;
+        }
+
+        let expected = golden.expected()?;
+        let actual = golden.run()?;
+        if expected != actual {
+            failures.push(format!(
+                "{}:\n{}",
+                golden.path.display(),
+                describe(&expected, &actual)
+            ));
+        }
+    }
+
+    assert!(checked > 0, "no golden files were found");
+    assert!(
+        failures.is_empty(),
+        "{} of {checked} golden files did not match, run with {BLESS_VAR}=1 to update them:\n\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+    Ok(())
+}
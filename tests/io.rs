@@ -0,0 +1,330 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    process::Command,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use assert_cmd::prelude::*;
+use linger::{
+    error::RuntimeError,
+    interpreter::{Capabilities, Capability, Interpreter, Value},
+    parser::{parse_program, Program},
+    tokenizer::tokenize,
+    Writer,
+};
+use predicates::prelude::predicate::str::starts_with;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/io/{}.ling", s);
+}
+
+fn file_name_to_program(s: &str) -> Program {
+    let content = fs::read_to_string(file_name_to_path(s)).expect("test program should exist");
+    let tokens = tokenize(content.as_str()).expect("test program should tokenize");
+    parse_program(tokens.as_slice()).expect("test program should parse")
+}
+
+fn temp_path(s: &str) -> String {
+    let path = env::temp_dir().join(format!("linger-io-{}-{}", std::process::id(), s));
+    return path.to_string_lossy().to_string();
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn args() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("args")).args(["one", "two"]);
+    cmd.assert().success().stdout("[one, two]");
+
+    Ok(())
+}
+
+#[test]
+fn args_after_double_dash() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("args"))
+        .args(["one", "--print-result", "--", "--two", "--"]);
+    cmd.assert().success().stdout("[one, --two, --]nil\n");
+
+    Ok(())
+}
+
+#[test]
+fn unknown_flag_after_file() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("args")).arg("--two");
+    cmd.assert()
+        .failure()
+        .stdout("")
+        .stderr(starts_with("usage:"));
+
+    Ok(())
+}
+
+#[test]
+fn main_args() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("main_args")).args(["one", "two"]);
+    cmd.assert().success().stdout("[one, two]");
+
+    Ok(())
+}
+
+#[test]
+fn main_rest_args() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("main_rest_args"))
+        .args(["one", "two"]);
+    cmd.assert().success().stdout("[one, two]");
+
+    Ok(())
+}
+
+#[test]
+fn main_args_need_no_capability() -> TestResult {
+    let run = |name| {
+        let mut buf = vec![];
+        let mut writer = Writer::new(Box::new(&mut buf));
+        Interpreter::new(&mut writer)
+            .with_capabilities(Capabilities::none())
+            .with_args(vec!["one".to_string()])
+            .run(file_name_to_program(name))
+    };
+
+    assert!(matches!(
+        run("main_args"),
+        Ok(Value::List(args)) if matches!(args.as_slice(), [Value::Str(arg)] if arg == "one")
+    ));
+    // the builtin still needs the capability
+    assert!(matches!(
+        run("args"),
+        Err(RuntimeError::CapabilityDenied(_, Capability::Env))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn read_file() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("read_file"))
+        .arg(file_name_to_path("args"));
+    cmd.assert()
+        .success()
+        .stdout(fs::read_to_string(file_name_to_path("args"))?);
+
+    Ok(())
+}
+
+#[test]
+fn write_and_append_file() -> TestResult {
+    let path = temp_path("write_and_append_file.txt");
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("write_and_append_file")).arg(&path);
+    cmd.assert().success().stdout("hello, world");
+    fs::remove_file(path)?;
+
+    Ok(())
+}
+
+#[test]
+fn list_dir() -> TestResult {
+    let path = temp_path("list_dir");
+    fs::create_dir_all(&path)?;
+    fs::write(format!("{path}/b.txt"), "")?;
+    fs::write(format!("{path}/a.txt"), "")?;
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("list_dir")).arg(&path);
+    cmd.assert().success().stdout("[a.txt, b.txt]");
+    fs::remove_dir_all(path)?;
+
+    Ok(())
+}
+
+#[test]
+fn getenv() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("getenv"))
+        .env("LINGER_TEST_VAR", "value")
+        .env_remove("LINGER_UNSET_TEST_VAR");
+    cmd.assert().success().stdout("value true");
+
+    Ok(())
+}
+
+#[test]
+fn read_line() -> TestResult {
+    let mut cmd = assert_cmd::Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("read_line"));
+    cmd.write_stdin("first\nsecond\n")
+        .assert()
+        .success()
+        .stdout("<first><second>");
+
+    Ok(())
+}
+
+#[test]
+fn time() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("time"));
+    cmd.assert().success().stdout("true true");
+
+    Ok(())
+}
+
+#[test]
+fn exit() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("exit"));
+    cmd.assert().code(3).stdout("before");
+
+    Ok(())
+}
+
+#[test]
+fn err_exit_non_integer() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-exit_non_integer"));
+    cmd.assert().failure().stderr(starts_with(
+        RuntimeError::ExpectedInteger(Box::new(Value::Num(1.5))).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_exit_out_of_range() -> TestResult {
+    for (file_name, code) in [("err-exit_too_large", 256.0), ("err-exit_negative", -1.0)] {
+        let mut cmd = Command::cargo_bin("linger-core")?;
+
+        cmd.arg(file_name_to_path(file_name));
+        cmd.assert().failure().stderr(starts_with(
+            RuntimeError::ExitCodeOutOfRange(Box::new(Value::Num(code))).to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn err_exit_reserved() -> TestResult {
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+
+    let result = Interpreter::new(&mut writer)
+        .with_capabilities(Capabilities::none().with(Capability::Process))
+        .run(file_name_to_program("err-exit_reserved"));
+    assert!(matches!(result, Err(RuntimeError::ReservedExitCode(250))));
+
+    Ok(())
+}
+
+#[test]
+fn capabilities_denied_by_default() -> TestResult {
+    let program = file_name_to_program("read_file");
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+
+    let result = Interpreter::new(&mut writer)
+        .with_capabilities(Capabilities::all().without(Capability::FileRead))
+        .with_args(vec![file_name_to_path("args")])
+        .run(program);
+    assert!(matches!(
+        result,
+        Err(RuntimeError::CapabilityDenied(_, Capability::FileRead))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn eprint() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("eprint"));
+    cmd.assert()
+        .success()
+        .stdout("to stdout")
+        .stderr("to stderr");
+
+    Ok(())
+}
+
+#[test]
+fn captured_streams() -> TestResult {
+    let program = file_name_to_program("echo_streams");
+    let mut out = vec![];
+    let mut err = vec![];
+    let mut writer = Writer::new(Box::new(&mut out))
+        .with_stderr(Box::new(&mut err))
+        .with_stdin(Box::new("hello\n".as_bytes()));
+
+    Interpreter::new(&mut writer)
+        .with_capabilities(Capabilities::none().with(Capability::Stdin))
+        .run(program)
+        .expect("program should run");
+    drop(writer);
+
+    assert_eq!(String::from_utf8(out)?, "out: hello");
+    assert_eq!(String::from_utf8(err)?, "err: hello");
+
+    Ok(())
+}
+
+#[test]
+fn writers_share_stdin() -> TestResult {
+    let mut buf = vec![];
+    let writer = Writer::new(Box::new(&mut buf));
+
+    // creating a writer must not wait for standard input while another writer exists
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _writer = Writer::stdio();
+        sender.send(()).expect("receiver should be alive");
+    });
+    receiver.recv_timeout(Duration::from_secs(10))?;
+    drop(writer);
+
+    Ok(())
+}
+
+struct FailingWriter;
+
+impl Write for FailingWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn err_write_failure() -> TestResult {
+    let program = file_name_to_program("eprint");
+    let mut writer = Writer::new(Box::new(FailingWriter));
+
+    let result = Interpreter::new(&mut writer).run(program);
+    assert!(matches!(result, Err(RuntimeError::Io(_))));
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::{fs, thread, time::Duration};
+
+use linger::{
+    error::RuntimeError,
+    interpreter::{ExecutionLimits, Interpreter, Value},
+    parser::{parse_program, Program},
+    tokenizer::tokenize,
+    Writer,
+};
+
+fn file_name_to_program(s: &str) -> Program {
+    let path = format!("test_programs/limits/{}.ling", s);
+    let content = fs::read_to_string(path).expect("test program should exist");
+    let tokens = tokenize(content.as_str()).expect("test program should tokenize");
+    parse_program(tokens.as_slice()).expect("test program should parse")
+}
+
+fn run_with_limits(s: &str, limits: ExecutionLimits) -> Result<Value, RuntimeError> {
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+    Interpreter::new(&mut writer)
+        .with_limits(limits)
+        .run(file_name_to_program(s))
+}
+
+#[test]
+fn within_limits() {
+    let result = run_with_limits("within_limits", ExecutionLimits::sandboxed());
+    assert!(matches!(result, Ok(Value::Num(n)) if n == 45.0));
+}
+
+#[test]
+fn step_limit() {
+    let result = run_with_limits(
+        "infinite_loop",
+        ExecutionLimits::none().with_max_steps(1000),
+    );
+    assert!(matches!(result, Err(RuntimeError::StepLimitExceeded(1000))));
+}
+
+#[test]
+fn call_depth_limit() {
+    let result = run_with_limits(
+        "unbounded_recursion",
+        ExecutionLimits::none().with_max_call_depth(8),
+    );
+    assert!(matches!(result, Err(RuntimeError::CallDepthExceeded(8))));
+}
+
+#[test]
+fn allocation_limit() {
+    let result = run_with_limits(
+        "unbounded_list_growth",
+        ExecutionLimits::none().with_max_allocation(10_000),
+    );
+    assert!(matches!(
+        result,
+        Err(RuntimeError::AllocationLimitExceeded(10_000))
+    ));
+}
+
+#[test]
+fn deadline() {
+    let result = run_with_limits(
+        "infinite_loop",
+        ExecutionLimits::none().with_deadline(Duration::from_millis(50)),
+    );
+    assert!(matches!(result, Err(RuntimeError::DeadlineExceeded(_))));
+}
+
+#[test]
+fn cancellation() {
+    let program = file_name_to_program("infinite_loop");
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+    let mut interpreter = Interpreter::new(&mut writer);
+
+    let handle = interpreter.cancellation_handle();
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+    });
+
+    let result = interpreter.run(program);
+    canceller.join().expect("canceller thread should not panic");
+    assert!(matches!(result, Err(RuntimeError::Cancelled)));
+}
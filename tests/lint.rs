@@ -0,0 +1,195 @@
+use std::{fs, process::Command};
+
+use assert_cmd::prelude::*;
+use linger::{
+    lint::{lint, Diagnostic, Level, LintConfig, Rule},
+    parser::{parse_procedures, Span},
+    tokenizer::tokenize,
+};
+use predicates::prelude::predicate::str::contains;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/lint/{}.ling", s);
+}
+
+/// Returns the rule, level and position of each diagnostic found in the lint test program named
+/// `s`.
+fn file_name_to_diagnostics(s: &str, config: &LintConfig) -> Vec<(Rule, Level, Span)> {
+    let source = fs::read_to_string(file_name_to_path(s)).expect("test program should exist");
+    let tokens = tokenize(source.as_str()).expect("test program should tokenize");
+    let procedures = parse_procedures(tokens.as_slice()).expect("test program should parse");
+    lint(&procedures, config)
+        .into_iter()
+        .map(
+            |Diagnostic {
+                 rule, level, span, ..
+             }| (rule, level, span),
+        )
+        .collect()
+}
+
+fn span(line: usize, col: usize) -> Span {
+    Span { line, col }
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn clean() {
+    assert_eq!(
+        file_name_to_diagnostics("clean", &LintConfig::new()),
+        vec![]
+    );
+}
+
+#[test]
+fn unused_variable() {
+    assert_eq!(
+        file_name_to_diagnostics("unused_variable", &LintConfig::new()),
+        vec![(Rule::UnusedVariable, Level::Warn, span(3, 3))]
+    );
+}
+
+#[test]
+fn unused_lambda() {
+    assert_eq!(
+        file_name_to_diagnostics("unused_lambda", &LintConfig::new()),
+        vec![
+            (Rule::UnusedVariable, Level::Warn, span(2, 3)),
+            (Rule::UnusedVariable, Level::Warn, span(3, 3)),
+            (Rule::ReassignConstant, Level::Deny, span(7, 3)),
+        ]
+    );
+}
+
+#[test]
+fn reassign_constant() {
+    assert_eq!(
+        file_name_to_diagnostics("reassign_constant", &LintConfig::new()),
+        vec![(Rule::ReassignConstant, Level::Deny, span(3, 3))]
+    );
+}
+
+#[test]
+fn break_not_in_loop() {
+    assert_eq!(
+        file_name_to_diagnostics("break_not_in_loop", &LintConfig::new()),
+        vec![(Rule::BreakNotInLoop, Level::Deny, span(4, 7))]
+    );
+}
+
+#[test]
+fn continue_not_in_loop() {
+    assert_eq!(
+        file_name_to_diagnostics("continue_not_in_loop", &LintConfig::new()),
+        vec![(Rule::ContinueNotInLoop, Level::Deny, span(3, 5))]
+    );
+}
+
+#[test]
+fn arg_mismatch() {
+    assert_eq!(
+        file_name_to_diagnostics("arg_mismatch", &LintConfig::new()),
+        vec![
+            (Rule::ArgMismatch, Level::Deny, span(2, 3)),
+            (Rule::ArgMismatch, Level::Deny, span(3, 3)),
+            (Rule::ArgMismatch, Level::Deny, span(4, 3))
+        ]
+    );
+}
+
+#[test]
+fn unreachable_code() {
+    assert_eq!(
+        file_name_to_diagnostics("unreachable_code", &LintConfig::new()),
+        vec![(Rule::UnreachableCode, Level::Warn, span(7, 3))]
+    );
+}
+
+#[test]
+fn configured_levels() {
+    let config = LintConfig::new()
+        .with_level(Rule::UnusedVariable, Level::Deny)
+        .with_level(Rule::ReassignConstant, Level::Allow);
+
+    assert_eq!(
+        file_name_to_diagnostics("unused_variable", &config),
+        vec![(Rule::UnusedVariable, Level::Deny, span(3, 3))]
+    );
+    assert_eq!(
+        file_name_to_diagnostics("reassign_constant", &config),
+        vec![]
+    );
+}
+
+#[test]
+fn check_warning() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check").arg(file_name_to_path("unused_variable"));
+    cmd.assert().success().stderr(contains(
+        "unused_variable.ling:3:3: warning: variable \"unused\" is never used [unused-variable]",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn check_error() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check").arg(file_name_to_path("reassign_constant"));
+    cmd.assert().failure().stderr(contains(
+        "reassign_constant.ling:3:3: error: cannot assign to \"c\" because it is a constant [reassign-constant]",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn check_configured_levels() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check")
+        .arg("--deny")
+        .arg("unused-variable")
+        .arg(file_name_to_path("unused_variable"));
+    cmd.assert()
+        .failure()
+        .stderr(contains("error: variable \"unused\" is never used"));
+
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check")
+        .arg("--allow")
+        .arg("reassign-constant")
+        .arg(file_name_to_path("reassign_constant"));
+    cmd.assert().success().stderr("");
+
+    Ok(())
+}
+
+#[test]
+fn check_does_not_run() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check").arg(file_name_to_path("clean"));
+    cmd.assert().success().stdout("");
+
+    Ok(())
+}
+
+#[test]
+fn check_unknown_rule() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check")
+        .arg("--deny")
+        .arg("no-such-rule")
+        .arg(file_name_to_path("clean"));
+    cmd.assert()
+        .failure()
+        .stderr(contains("unknown lint rule \"no-such-rule\""));
+
+    Ok(())
+}
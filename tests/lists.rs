@@ -68,7 +68,7 @@ fn err_head_non_list() -> TestResult {
 
     cmd.arg(file_name_to_path("err-head_non_list"));
     cmd.assert().failure().stdout("").stderr(starts_with(
-        RuntimeError::ExpectedList(Value::Num(4.0)).to_string(),
+        RuntimeError::ExpectedList(Box::new(Value::Num(4.0))).to_string(),
     ));
 
     Ok(())
@@ -80,7 +80,7 @@ fn err_rest_non_list() -> TestResult {
 
     cmd.arg(file_name_to_path("err-rest_non_list"));
     cmd.assert().failure().stdout("").stderr(starts_with(
-        RuntimeError::ExpectedList(Value::Nil).to_string(),
+        RuntimeError::ExpectedList(Box::new(Value::Nil)).to_string(),
     ));
 
     Ok(())
@@ -92,7 +92,7 @@ fn err_indexing_non_list() -> TestResult {
 
     cmd.arg(file_name_to_path("err-indexing_non_list"));
     cmd.assert().failure().stdout("").stderr(starts_with(
-        RuntimeError::NotIndexable(Value::Num(10.0)).to_string(),
+        RuntimeError::NotIndexable(Box::new(Value::Num(10.0))).to_string(),
     ));
 
     Ok(())
@@ -127,14 +127,14 @@ fn err_index_not_an_integer() -> TestResult {
 
     cmd_string.arg(file_name_to_path("err-index_not_an_integer_string"));
     cmd_string.assert().failure().stdout("").stderr(starts_with(
-        RuntimeError::ExpectedInteger(Value::Str("hello".to_string())).to_string(),
+        RuntimeError::ExpectedInteger(Box::new(Value::Str("hello".to_string()))).to_string(),
     ));
 
     let mut cmd_float = Command::cargo_bin("linger-core")?;
 
     cmd_float.arg(file_name_to_path("err-index_not_an_integer_float"));
     cmd_float.assert().failure().stdout("").stderr(starts_with(
-        RuntimeError::ExpectedInteger(Value::Num(3.14)).to_string(),
+        RuntimeError::ExpectedInteger(Box::new(Value::Num(3.14))).to_string(),
     ));
 
     Ok(())
@@ -0,0 +1,184 @@
+use std::fs;
+
+use assert_cmd::Command;
+use predicates::prelude::predicate::str::contains;
+use serde_json::{json, Value};
+
+const URI: &str = "file:///definitions.ling";
+
+fn frame(message: Value) -> String {
+    let content = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{content}", content.len())
+}
+
+/// Returns the framed messages of a session which opens the `definitions` analysis test program,
+/// sends `requests`, and then shuts the server down.
+fn session(requests: Vec<Value>) -> String {
+    file_session("definitions", requests)
+}
+
+/// Returns the framed messages of a session which opens the analysis test program `s`, sends
+/// `requests`, and then shuts the server down.
+fn file_session(s: &str, requests: Vec<Value>) -> String {
+    let text = fs::read_to_string(format!("test_programs/analysis/{}.ling", s))
+        .expect("test program should exist");
+    let mut messages = vec![
+        json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": URI, "languageId": "linger", "version": 1, "text": text },
+            },
+        }),
+    ];
+    messages.extend(requests);
+    messages.push(json!({ "jsonrpc": "2.0", "id": 99, "method": "shutdown" }));
+    messages.push(json!({ "jsonrpc": "2.0", "method": "exit" }));
+    messages.into_iter().map(frame).collect()
+}
+
+fn request(id: u64, method: &str, line: u64, character: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": {
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        },
+    })
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn initialize_and_shutdown() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+
+    cmd.write_stdin(session(vec![]));
+    cmd.assert()
+        .success()
+        .stdout(contains(r#""definitionProvider":true"#))
+        .stdout(contains(r#""diagnostics":[]"#))
+        .stdout(contains(r#""id":99,"jsonrpc":"2.0","result":null"#));
+
+    Ok(())
+}
+
+#[test]
+fn exit_without_shutdown() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+
+    cmd.write_stdin(frame(json!({ "jsonrpc": "2.0", "method": "exit" })));
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn diagnostics() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+    let change = json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didChange",
+        "params": {
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": "proc main() {\n  add(1);\n}\nproc add(a, b) {}" }],
+        },
+    });
+
+    cmd.write_stdin(session(vec![change]));
+    cmd.assert().success().stdout(contains(
+        r#""diagnostics":[{"code":"arg-mismatch","message":"procedure \"add\" expected 2 args, instead got 1","range":{"end":{"character":5,"line":1},"start":{"character":2,"line":1}},"severity":2,"source":"linger"}]"#,
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn definition() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+
+    cmd.write_stdin(session(vec![request(1, "textDocument/definition", 2, 12)]));
+    cmd.assert().success().stdout(contains(
+        r#""id":1,"jsonrpc":"2.0","result":{"range":{"end":{"character":8,"line":15},"start":{"character":5,"line":15}},"uri":"file:///definitions.ling"}"#,
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn definition_utf16_positions() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+
+    // the emoji before `café` takes up two UTF-16 code units
+    cmd.write_stdin(file_session(
+        "unicode",
+        vec![request(1, "textDocument/definition", 2, 16)],
+    ));
+    cmd.assert().success().stdout(contains(
+        r#""id":1,"jsonrpc":"2.0","result":{"range":{"end":{"character":12,"line":1},"start":{"character":8,"line":1}},"uri":"file:///definitions.ling"}"#,
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn hover() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+
+    cmd.write_stdin(session(vec![request(1, "textDocument/hover", 2, 12)]));
+    cmd.assert().success().stdout(contains(
+        r#""result":{"contents":{"kind":"markdown","value":"```linger\nproc add(a, b)\n```"}}"#,
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn completion() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+
+    cmd.write_stdin(session(vec![request(1, "textDocument/completion", 8, 4)]));
+    cmd.assert()
+        .success()
+        .stdout(contains(
+            r#"{"detail":"(builtin) print","kind":3,"label":"print"}"#,
+        ))
+        .stdout(contains(r#"{"detail":"let i","kind":6,"label":"i"}"#));
+
+    Ok(())
+}
+
+#[test]
+fn document_symbols() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+    let symbols = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "textDocument/documentSymbol",
+        "params": { "textDocument": { "uri": URI } },
+    });
+
+    cmd.write_stdin(session(vec![symbols]));
+    cmd.assert().success().stdout(contains(
+        r#"{"detail":"proc add(a, b)","kind":12,"name":"add","range":{"end":{"character":1,"line":17},"start":{"character":0,"line":15}},"selectionRange":{"end":{"character":8,"line":15},"start":{"character":5,"line":15}}}"#,
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn unsupported_method() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-lsp")?;
+    let unsupported = json!({ "jsonrpc": "2.0", "id": 1, "method": "textDocument/rename" });
+
+    cmd.write_stdin(session(vec![unsupported]));
+    cmd.assert()
+        .success()
+        .stdout(contains(r#""error":{"code":-32601"#));
+
+    Ok(())
+}
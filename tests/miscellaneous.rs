@@ -33,6 +33,16 @@ fn string_indexing() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn string_indexing_unicode() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("string_indexing_unicode"));
+    cmd.assert().success().stdout(starts_with("éöe"));
+
+    Ok(())
+}
+
 #[test]
 fn is_empty() -> TestResult {
     let mut cmd = Command::cargo_bin("linger-core")?;
@@ -67,7 +77,7 @@ fn err_is_empty_non_list() -> TestResult {
     cmd.assert()
         .failure()
         .stderr(starts_with(
-            RuntimeError::ExpectedList(Value::Bool(true)).to_string(),
+            RuntimeError::ExpectedList(Box::new(Value::Bool(true))).to_string(),
         ))
         .stdout("");
 
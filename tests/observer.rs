@@ -0,0 +1,163 @@
+use std::{cell::RefCell, fs, rc::Rc};
+
+use linger::{
+    error::RuntimeError,
+    interpreter::{CancellationHandle, Interpreter, Observer, Value},
+    parser::{parse_program, Program, Span},
+    tokenizer::tokenize,
+    Writer,
+};
+
+fn file_name_to_program(s: &str) -> Program {
+    let path = format!("test_programs/observer/{}.ling", s);
+    let content = fs::read_to_string(path).expect("test program should exist");
+    let tokens = tokenize(content.as_str()).expect("test program should tokenize");
+    parse_program(tokens.as_slice()).expect("test program should parse")
+}
+
+/// An observer which records every event as a line of text.
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Rc<RefCell<Vec<String>>>,
+}
+
+impl Observer for Recorder {
+    fn on_statement(&mut self, span: Span) {
+        self.events
+            .borrow_mut()
+            .push(format!("statement {}", span.line));
+    }
+
+    fn on_call(&mut self, name: &str, args: &[(String, Value)]) {
+        let args: Vec<String> = args
+            .iter()
+            .map(|(param, value)| format!("{param}: {value}"))
+            .collect();
+        self.events
+            .borrow_mut()
+            .push(format!("call {name}({})", args.join(", ")));
+    }
+
+    fn on_return(&mut self, value: &Value) {
+        self.events.borrow_mut().push(format!("return {value}"));
+    }
+
+    fn on_error(&mut self, error: &RuntimeError) {
+        self.events.borrow_mut().push(format!("error {error}"));
+    }
+
+    fn on_print(&mut self, text: &str) {
+        self.events.borrow_mut().push(format!("print {text}"));
+    }
+}
+
+/// An observer which cancels the run once it has seen a given number of statements.
+struct Meter {
+    remaining: usize,
+    cancellation: CancellationHandle,
+}
+
+impl Observer for Meter {
+    fn on_statement(&mut self, _span: Span) {
+        match self.remaining {
+            0 => self.cancellation.cancel(),
+            _ => self.remaining -= 1,
+        }
+    }
+}
+
+#[test]
+fn events() {
+    let recorder = Recorder::default();
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+    let result = Interpreter::new(&mut writer)
+        .with_observer(recorder.clone())
+        .run(file_name_to_program("calls"));
+
+    assert!(matches!(result, Ok(Value::Num(n)) if n == 3.0));
+    assert_eq!(
+        *recorder.events.borrow(),
+        vec![
+            "call main()",
+            "statement 2",
+            "call add(a: 1, b: 2)",
+            "statement 8",
+            "return 3",
+            "statement 3",
+            "print 3",
+            "statement 4",
+            "return 3",
+        ]
+    );
+    drop(writer);
+    assert_eq!(String::from_utf8(buf).unwrap(), "3");
+}
+
+#[test]
+fn error_fails_every_call() {
+    let recorder = Recorder::default();
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+    let result = Interpreter::new(&mut writer)
+        .with_observer(recorder.clone())
+        .run(file_name_to_program("error"));
+
+    let error = result.expect_err("program should fail").to_string();
+    let calls: Vec<String> = recorder
+        .events
+        .borrow()
+        .iter()
+        .filter(|event| !event.starts_with("statement"))
+        .cloned()
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            "call main()".to_string(),
+            "call outer()".to_string(),
+            "call inner(x: 1)".to_string(),
+            format!("error {error}"),
+            format!("error {error}"),
+            format!("error {error}"),
+        ]
+    );
+}
+
+#[test]
+fn observers_are_told_in_order() {
+    let events = Rc::new(RefCell::new(vec![]));
+    let first = Recorder {
+        events: events.clone(),
+    };
+    let second = Recorder {
+        events: events.clone(),
+    };
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+    let result = Interpreter::new(&mut writer)
+        .with_observer(first)
+        .with_observer(second)
+        .run(file_name_to_program("calls"));
+
+    assert!(result.is_ok());
+    let events = events.borrow();
+    assert_eq!(events.len(), 18);
+    assert!(events.chunks(2).all(|pair| pair[0] == pair[1]));
+}
+
+#[test]
+fn observer_can_cancel() {
+    let mut buf = vec![];
+    let mut writer = Writer::new(Box::new(&mut buf));
+    let interpreter = Interpreter::new(&mut writer);
+    let meter = Meter {
+        remaining: 100,
+        cancellation: interpreter.cancellation_handle(),
+    };
+    let result = interpreter
+        .with_observer(meter)
+        .run(file_name_to_program("loop"));
+
+    assert!(matches!(result, Err(RuntimeError::Cancelled)));
+}
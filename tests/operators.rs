@@ -90,7 +90,7 @@ fn err_bad_arg_plus_bool() -> TestResult {
     cmd.assert()
         .failure()
         .stderr(starts_with(
-            RuntimeError::BadArg(Value::Bool(true)).to_string(),
+            RuntimeError::BadArg(Box::new(Value::Bool(true))).to_string(),
         ))
         .stdout("");
 
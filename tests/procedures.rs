@@ -52,6 +52,126 @@ fn single_line_closure_no_brackets() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn method_call() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("method_call"));
+    cmd.assert()
+        .success()
+        .stdout(contains("[4, 16, 36] 1 false 3 10 abc"));
+
+    Ok(())
+}
+
+#[test]
+fn err_unknown_method() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-unknown_method"));
+    cmd.assert().failure().stderr(contains(
+        RuntimeError::UnknownVariable("sum".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn default_args() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("default_args"));
+    cmd.assert()
+        .success()
+        .stdout(contains("Hello, Ada Hi, Bob [3] [1, 2, 3] 4 5"));
+
+    Ok(())
+}
+
+#[test]
+fn rest_params() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("rest_params"));
+    cmd.assert()
+        .success()
+        .stdout(contains("0 1 6 [1, 2, 3] [a] [b, 1, 2]"));
+
+    Ok(())
+}
+
+#[test]
+fn named_args() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("named_args"));
+    cmd.assert()
+        .success()
+        .stdout(contains("1cm 2m 6 Rect(1, 2) exactly 3km"));
+
+    Ok(())
+}
+
+#[test]
+fn err_missing_arg() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-missing_arg"));
+    cmd.assert().failure().stderr(contains(
+        RuntimeError::MissingArg("greet".to_string(), "name".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_unknown_param() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-unknown_param"));
+    cmd.assert().failure().stderr(contains(
+        RuntimeError::UnknownParam("greet".to_string(), "greting".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_duplicate_arg() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-duplicate_arg"));
+    cmd.assert().failure().stderr(contains(
+        RuntimeError::DuplicateArg("greet".to_string(), "name".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_rest_param_not_last() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-rest_param_not_last"));
+    cmd.assert().failure().stderr(contains(
+        ParseError::RestParamNotLast("nums".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_positional_arg_after_named() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-positional_arg_after_named"));
+    cmd.assert()
+        .failure()
+        .stderr(contains(ParseError::PositionalArgAfterNamed.to_string()));
+
+    Ok(())
+}
+
 #[test]
 fn err_keyword_as_proc() -> TestResult {
     let mut cmd = Command::cargo_bin("linger-core")?;
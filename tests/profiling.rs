@@ -0,0 +1,139 @@
+use std::{env, fs};
+
+use assert_cmd::Command;
+use predicates::prelude::{predicate::str::contains, PredicateBooleanExt};
+use regex::Regex;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/profiling/{}.ling", s);
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn trace() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("--trace").arg(file_name_to_path("calls"));
+    cmd.assert()
+        .success()
+        .stdout("12")
+        .stderr(contains(
+            "-> <lambda@2:18>(x: 1)\n<- <lambda@2:18> returned 2\n",
+        ))
+        .stderr(contains("-> add(a: 0, b: 2)\n<- add returned 2\n"))
+        .stderr(contains("-> add(a: 6, b: 6)\n<- add returned 12\n"));
+
+    Ok(())
+}
+
+#[test]
+fn trace_nested_calls() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("--trace").arg(file_name_to_path("fact"));
+    cmd.assert().success().stdout("6").stderr(
+        "-> fact(n: 3)\n  \
+        -> fact(n: 2)\n    \
+        -> fact(n: 1)\n    \
+        <- fact returned 1\n  \
+        <- fact returned 2\n\
+        <- fact returned 6\n",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn trace_error() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("--trace").arg(file_name_to_path("error"));
+    cmd.assert().failure().stderr(contains(
+        "-> fail(message: \"boom\")\n<- fail failed: bad argument \"boom\"\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn profile() -> TestResult {
+    let path = env::temp_dir().join("linger-profile-calls.folded");
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(format!("--profile={}", path.display()))
+        .arg(file_name_to_path("calls"));
+    cmd.assert()
+        .success()
+        .stdout("12")
+        .stderr(contains("procedure"))
+        .stderr(
+            contains("calls")
+                .and(contains("inclusive"))
+                .and(contains("exclusive")),
+        )
+        .stderr(predicates::str::is_match(r"(?m)^main +1 ")?)
+        .stderr(predicates::str::is_match(r"(?m)^add +3 ")?)
+        .stderr(predicates::str::is_match(r"(?m)^<lambda@2:18> +3 ")?);
+
+    let folded = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    let stacks: Vec<&str> = folded
+        .lines()
+        .filter_map(|line| line.rsplit_once(' '))
+        .map(|(stack, _)| stack)
+        .collect();
+    assert_eq!(stacks, vec!["main", "main;<lambda@2:18>", "main;add"]);
+    assert!(Regex::new(r"^(\S+ \d+\n)+$")?.is_match(&folded));
+
+    Ok(())
+}
+
+#[test]
+fn profile_recursion() -> TestResult {
+    let path = env::temp_dir().join("linger-profile-fact.folded");
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(format!("--profile={}", path.display()))
+        .arg(file_name_to_path("fact"));
+    cmd.assert()
+        .success()
+        .stderr(predicates::str::is_match(r"(?m)^fact +3 ")?);
+
+    let folded = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    assert!(folded.contains("main;fact;fact;fact "));
+
+    Ok(())
+}
+
+#[test]
+fn profile_failing_program() -> TestResult {
+    let path = env::temp_dir().join("linger-profile-error.folded");
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(format!("--profile={}", path.display()))
+        .arg(file_name_to_path("error"));
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::is_match(r"(?m)^fail +1 ")?);
+
+    let folded = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    assert!(folded.contains("main;fail "));
+
+    Ok(())
+}
+
+#[test]
+fn unknown_flag() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("--bogus").arg(file_name_to_path("calls"));
+    cmd.assert()
+        .failure()
+        .stdout(contains("12").not())
+        .stderr(contains("usage:"));
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use linger::{
+    formatter::format_source,
+    parser::{
+        parse_declarations, parse_expression, parse_program, parse_program_recovering,
+        parse_source_file,
+    },
+    tokenizer::{tokenize, tokenize_with_trivia},
+};
+use proptest::prelude::*;
+use regex::Regex;
+
+/// Tokens and fragments of Linger source which are joined at random to make programs which are
+/// mostly invalid, but which reach deep into the parser.
+const FRAGMENTS: [&str; 58] = [
+    "(", ")", "{", "}", "[", "]", ",", ";", ":", ".", "..", "...", "->", "=", "+=", "+", "-", "*",
+    "/", "%", "!", "==", "!=", "<", ">", "<=", "&&", "||", "++", "--", "proc", "main", "let",
+    "const", "if", "else", "while", "for", "return", "break", "continue", "match", "struct",
+    "enum", "nil", "true", "false", "x", "f", "P", "list", "print", "_", "1", "2.5", "\"s\"",
+    "/* c */", "// c\n",
+];
+
+fn variable() -> impl Strategy<Value = String> {
+    prop::sample::select(vec!["a", "b", "xs", "f"]).prop_map(str::to_string)
+}
+
+/// Wraps the source of an expression in parentheses, unless it is a variable, which would then be
+/// parsed as the parameters of a lambda.
+fn parenthesize(expr: &str) -> String {
+    match expr.chars().all(char::is_alphabetic) {
+        true => expr.to_string(),
+        false => format!("({expr})"),
+    }
+}
+
+/// Source for a valid expression, which is written with as few parentheses as possible so that
+/// the formatter has to keep the precedence of its operators.
+fn expression() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![
+        variable(),
+        (0..1000u32).prop_map(|n| n.to_string()),
+        (0..100u32, 1..10u32).prop_map(|(whole, tenths)| format!("{whole}.{tenths}")),
+        prop::sample::select(vec!["true", "false", "nil"]).prop_map(str::to_string),
+        "[a-z ]{0,6}".prop_map(|s| format!("\"{s}\"")),
+    ];
+    leaf.prop_recursive(4, 32, 3, |inner| {
+        let operator = prop::sample::select(vec![
+            "+", "-", "*", "/", "%", "==", "!=", "<", ">", "<=", ">=", "&&", "||",
+        ]);
+        prop_oneof![
+            (inner.clone(), operator, inner.clone()).prop_map(|(l, op, r)| format!("{l} {op} {r}")),
+            inner.clone().prop_map(|e| parenthesize(&e)),
+            (prop::sample::select(vec!["-", "!"]), inner.clone())
+                .prop_map(|(op, e)| format!("{op}{}", parenthesize(&e))),
+            prop::collection::vec(inner.clone(), 0..3)
+                .prop_map(|args| format!("f({})", args.join(", "))),
+            prop::collection::vec(inner.clone(), 0..3)
+                .prop_map(|args| format!("list({})", args.join(", "))),
+            (inner.clone(), inner.clone())
+                .prop_map(|(e, arg)| format!("{}.map({arg})", parenthesize(&e))),
+            (inner.clone(), inner.clone()).prop_map(|(e, i)| format!("{}[{i}]", parenthesize(&e))),
+            inner
+                .clone()
+                .prop_map(|e| format!("{}.x", parenthesize(&e))),
+            inner.clone().prop_map(|e| format!("(a, b = 1) -> {e}")),
+            inner.clone().prop_map(|e| format!("P {{ x: {e} }}")),
+            (inner.clone(), inner.clone()).prop_map(|(e, arm)| format!(
+                "match ({e}) {{ 1 -> {arm}, [x, ..rest] -> x, _ -> nil }}"
+            )),
+        ]
+    })
+}
+
+/// Source for a valid statement, ending with a semicolon unless it ends with a block.
+fn statement() -> impl Strategy<Value = String> {
+    let simple = prop_oneof![
+        (variable(), expression()).prop_map(|(v, e)| format!("let {v} = {e};")),
+        (variable(), expression()).prop_map(|(v, e)| format!("const {v}: num = {e};")),
+        (variable(), expression()).prop_map(|(v, e)| format!("{v} = {e};")),
+        (variable(), expression()).prop_map(|(v, e)| format!("{v} += {e};")),
+        expression().prop_map(|e| format!("a.x = {e};")),
+        expression().prop_map(|e| format!("print({e});")),
+        expression().prop_map(|e| format!("return {e};")),
+        Just("break;".to_string()),
+    ];
+    simple.prop_recursive(3, 16, 3, |inner| {
+        let block = prop::collection::vec(inner.clone(), 0..3)
+            .prop_map(|statements| format!("{{ {} }}", statements.join(" ")));
+        prop_oneof![
+            (expression(), block.clone()).prop_map(|(e, b)| format!("while ({e}) {b}")),
+            (
+                expression(),
+                block.clone(),
+                expression(),
+                block.clone(),
+                block.clone()
+            )
+                .prop_map(|(c1, b1, c2, b2, b3)| format!(
+                    "if ({c1}) {b1} else if ({c2}) {b2} else {b3}"
+                )),
+            (expression(), block.clone())
+                .prop_map(|(e, b)| format!("for (let i = 0; i < {e}; i += 1) {b}")),
+        ]
+    })
+}
+
+fn program() -> impl Strategy<Value = String> {
+    (
+        prop::collection::vec(statement(), 0..4),
+        prop::collection::vec(statement(), 0..4),
+    )
+        .prop_map(|(helper, main)| {
+            format!(
+                "struct P {{ x, y: num }}\n\
+                 proc helper(a, b = 2, ...rest) -> num {{ {} }}\n\
+                 proc main() {{ {} }}\n",
+                helper.join(" "),
+                main.join(" ")
+            )
+        })
+}
+
+/// The Debug representation of the declarations in `source`, without the positions in it.
+fn declarations_without_spans(source: &str) -> Result<String, String> {
+    let tokens = tokenize(source).map_err(|e| e.to_string())?;
+    let declarations = parse_declarations(tokens.as_slice()).map_err(|e| e.to_string())?;
+    let span = Regex::new(r"Span \{ line: \d+, col: \d+ \}").unwrap();
+    Ok(span
+        .replace_all(&format!("{declarations:?}"), "Span")
+        .to_string())
+}
+
+proptest! {
+    // a failing case is checked in as a program under `test_programs` instead
+    #![proptest_config(ProptestConfig {
+        failure_persistence: None,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn tokenize_never_panics(source in any::<String>()) {
+        let _ = tokenize(&source);
+        let _ = tokenize_with_trivia(&source);
+    }
+
+    #[test]
+    fn tokenize_never_panics_on_source_like_text(source in r#"[a-z0-9 .,;:(){}\[\]<>=!&|+*/%"\\\n_-]{0,64}"#) {
+        let _ = tokenize(&source);
+        let _ = tokenize_with_trivia(&source);
+    }
+
+    #[test]
+    fn parse_never_panics(fragments in prop::collection::vec(prop::sample::select(FRAGMENTS.to_vec()), 0..64)) {
+        let source = fragments.join(" ");
+        if let Ok(tokens) = tokenize(&source) {
+            let _ = parse_program_recovering(tokens.as_slice());
+            let _ = parse_expression(tokens.as_slice());
+        }
+        if let Ok(tokens) = tokenize_with_trivia(&source) {
+            let _ = parse_source_file(tokens.as_slice());
+        }
+    }
+
+    #[test]
+    fn formatting_is_idempotent(fragments in prop::collection::vec(prop::sample::select(FRAGMENTS.to_vec()), 0..64)) {
+        let source = fragments.join(" ");
+        if let Ok(formatted) = format_source(&source) {
+            prop_assert_eq!(format_source(&formatted).ok(), Some(formatted.clone()));
+        }
+    }
+
+    #[test]
+    fn formatted_program_parses_to_the_same_declarations(source in program()) {
+        let declarations = declarations_without_spans(&source);
+        prop_assume!(declarations.is_ok());
+
+        let formatted = format_source(&source).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        prop_assert_eq!(declarations_without_spans(&formatted), declarations, "formatted:\n{}", formatted);
+        prop_assert_eq!(format_source(&formatted).ok(), Some(formatted.clone()));
+    }
+
+    #[test]
+    fn printed_program_parses_to_the_same_program(source in program()) {
+        let tokens = tokenize(&source).map_err(|e| TestCaseError::reject(e.to_string()))?;
+        let program = parse_program(tokens.as_slice()).map_err(|e| TestCaseError::reject(e.to_string()))?;
+
+        let printed = program.to_string();
+        let tokens = tokenize(&printed).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        let reparsed = parse_program(tokens.as_slice()).map_err(|e| TestCaseError::fail(format!("{e}\n{printed}")))?;
+        prop_assert_eq!(reparsed.to_string(), printed);
+    }
+}
@@ -0,0 +1,129 @@
+use std::{fs, process::Command};
+
+use assert_cmd::prelude::*;
+use linger::{
+    error::ParseError,
+    parser::{parse_procedures_recovering, parse_program_recovering, SugaredStatement},
+    tokenizer::{tokenize, Keyword, Token, TokenValue},
+};
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/recovery/{}.ling", s);
+}
+
+fn file_name_to_tokens(s: &str) -> Vec<Token> {
+    let source = fs::read_to_string(file_name_to_path(s)).expect("test program should exist");
+    tokenize(source.as_str()).expect("test program should tokenize")
+}
+
+fn file_name_to_errors(s: &str) -> Vec<ParseError> {
+    match parse_program_recovering(file_name_to_tokens(s).as_slice()) {
+        Ok(_) => vec![],
+        Err(errors) => errors,
+    }
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn err_multiple_errors() {
+    assert_eq!(
+        file_name_to_errors("err-multiple_errors"),
+        vec![
+            ParseError::UnexpectedToken(Token(TokenValue::SEMICOLON, 2, 11, 24)),
+            ParseError::Expected(TokenValue::RPAREN, Token(TokenValue::COMMA, 4, 13, 50)),
+            ParseError::Expected(
+                TokenValue::SEMICOLON,
+                Token(TokenValue::R_CURLY_BRACKET, 8, 1, 88)
+            ),
+            ParseError::UnexpectedToken(Token(TokenValue::SEMICOLON, 11, 14, 119)),
+            ParseError::UnexpectedToken(Token(TokenValue::RPAREN, 12, 11, 131)),
+        ]
+    );
+}
+
+#[test]
+fn err_unterminated_block() {
+    assert_eq!(
+        file_name_to_errors("err-unterminated_block"),
+        vec![
+            ParseError::Expected(
+                TokenValue::R_CURLY_BRACKET,
+                Token(TokenValue::KW(Keyword::Proc), 5, 1, 46)
+            ),
+            ParseError::UnexpectedToken(Token(TokenValue::SEMICOLON, 6, 11, 71)),
+        ]
+    );
+}
+
+#[test]
+fn err_bad_proc_header() {
+    assert_eq!(
+        file_name_to_errors("err-bad_proc_header"),
+        vec![
+            ParseError::UnexpectedToken(Token(TokenValue::COMMA, 1, 12, 11)),
+            ParseError::KeywordAsVar("let".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn partial_ast() {
+    let (procedures, errors) =
+        parse_procedures_recovering(file_name_to_tokens("err-multiple_errors").as_slice());
+
+    assert_eq!(errors.len(), 5);
+    assert_eq!(procedures.len(), 2);
+    let statements = match &procedures[0].body {
+        SugaredStatement::Block(statements) => statements,
+        statement => panic!("expected a block, instead got {statement:?}"),
+    };
+    let kinds: Vec<&str> = statements
+        .iter()
+        .map(|statement| match statement {
+            SugaredStatement::Spanned(_, statement) => match statement.as_ref() {
+                SugaredStatement::Error(_) => "error",
+                _ => "statement",
+            },
+            _ => "unspanned",
+        })
+        .collect();
+    assert_eq!(kinds, vec!["error", "statement", "error", "error"]);
+}
+
+#[test]
+fn run_reports_every_error() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-multiple_errors"));
+    let output = cmd.output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(!output.status.success());
+    for e in file_name_to_errors("err-multiple_errors") {
+        assert!(
+            stderr.contains(e.to_string().as_str()),
+            "{e} not in {stderr}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn check_reports_every_error() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check")
+        .arg(file_name_to_path("err-unterminated_block"));
+    let output = cmd.output()?;
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(!output.status.success());
+    for e in file_name_to_errors("err-unterminated_block") {
+        assert!(
+            stderr.contains(e.to_string().as_str()),
+            "{e} not in {stderr}"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use std::{fs, process::Command};
+
+use assert_cmd::prelude::*;
+use linger::{
+    error::{ParseError, RuntimeError, TypeError},
+    parser::{parse_declarations, Span},
+    tokenizer::{tokenize, Operator},
+    typecheck::{typecheck, Type},
+};
+use predicates::prelude::predicate::str::starts_with;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/structs/{}.ling", s);
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn construct() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("construct"));
+    cmd.assert()
+        .success()
+        .stdout("Point { x: 1, y: 2 } Person { name: \"ada\", age: 36 }");
+
+    Ok(())
+}
+
+#[test]
+fn empty() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("empty"));
+    cmd.assert().success().stdout("Unit {}");
+
+    Ok(())
+}
+
+#[test]
+fn field_access() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("field_access"));
+    cmd.assert().success().stdout("1 7");
+
+    Ok(())
+}
+
+#[test]
+fn field_assign() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("field_assign"));
+    cmd.assert()
+        .success()
+        .stdout("Point { x: 10, y: 2 } Point { x: 1, y: 2 } Point { x: 1, y: 7 }");
+
+    Ok(())
+}
+
+#[test]
+fn equality() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("equality"));
+    cmd.assert().success().stdout("true false false true");
+
+    Ok(())
+}
+
+#[test]
+fn err_missing_field() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-missing_field"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        RuntimeError::MissingField("Point".to_string(), "y".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_unknown_field() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-unknown_field"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        RuntimeError::UnknownField("Point".to_string(), "z".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_unknown_struct() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-unknown_struct"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        RuntimeError::UnknownStruct("Point".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_reassign_const_field() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-reassign_const_field"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        RuntimeError::ReassignConstant("p".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn err_keyword_as_field() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-keyword_as_field"));
+    cmd.assert().failure().stdout("").stderr(starts_with(
+        ParseError::KeywordAsField("if".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn typed() {
+    let source = fs::read_to_string(file_name_to_path("typed")).expect("test program should exist");
+    let tokens = tokenize(source.as_str()).expect("test program should tokenize");
+    let declarations = parse_declarations(tokens.as_slice()).expect("test program should parse");
+    let point = Type::Named("Point".to_string());
+    let person = Type::Named("Person".to_string());
+
+    assert_eq!(
+        typecheck(&declarations),
+        vec![
+            (
+                Span { line: 11, col: 3 },
+                TypeError::Mismatch(Type::Num, Type::Str)
+            ),
+            (
+                Span { line: 12, col: 3 },
+                TypeError::NoField(point.clone(), "z".to_string())
+            ),
+            (
+                Span { line: 12, col: 3 },
+                TypeError::MissingField(point, "y".to_string())
+            ),
+            (
+                Span { line: 13, col: 3 },
+                TypeError::BadOperands(Operator::Minus, Type::Str, Type::Num)
+            ),
+            (
+                Span { line: 13, col: 3 },
+                TypeError::NoField(person.clone(), "age".to_string())
+            ),
+            (
+                Span { line: 13, col: 3 },
+                TypeError::BadOperands(Operator::Eq, person, Type::Num)
+            ),
+        ]
+    );
+}
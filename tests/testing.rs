@@ -0,0 +1,193 @@
+use assert_cmd::Command;
+use predicates::prelude::predicate::str::contains;
+use regex::Regex;
+
+fn dir_name_to_path(s: &str) -> String {
+    return format!("test_programs/testing/{}", s);
+}
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/testing/{}.ling", s);
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn passing_tests() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("test").arg(dir_name_to_path("passing"));
+    cmd.assert().success().stdout(
+        "test math.ling::test_add ... ok\n\
+        test math.ling::test_assert ... ok\n\
+        test math.ling::test_assert_error ... ok\n\
+        test nested/strings.ling::test_concatenation ... ok\n\
+        \n\
+        test result: ok. 4 passed; 0 failed\n",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn failing_tests() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("test").arg(dir_name_to_path("failing"));
+    cmd.assert()
+        .failure()
+        .stdout(contains("test broken.ling ... FAILED\n"))
+        .stdout(contains("test strings.ling::test_passes ... ok\n"))
+        .stdout(contains(
+            "---- strings.ling::test_lines ----\n\
+            assertion failed: \"a\\nb\\nc\" is not equal to \"a\\nx\\nc\"\n  \
+            a\n\
+            - b\n\
+            + x\n  \
+            c\n",
+        ))
+        .stdout(contains(
+            "---- strings.ling::test_message ----\nchecking\nassertion failed: not true\n",
+        ))
+        .stdout(contains(
+            "expected an error, but the procedure returned 1\n",
+        ))
+        .stdout(contains("test result: FAILED. 1 passed; 4 failed\n"));
+
+    Ok(())
+}
+
+#[test]
+fn single_file() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("test")
+        .arg(dir_name_to_path("passing/nested/strings.ling"));
+    cmd.assert().success().stdout(contains(
+        "test test_programs/testing/passing/nested/strings.ling::test_concatenation ... ok\n",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn tap() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("test")
+        .arg("--format=tap")
+        .arg(dir_name_to_path("failing"));
+    cmd.assert().failure().stdout(
+        "TAP version 13\n\
+        1..5\n\
+        not ok 1 - broken.ling\n\
+        # keyword \"let\" used as variable\n\
+        not ok 2 - strings.ling::test_lines\n\
+        # assertion failed: \"a\\nb\\nc\" is not equal to \"a\\nx\\nc\"\n\
+        #   a\n\
+        # - b\n\
+        # + x\n\
+        #   c\n\
+        not ok 3 - strings.ling::test_message\n\
+        # assertion failed: not true\n\
+        not ok 4 - strings.ling::test_no_error\n\
+        # expected an error, but the procedure returned 1\n\
+        ok 5 - strings.ling::test_passes\n",
+    );
+
+    Ok(())
+}
+
+#[test]
+fn junit() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("test")
+        .arg("--format=junit")
+        .arg(dir_name_to_path("failing"));
+    let output = cmd.assert().failure().get_output().stdout.clone();
+    let xml = String::from_utf8(output)?;
+    let xml = Regex::new(r#"time="\d+\.\d{3}""#)?.replace_all(&xml, r#"time="""#);
+    assert!(xml.starts_with(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <testsuites tests=\"5\" failures=\"4\" time=\"\">\n  \
+        <testsuite name=\"broken.ling\" tests=\"1\" failures=\"1\" time=\"\">\n"
+    ));
+    assert!(xml.contains(
+        "    <testcase name=\"test_message\" classname=\"strings.ling\" time=\"\">\n      \
+        <failure message=\"assertion failed: not true\">assertion failed: not true</failure>\n      \
+        <system-out>checking</system-out>\n    \
+        </testcase>\n"
+    ));
+    assert!(xml.contains(
+        "    <testcase name=\"test_passes\" classname=\"strings.ling\" time=\"\"/>\n  \
+        </testsuite>\n\
+        </testsuites>\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn unknown_format() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("test")
+        .arg("--format=xml")
+        .arg(dir_name_to_path("passing"));
+    cmd.assert()
+        .failure()
+        .stderr("unknown test report format \"xml\"\n");
+
+    Ok(())
+}
+
+#[test]
+fn assertion_outside_of_tests() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("assert_in_main"));
+    cmd.assert()
+        .failure()
+        .stderr("assertion failed: [1, 2] is not equal to [1, 3]\n");
+
+    Ok(())
+}
+
+#[test]
+fn assertion_types() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check").arg(file_name_to_path("assert_types"));
+    cmd.assert()
+        .failure()
+        .stderr(contains(
+            "assert_types.ling:2:3: error: expected type \"bool\" but found \"num\"",
+        ))
+        .stderr(contains(
+            "assert_types.ling:4:3: error: operator \"-\" cannot be applied to \"str\" and \"num\"",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn bad_signature_fails_only_its_test() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("test").arg(file_name_to_path("bad_signature"));
+    cmd.assert()
+        .failure()
+        .stdout(contains("bad_signature.ling::test_a ... ok\n"))
+        .stdout(contains(
+            "---- test_programs/testing/bad_signature.ling::test_b ----\n\
+            procedure \"test_b\" is run first, so it takes at most one parameter\n",
+        ))
+        .stdout(contains(
+            "---- test_programs/testing/bad_signature.ling::test_c ----\n\
+            assertion failed: 1 is not equal to 2\n",
+        ))
+        .stdout(contains("test result: FAILED. 1 passed; 2 failed\n"));
+
+    Ok(())
+}
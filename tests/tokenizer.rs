@@ -0,0 +1,194 @@
+use std::{fs, process::Command};
+
+use assert_cmd::prelude::*;
+use linger::{
+    error::TokenizerError,
+    parser::parse_procedures,
+    tokenizer::{tokenize, tokenize_with_trivia, Keyword, Token, TokenValue, Trivia},
+};
+use predicates::prelude::predicate::str::{diff, starts_with};
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/tokenizer/{}.ling", s);
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn keyword_prefixes() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("keyword_prefixes"));
+    cmd.assert().success().stdout(starts_with("21"));
+
+    Ok(())
+}
+
+#[test]
+fn keyword_boundaries() {
+    assert_eq!(
+        tokenize("if iffy for format").unwrap(),
+        vec![
+            Token(TokenValue::KW(Keyword::If), 1, 1, 0),
+            Token(TokenValue::ID("iffy".to_string()), 1, 4, 3),
+            Token(TokenValue::KW(Keyword::For), 1, 9, 8),
+            Token(TokenValue::ID("format".to_string()), 1, 13, 12),
+        ]
+    );
+}
+
+#[test]
+fn offsets() -> TestResult {
+    let source = fs::read_to_string(file_name_to_path("offsets"))?;
+    let lines: Vec<&str> = source.split('\n').collect();
+
+    for Token(value, line, col, offset) in tokenize_with_trivia(source.as_str()).unwrap() {
+        let line_start = lines[..line - 1].iter().map(|l| l.len() + 1).sum::<usize>();
+        assert_eq!(offset, line_start + col - 1, "{value:?} at {line}:{col}");
+
+        let text = match &value {
+            TokenValue::STR(_) => "\"".to_string(),
+            TokenValue::NUM(_) => source[offset..].chars().take(1).collect(),
+            value => value.to_string(),
+        };
+        assert!(
+            source[offset..].starts_with(text.as_str()),
+            "{value:?} at {line}:{col} should begin with {text:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn trivia() -> TestResult {
+    let source = fs::read_to_string(file_name_to_path("offsets"))?;
+    let trivia: Vec<_> = tokenize_with_trivia(source.as_str())
+        .unwrap()
+        .into_iter()
+        .filter_map(|Token(value, line, ..)| match value {
+            TokenValue::TRIVIA(trivia) => Some((trivia, line)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        trivia,
+        vec![
+            (Trivia::Comment(" a comment".to_string()), 1),
+            (Trivia::BlankLine, 4),
+            (Trivia::TrailingComment(" shout".to_string()), 6),
+            (Trivia::BlankLine, 10),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn long_line() {
+    let source = "1 + ".repeat(100_000) + "1";
+
+    assert_eq!(tokenize(source.as_str()).unwrap().len(), 200_001);
+}
+
+#[test]
+fn block_comments() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("block_comments"));
+    cmd.assert().success().stdout(starts_with("2"));
+
+    Ok(())
+}
+
+#[test]
+fn nested_block_comment_trivia() {
+    assert_eq!(
+        tokenize_with_trivia("/* a /* b */\n c */ x").unwrap(),
+        vec![
+            Token(
+                TokenValue::TRIVIA(Trivia::BlockComment(" a /* b */\n c ".to_string())),
+                1,
+                1,
+                0
+            ),
+            Token(TokenValue::ID("x".to_string()), 2, 7, 19),
+        ]
+    );
+}
+
+#[test]
+fn err_unterminated_block_comment() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-unterminated_block_comment"));
+    cmd.assert().failure().stderr(starts_with(
+        TokenizerError::UnterminatedBlockComment.to_string(),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn multi_line_strings() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("multi_line_strings"));
+    cmd.assert()
+        .success()
+        .stdout(diff("first \"line\"\nsecond\tline"));
+
+    Ok(())
+}
+
+#[test]
+fn doc_comments() -> TestResult {
+    let source = fs::read_to_string(file_name_to_path("doc_comments"))?;
+    let procedures = parse_procedures(tokenize(source.as_str()).unwrap().as_slice()).unwrap();
+
+    assert_eq!(
+        procedures[0].doc,
+        Some("Adds two numbers.\n\nBoth `a` and `b` must be numbers.".to_string())
+    );
+    assert_eq!(procedures[1].doc, None);
+
+    let mut cmd = Command::cargo_bin("linger-core")?;
+    cmd.arg(file_name_to_path("doc_comments"));
+    cmd.assert().success().stdout(starts_with("3"));
+
+    Ok(())
+}
+
+#[test]
+fn unicode() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("unicode"));
+    cmd.assert().success().stdout(starts_with("Hi 😀 ☕"));
+
+    Ok(())
+}
+
+#[test]
+fn unicode_columns() {
+    assert_eq!(
+        tokenize("\"日本\" naïve").unwrap(),
+        vec![
+            Token(TokenValue::STR("日本".to_string()), 1, 1, 0),
+            Token(TokenValue::ID("naïve".to_string()), 1, 6, 9),
+        ]
+    );
+}
+
+#[test]
+fn err_invalid_character_escape() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-invalid_character_escape"));
+    cmd.assert().failure().stderr(starts_with(
+        TokenizerError::InvalidCharacterEscape("\\u{D800}".to_string()).to_string(),
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::{fs, process::Command};
+
+use assert_cmd::prelude::*;
+use linger::{
+    error::TypeError,
+    parser::{parse_declarations, Span},
+    tokenizer::{tokenize, Operator},
+    typecheck::{typecheck, Type},
+};
+use predicates::prelude::predicate::str::contains;
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/typecheck/{}.ling", s);
+}
+
+/// Returns the type errors found in the typecheck test program named `s`.
+fn file_name_to_errors(s: &str) -> Vec<(Span, TypeError)> {
+    let source = fs::read_to_string(file_name_to_path(s)).expect("test program should exist");
+    let tokens = tokenize(source.as_str()).expect("test program should tokenize");
+    let declarations = parse_declarations(tokens.as_slice()).expect("test program should parse");
+    typecheck(&declarations)
+}
+
+fn span(line: usize, col: usize) -> Span {
+    Span { line, col }
+}
+
+fn list(ty: Type) -> Type {
+    Type::List(Box::new(ty))
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+#[test]
+fn annotated() {
+    assert_eq!(file_name_to_errors("annotated"), vec![]);
+}
+
+#[test]
+fn annotated_runs() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("annotated"));
+    cmd.assert().success().stdout("ada 42");
+
+    Ok(())
+}
+
+#[test]
+fn mismatches() {
+    assert_eq!(
+        file_name_to_errors("mismatches"),
+        vec![
+            (span(6, 3), TypeError::Mismatch(Type::Str, Type::Num)),
+            (
+                span(10, 3),
+                TypeError::Mismatch(list(Type::Str), list(Type::Num))
+            ),
+            (span(11, 3), TypeError::Mismatch(Type::Num, Type::Str)),
+            (span(12, 3), TypeError::Mismatch(Type::Num, Type::Str)),
+            (span(13, 3), TypeError::Mismatch(Type::Str, Type::Num)),
+        ]
+    );
+}
+
+#[test]
+fn params() {
+    assert_eq!(
+        file_name_to_errors("params"),
+        vec![
+            (span(5, 1), TypeError::Mismatch(Type::Num, Type::Str)),
+            (span(10, 3), TypeError::Mismatch(Type::Str, Type::Num)),
+            (span(10, 3), TypeError::Mismatch(Type::Str, Type::Num)),
+        ]
+    );
+}
+
+#[test]
+fn inferred() {
+    assert_eq!(
+        file_name_to_errors("inferred"),
+        vec![
+            (span(8, 3), TypeError::Mismatch(Type::Bool, Type::Num)),
+            (
+                span(9, 5),
+                TypeError::BadOperands(Operator::Minus, Type::Str, Type::Num)
+            ),
+            (span(11, 3), TypeError::NotIndexable(Type::Num)),
+        ]
+    );
+}
+
+#[test]
+fn unannotated() {
+    assert_eq!(file_name_to_errors("unannotated"), vec![]);
+}
+
+#[test]
+fn unannotated_runs() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("unannotated"));
+    cmd.assert().success().stdout("3 yes 1");
+
+    Ok(())
+}
+
+#[test]
+fn err_unknown_type() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg(file_name_to_path("err-unknown_type"));
+    cmd.assert()
+        .failure()
+        .stderr(contains("unknown type \"number\""));
+
+    Ok(())
+}
+
+#[test]
+fn check_type_error() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check").arg(file_name_to_path("mismatches"));
+    cmd.assert().failure().stderr(contains(
+        "mismatches.ling:11:3: error: expected type \"num\" but found \"str\"",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn check_annotated() -> TestResult {
+    let mut cmd = Command::cargo_bin("linger-core")?;
+
+    cmd.arg("check").arg(file_name_to_path("annotated"));
+    cmd.assert().success().stderr("");
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+};
+
+use linger::{
+    parser::{parse_declarations, parse_expression, parse_program},
+    tokenizer::tokenize,
+};
+
+fn file_name_to_path(s: &str) -> String {
+    return format!("test_programs/unparse/{}.ling", s);
+}
+
+fn linger_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir).expect("directory should be readable") {
+        let path = entry.expect("directory entry should be readable").path();
+        if path.is_dir() {
+            files.append(&mut linger_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "ling") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    return files;
+}
+
+/// Returns the desugared program in `source` printed as source, or `None` if `source` is not a
+/// valid program.
+fn printed_program(source: &str) -> Option<String> {
+    let tokens = tokenize(source).ok()?;
+    let program = parse_program(tokens.as_slice()).ok()?;
+    Some(program.to_string())
+}
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+/// The stack size of the main thread on Linux, which the `linger` binary runs programs on.
+const MAIN_THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+#[test]
+fn desugared_program_shows_lowered_sugar() -> TestResult {
+    let source = fs::read_to_string(file_name_to_path("sugar"))?;
+    let desugared = fs::read_to_string(file_name_to_path("sugar.desugared"))?;
+
+    assert_eq!(printed_program(&source).unwrap() + "\n", desugared);
+
+    Ok(())
+}
+
+#[test]
+fn sugared_statement_prints_as_written() -> TestResult {
+    let source = fs::read_to_string(file_name_to_path("sugar"))?;
+    let tokens = tokenize(&source).unwrap();
+    let declarations = parse_declarations(tokens.as_slice()).unwrap();
+    let main = declarations
+        .procedures
+        .iter()
+        .find(|proc| proc.name == "main")
+        .unwrap();
+
+    let (_, body) = source.split_once("proc main() ").unwrap();
+    assert_eq!(main.body.to_string(), body.trim_end());
+
+    Ok(())
+}
+
+#[test]
+fn expressions_have_minimal_parentheses() {
+    for source in [
+        "a + b * c",
+        "(a + b) * c",
+        "a - (b - c)",
+        "a - b - c",
+        "-(a + b)",
+        "!f(a)[0].x",
+        "(a || b) && c",
+        "(x) -> (y) -> x + y",
+        "((x) -> x)(1)",
+        "P { x: 1, y: -2 }",
+        "match (a) {\n  _ -> 1\n} + 1",
+    ] {
+        let tokens = tokenize(source).unwrap();
+        let expr = parse_expression(tokens.as_slice()).unwrap();
+        assert_eq!(expr.to_string(), source);
+    }
+}
+
+#[test]
+fn printed_programs_parse_to_the_same_program() {
+    // the programs under `test_programs/fuzz/` which are nested up to the limit of the parser
+    // need more stack than a test thread has
+    let test = thread::Builder::new()
+        .stack_size(MAIN_THREAD_STACK_SIZE)
+        .spawn(|| {
+            for path in linger_files(Path::new("test_programs")) {
+                let source = fs::read_to_string(&path).unwrap();
+                let Some(printed) = printed_program(&source) else {
+                    continue;
+                };
+                assert_eq!(
+                    printed_program(&printed).as_deref(),
+                    Some(printed.as_str()),
+                    "{} does not print as a valid program:\n{printed}",
+                    path.display()
+                );
+            }
+        })
+        .expect("test thread should start");
+
+    test.join().expect("test thread should not panic");
+}